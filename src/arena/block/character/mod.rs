@@ -2,7 +2,7 @@
 use super::util::prelude::*;
 
 use super::super::resource::ImageData;
-use super::chat_message::{self, Message};
+use super::chat_message::{self, Message, ParseError};
 use super::util::{Pack, PackDepth};
 use super::Property;
 use super::{BlockMut, BlockRef};
@@ -11,12 +11,18 @@ use crate::libs::select_list::SelectList;
 use lazy_static::lazy_static;
 use regex::Regex;
 
+pub mod sheet;
+pub mod template;
+
+pub use template::Template;
+
 lazy_static! {
     static ref SECTION: Regex = Regex::new(r"\A//---\s*(.*)(\n|\z)").unwrap();
     static ref SUB_SECTION: Regex = Regex::new(r"\A//----+\s*(.*)(\n|\z)").unwrap();
     static ref DEFINITION: Regex = Regex::new(r"\A//(.+)=((.*\\\n)*(.*))(\n|\z)").unwrap();
     static ref LINE: Regex = Regex::new(r"\A(.*)(\n|\z)").unwrap();
     static ref NL: Regex = Regex::new(r"([^\\])(\\\\)*\\n").unwrap();
+    static ref EXPRESSION: Regex = Regex::new(r"(?:\A|\s)@(\S+)").unwrap();
 }
 
 block! {
@@ -166,7 +172,7 @@ impl StandingTexture {
 block! {
     [pub Description(constructor, pack)]
     raw: String = String::new();
-    data: Message = Message::from_str("");
+    data: Message = Message::from(vec![]);
 }
 
 impl Description {
@@ -175,10 +181,14 @@ impl Description {
     }
 
     pub fn set_raw(&mut self, description: String) {
-        self.data = Message::from_str(&description);
+        self.data = Message::from_str_or_text(&description);
         self.raw = description;
     }
 
+    pub fn parse_error(&self) -> Option<ParseError> {
+        Message::from_str(&self.raw).err()
+    }
+
     pub fn data(&self) -> &Message {
         &self.data
     }
@@ -203,6 +213,8 @@ block! {
     description: Description = Description::new();
     is_fixed_position: bool = false;
     properties: Vec<BlockMut<Property>> = vec![];
+    speech: Option<(String, chrono::DateTime<chrono::Utc>)> = None;
+    speech_duration: f64 = 10.0;
 }
 
 impl Character {
@@ -286,6 +298,39 @@ impl Character {
         .0
     }
 
+    pub fn speech(&self) -> Option<&(String, chrono::DateTime<chrono::Utc>)> {
+        self.speech.as_ref()
+    }
+
+    pub fn set_speech(&mut self, speech: &str) {
+        let lines: Vec<_> = speech
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .take(3)
+            .map(|line| {
+                if line.chars().count() > 40 {
+                    format!("{}…", line.chars().take(40).collect::<String>())
+                } else {
+                    String::from(line)
+                }
+            })
+            .collect();
+
+        self.speech = if lines.is_empty() {
+            None
+        } else {
+            Some((lines.join("\n"), chrono::Utc::now()))
+        };
+    }
+
+    pub fn speech_duration(&self) -> f64 {
+        self.speech_duration
+    }
+
+    pub fn set_speech_duration(&mut self, speech_duration: f64) {
+        self.speech_duration = speech_duration;
+    }
+
     pub fn chatpallet(&self) -> &ChatPallet {
         &self.chatpallet
     }
@@ -310,6 +355,22 @@ impl Character {
         self.textures.set_selected_idx(tex_idx);
     }
 
+    pub fn find_expression(&self, text: &str) -> Option<(usize, String)> {
+        for captures in EXPRESSION.captures_iter(text) {
+            let name = unwrap!(captures.get(1); None);
+            if let Some(tex_idx) = self
+                .textures
+                .iter()
+                .position(|texture| texture.name() == name.as_str())
+            {
+                let tag = unwrap!(captures.get(0); None);
+                let text = format!("{}{}", &text[..tag.start()], &text[tag.end()..]);
+                return Some((tex_idx, String::from(text.trim())));
+            }
+        }
+        None
+    }
+
     pub fn set_texture_image(&mut self, tex_idx: usize, image: Option<BlockRef<ImageData>>) {
         if let Some(texture) = self.textures.get_mut(tex_idx) {
             texture.image = image;
@@ -343,6 +404,14 @@ impl Character {
         &self.properties
     }
 
+    pub fn token_gauges(&self, client_id: &String) -> Vec<super::property::TokenGauge> {
+        self.properties
+            .iter()
+            .filter_map(|prop| prop.map(|prop| prop.token_gauges(client_id)))
+            .flatten()
+            .collect()
+    }
+
     pub fn push_property(&mut self, property: BlockMut<Property>) {
         self.properties.push(property);
     }
@@ -361,7 +430,7 @@ impl Character {
         |ref_name: &String| {
             for (pat, text) in self.chatpallet().defs() {
                 if pat.is_match(ref_name) {
-                    let message = Message::from_str(pat.replace(ref_name, text).as_ref());
+                    let message = Message::from_str_or_text(pat.replace(ref_name, text).as_ref());
                     return message;
                 }
             }
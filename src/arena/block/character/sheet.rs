@@ -0,0 +1,215 @@
+use super::template::{self, PropertyTemplate, Template, ValueTemplate};
+use serde::Deserialize;
+use serde_json::Value as Json;
+
+const MAPPINGS: [(&str, &str); 2] = [
+    (
+        "ytsheet2_sword_world_2.toml",
+        include_str!("./sheets/ytsheet2_sword_world_2.toml"),
+    ),
+    (
+        "charasheet_cthulhu_6th.toml",
+        include_str!("./sheets/charasheet_cthulhu_6th.toml"),
+    ),
+];
+
+#[derive(Clone, Deserialize)]
+pub struct Mapping {
+    pub name: String,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub detect: Vec<String>,
+    #[serde(default)]
+    pub character_name: Option<String>,
+    #[serde(default)]
+    pub chatpallet: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<FieldMapping>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct FieldMapping {
+    pub path: Vec<String>,
+    pub key: String,
+    #[serde(default)]
+    pub max: Option<String>,
+}
+
+pub struct Sheet {
+    pub name: Option<String>,
+    pub template: Template,
+}
+
+pub fn mappings() -> Vec<Mapping> {
+    MAPPINGS
+        .iter()
+        .filter_map(|(file_name, mapping)| match Mapping::parse(mapping) {
+            Ok(mapping) => Some(mapping),
+            Err(err) => {
+                web_sys::console::error_1(
+                    &format!("対応表{}を読み込めません：{}", file_name, err).into(),
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn import(custom_mappings: &[Mapping], text: &str) -> Result<Sheet, String> {
+    let data = serde_json::from_str::<Json>(text).map_err(|err| err.to_string())?;
+    let mappings = mappings();
+
+    // 追加された対応表を組み込みのものより優先する
+    custom_mappings
+        .iter()
+        .chain(mappings.iter())
+        .find(|mapping| mapping.is_match(&data))
+        .map(|mapping| mapping.import(&data))
+        .ok_or_else(|| String::from("対応するキャラクターシートの形式がありません"))
+}
+
+impl Mapping {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mapping: Self = toml::from_str(text).map_err(|err| err.to_string())?;
+        if mapping.detect.is_empty() {
+            Err(String::from("detectが指定されていません"))
+        } else {
+            Ok(mapping)
+        }
+    }
+
+    pub fn is_match(&self, data: &Json) -> bool {
+        !self.detect.is_empty() && self.detect.iter().all(|key| data.get(key).is_some())
+    }
+
+    pub fn import(&self, data: &Json) -> Sheet {
+        let mut template = self
+            .template
+            .as_ref()
+            .and_then(|name| {
+                template::presets()
+                    .into_iter()
+                    .find(|template| template.name == *name)
+            })
+            .unwrap_or_else(|| Template {
+                name: self.name.clone(),
+                system: String::new(),
+                chatpallet: None,
+                description: None,
+                properties: vec![],
+            });
+
+        if let Some(chatpallet) = self.chatpallet.as_ref().and_then(|key| text(data, key)) {
+            template.chatpallet = Some(chatpallet);
+        }
+
+        if let Some(description) = self.description.as_ref().and_then(|key| text(data, key)) {
+            template.description = Some(description);
+        }
+
+        for field in &self.fields {
+            if let Some(value) = text(data, &field.key) {
+                let max = field.max.as_ref().and_then(|key| text(data, key));
+                if let Some(prop) = find_or_push(&mut template.properties, &field.path) {
+                    set_value(prop, value, max);
+                }
+            }
+        }
+
+        Sheet {
+            name: self.character_name.as_ref().and_then(|key| text(data, key)),
+            template,
+        }
+    }
+}
+
+fn text(data: &Json, key: &str) -> Option<String> {
+    match data.get(key)? {
+        Json::String(text) => Some(text.replace("<br>", "\n").replace("&lt;br&gt;", "\n")),
+        Json::Number(number) => Some(number.to_string()),
+        Json::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn find_or_push<'a>(
+    props: &'a mut Vec<PropertyTemplate>,
+    path: &[String],
+) -> Option<&'a mut PropertyTemplate> {
+    let (name, rest) = path.split_first()?;
+
+    let idx = if let Some(idx) = props.iter().position(|prop| prop.name == *name) {
+        idx
+    } else {
+        props.push(PropertyTemplate {
+            name: name.clone(),
+            view: template::ViewTemplate::List,
+            is_shown_on_token: false,
+            values: vec![],
+            rows: vec![],
+            children: vec![],
+        });
+        props.len() - 1
+    };
+
+    if rest.is_empty() {
+        props.get_mut(idx)
+    } else {
+        find_or_push(&mut props[idx].children, rest)
+    }
+}
+
+fn set_value(prop: &mut PropertyTemplate, value: String, max: Option<String>) {
+    let number = value.trim().parse::<f64>().ok();
+    let max = max.and_then(|max| max.trim().parse::<f64>().ok());
+
+    let value = match (prop.values.first(), number) {
+        (Some(ValueTemplate::NumberMinMax { min, .. }), Some(number)) => {
+            ValueTemplate::NumberMinMax {
+                value: number,
+                min: *min,
+                max: max.unwrap_or(number),
+            }
+        }
+        (Some(ValueTemplate::NumberMid { mid, .. }), Some(number)) => ValueTemplate::NumberMid {
+            value: number,
+            mid: *mid,
+        },
+        (Some(ValueTemplate::Note { .. }), _) => ValueTemplate::Note { value },
+        (Some(ValueTemplate::Normal { .. }), _) => ValueTemplate::Normal { value },
+        (_, Some(number)) => ValueTemplate::Number { value: number },
+        (_, None) => ValueTemplate::Normal { value },
+    };
+
+    if let Some(head) = prop.values.first_mut() {
+        *head = value;
+    } else {
+        prop.values.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mappings_are_valid() {
+        let presets = template::presets()
+            .into_iter()
+            .map(|template| template.name)
+            .collect::<Vec<_>>();
+
+        for (file_name, mapping) in &MAPPINGS {
+            let mapping = match Mapping::parse(mapping) {
+                Ok(mapping) => mapping,
+                Err(err) => panic!("{}: {}", file_name, err),
+            };
+            if let Some(template) = &mapping.template {
+                assert!(presets.contains(template), "{}: {}", file_name, template);
+            }
+        }
+    }
+}
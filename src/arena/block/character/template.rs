@@ -0,0 +1,330 @@
+#[allow(unused_imports)]
+use super::super::util::prelude::*;
+
+use super::super::property::{self, Property, PropertyView};
+use super::super::BlockMut;
+use super::Character;
+use crate::libs::element::download;
+use crate::libs::random_id::U128Id;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const PRESETS: [(&str, &str); 2] = [
+    (
+        "sword_world_2.toml",
+        include_str!("./templates/sword_world_2.toml"),
+    ),
+    (
+        "cthulhu_6th.toml",
+        include_str!("./templates/cthulhu_6th.toml"),
+    ),
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    #[serde(default)]
+    pub system: String,
+    #[serde(default)]
+    pub chatpallet: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub properties: Vec<PropertyTemplate>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PropertyTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub view: ViewTemplate,
+    #[serde(default)]
+    pub is_shown_on_token: bool,
+    #[serde(default)]
+    pub values: Vec<ValueTemplate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rows: Vec<RowTemplate>,
+    #[serde(default)]
+    pub children: Vec<PropertyTemplate>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RowTemplate {
+    #[serde(default)]
+    pub values: Vec<ValueTemplate>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewTemplate {
+    Board,
+    List,
+}
+
+impl Default for ViewTemplate {
+    fn default() -> Self {
+        Self::List
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ValueTemplate {
+    Number {
+        #[serde(default)]
+        value: f64,
+    },
+    NumberMinMax {
+        #[serde(default)]
+        value: f64,
+        #[serde(default)]
+        min: f64,
+        #[serde(default)]
+        max: f64,
+    },
+    NumberMid {
+        #[serde(default)]
+        value: f64,
+        #[serde(default)]
+        mid: f64,
+    },
+    Normal {
+        #[serde(default)]
+        value: String,
+    },
+    Note {
+        #[serde(default)]
+        value: String,
+    },
+    Check {
+        #[serde(default)]
+        value: bool,
+    },
+    Select {
+        #[serde(default)]
+        value: usize,
+        #[serde(default)]
+        items: Vec<String>,
+    },
+    Formula {
+        #[serde(default)]
+        value: String,
+    },
+}
+
+impl From<&ValueTemplate> for property::Value {
+    fn from(value: &ValueTemplate) -> Self {
+        match value {
+            ValueTemplate::Number { value } => Self::Number(*value),
+            ValueTemplate::NumberMinMax { value, min, max } => {
+                Self::NumberMinMax(*value, *min, *max)
+            }
+            ValueTemplate::NumberMid { value, mid } => Self::NumberMid(*value, *mid),
+            ValueTemplate::Normal { value } => Self::Normal(value.clone()),
+            ValueTemplate::Note { value } => Self::Note(value.clone()),
+            ValueTemplate::Check { value } => Self::Check(*value),
+            ValueTemplate::Select { value, items } => Self::Select(*value, items.clone()),
+            ValueTemplate::Formula { value } => Self::Formula(value.clone()),
+        }
+    }
+}
+
+impl From<&property::Value> for ValueTemplate {
+    fn from(value: &property::Value) -> Self {
+        match value {
+            property::Value::Number(value) => Self::Number { value: *value },
+            property::Value::NumberMinMax(value, min, max) => Self::NumberMinMax {
+                value: *value,
+                min: *min,
+                max: *max,
+            },
+            property::Value::NumberMid(value, mid) => Self::NumberMid {
+                value: *value,
+                mid: *mid,
+            },
+            property::Value::Normal(value) => Self::Normal {
+                value: value.clone(),
+            },
+            property::Value::Note(value) => Self::Note {
+                value: value.clone(),
+            },
+            property::Value::Check(value) => Self::Check { value: *value },
+            property::Value::Select(value, items) => Self::Select {
+                value: *value,
+                items: items.clone(),
+            },
+            property::Value::Formula(value) => Self::Formula {
+                value: value.clone(),
+            },
+        }
+    }
+}
+
+pub fn presets() -> Vec<Template> {
+    PRESETS
+        .iter()
+        .filter_map(|(file_name, preset)| match Template::parse(preset) {
+            Ok(template) => Some(template),
+            Err(err) => {
+                web_sys::console::error_1(
+                    &format!("テンプレート{}を読み込めません：{}", file_name, err).into(),
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+impl Template {
+    pub fn from_toml(text: &str) -> Option<Self> {
+        toml::from_str(text).ok()
+    }
+
+    pub fn from_json(text: &str) -> Option<Self> {
+        serde_json::from_str(text).ok()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.trim_start().starts_with('{') {
+            serde_json::from_str(text).map_err(|err| err.to_string())
+        } else {
+            toml::from_str(text).map_err(|err| err.to_string())
+        }
+    }
+
+    pub fn to_toml(&self) -> Option<String> {
+        toml::to_string(self).ok()
+    }
+
+    pub fn to_json(&self) -> Option<String> {
+        serde_json::to_string_pretty(self).ok()
+    }
+
+    pub fn download_as_toml(&self) {
+        if let Some(data) = self.to_toml() {
+            download(
+                &format!("{}.toml", self.name),
+                "application/toml;charset=utf-8",
+                &data,
+            );
+        }
+    }
+
+    pub fn download_as_json(&self) {
+        if let Some(data) = self.to_json() {
+            download(
+                &format!("{}.json", self.name),
+                "application/json;charset=utf-8",
+                &data,
+            );
+        }
+    }
+
+    pub fn from_character(character: &Character) -> Self {
+        Self {
+            name: character.name().clone(),
+            system: String::new(),
+            chatpallet: Some(character.chatpallet().data().clone()),
+            description: Some(character.description().raw().clone()),
+            properties: character
+                .properties()
+                .iter()
+                .filter_map(|prop| prop.map(PropertyTemplate::from_property))
+                .collect(),
+        }
+    }
+
+    pub fn apply(&self, arena: &mut ArenaMut, character: &mut Character) -> HashSet<U128Id> {
+        let mut inserted = HashSet::new();
+
+        if let Some(chatpallet) = &self.chatpallet {
+            character.set_chatpallet(chatpallet.clone());
+        }
+
+        if let Some(description) = &self.description {
+            character.set_description(description.clone());
+        }
+
+        for prop in &self.properties {
+            character.push_property(prop.instantiate(arena, &mut inserted));
+        }
+
+        inserted
+    }
+}
+
+impl PropertyTemplate {
+    fn from_property(prop: &Property) -> Self {
+        let mut rows = prop.data().values().iter();
+        Self {
+            name: prop.name().clone(),
+            view: match prop.view() {
+                PropertyView::Board => ViewTemplate::Board,
+                PropertyView::List => ViewTemplate::List,
+            },
+            is_shown_on_token: prop.is_shown_on_token(),
+            values: rows
+                .next()
+                .map(|row| row.iter().map(ValueTemplate::from).collect())
+                .unwrap_or_default(),
+            rows: rows
+                .map(|row| RowTemplate {
+                    values: row.iter().map(ValueTemplate::from).collect(),
+                })
+                .collect(),
+            children: prop
+                .children()
+                .iter()
+                .filter_map(|child| child.map(Self::from_property))
+                .collect(),
+        }
+    }
+
+    fn instantiate(
+        &self,
+        arena: &mut ArenaMut,
+        inserted: &mut HashSet<U128Id>,
+    ) -> BlockMut<Property> {
+        let mut prop = Property::new();
+
+        prop.set_name(self.name.clone());
+        prop.set_view(match self.view {
+            ViewTemplate::Board => PropertyView::Board,
+            ViewTemplate::List => PropertyView::List,
+        });
+        prop.set_is_shown_on_token(self.is_shown_on_token);
+
+        let rows = std::iter::once(&self.values).chain(self.rows.iter().map(|row| &row.values));
+        for (row_idx, row) in rows.enumerate() {
+            if row_idx > 0 {
+                prop.data_mut().push_row();
+            }
+            for value in row {
+                prop.data_mut()
+                    .push_value(row_idx, property::Value::from(value));
+            }
+        }
+
+        for child in &self.children {
+            prop.push_child(child.instantiate(arena, inserted));
+        }
+
+        let prop = arena.insert(prop);
+        inserted.insert(prop.id());
+        prop
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_are_valid() {
+        for (file_name, preset) in &PRESETS {
+            if let Err(err) = Template::parse(preset) {
+                panic!("{}: {}", file_name, err);
+            }
+        }
+    }
+}
@@ -15,6 +15,16 @@ impl ChatChannel {
         self.messages.push(message);
     }
 
+    pub fn remove_message(&mut self, block_id: &U128Id) {
+        if let Some(message_idx) = self
+            .messages
+            .iter()
+            .position(|message| message.id() == *block_id)
+        {
+            self.messages.remove(message_idx);
+        }
+    }
+
     pub fn messages(&self) -> &Vec<BlockMut<ChatMessage>> {
         &self.messages
     }
@@ -0,0 +1,160 @@
+#[allow(unused_imports)]
+use super::super::util::prelude::*;
+
+use super::super::util::{Pack, PackDepth};
+use super::Sender;
+use crate::libs::bcdice::js::CommandResult;
+
+block! {
+    [pub DiceRand(constructor, pack)]
+    (kind): String;
+    (sides): u32;
+    (value): u32;
+}
+
+impl DiceRand {
+    pub fn kind(&self) -> &String {
+        &self.kind
+    }
+
+    pub fn sides(&self) -> u32 {
+        self.sides
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+}
+
+// 出目を決めた種と各ピアの乱数。受け取った側はこれで振り直して結果を照合する
+block! {
+    [pub DiceProof(constructor, pack)]
+    (roll): U128Id;
+    (index): u32;
+    (seed): String;
+    (entropies): Vec<(String, String)>;
+    (system): String;
+    (command): String;
+}
+
+impl DiceProof {
+    pub fn roll(&self) -> &U128Id {
+        &self.roll
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn seed(&self) -> &String {
+        &self.seed
+    }
+
+    pub fn entropies(&self) -> &Vec<(String, String)> {
+        &self.entropies
+    }
+
+    pub fn system(&self) -> &String {
+        &self.system
+    }
+
+    pub fn command(&self) -> &String {
+        &self.command
+    }
+
+    pub fn token(&self) -> String {
+        format!("{}-{}-{}", self.roll, self.index, self.seed)
+    }
+}
+
+impl Clone for DiceProof {
+    fn clone(&self) -> Self {
+        Self {
+            roll: U128Id::clone(&self.roll),
+            index: self.index,
+            seed: self.seed.clone(),
+            entropies: self.entropies.clone(),
+            system: self.system.clone(),
+            command: self.command.clone(),
+        }
+    }
+}
+
+block! {
+    [pub DiceRoll(constructor, pack)]
+    (roller): Sender;
+    rands: Vec<DiceRand> = vec![];
+    success: bool = false;
+    failure: bool = false;
+    critical: bool = false;
+    fumble: bool = false;
+    proof: Option<DiceProof> = None;
+}
+
+impl DiceRoll {
+    pub fn from_command_result(roller: Sender, command_result: &CommandResult) -> Self {
+        let mut dice_roll = Self::new(roller);
+        dice_roll.rands = command_result
+            .detailed_rands
+            .iter()
+            .filter(|rand| rand.sides > 0)
+            .map(|rand| {
+                DiceRand::new(
+                    rand.kind.clone(),
+                    rand.sides as u32,
+                    rand.value.max(0) as u32,
+                )
+            })
+            .collect();
+        dice_roll.success = command_result.success;
+        dice_roll.failure = command_result.failure;
+        dice_roll.critical = command_result.critical;
+        dice_roll.fumble = command_result.fumble;
+        dice_roll
+    }
+
+    pub fn roller(&self) -> &Sender {
+        &self.roller
+    }
+
+    pub fn rands(&self) -> &Vec<DiceRand> {
+        &self.rands
+    }
+
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    pub fn failure(&self) -> bool {
+        self.failure
+    }
+
+    pub fn critical(&self) -> bool {
+        self.critical
+    }
+
+    pub fn fumble(&self) -> bool {
+        self.fumble
+    }
+
+    pub fn proof(&self) -> Option<&DiceProof> {
+        self.proof.as_ref()
+    }
+
+    pub fn set_proof(&mut self, proof: Option<DiceProof>) {
+        self.proof = proof;
+    }
+
+    pub fn is_same_roll(&self, other: &Self) -> bool {
+        self.rands.len() == other.rands.len()
+            && self
+                .rands
+                .iter()
+                .zip(other.rands.iter())
+                .all(|(a, b)| a.kind == b.kind && a.sides == b.sides && a.value == b.value)
+            && self.success == other.success
+            && self.failure == other.failure
+            && self.critical == other.critical
+            && self.fumble == other.fumble
+    }
+}
@@ -0,0 +1,110 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref MACRO_PARAM: Regex = Regex::new(
+        r"\{\$([^:=}]+)(?::(number|select|property)(?:\(([^)]*)\))?)?(?:=([^}]*))?\}"
+    )
+    .unwrap();
+}
+
+#[derive(Clone)]
+pub enum MacroParamKind {
+    Text,
+    Number,
+    Select(Vec<String>),
+    Property,
+}
+
+#[derive(Clone)]
+pub struct MacroParam {
+    name: String,
+    kind: MacroParamKind,
+    default: String,
+}
+
+impl MacroParam {
+    pub fn parse_all(text: &str) -> Vec<Self> {
+        let mut params: Vec<Self> = vec![];
+
+        for captures in MACRO_PARAM.captures_iter(text) {
+            let name = param_name(&captures);
+            if params.iter().any(|param| param.name == name) {
+                continue;
+            }
+
+            let kind = match captures.get(2).map(|kind| kind.as_str()) {
+                Some("number") => MacroParamKind::Number,
+                Some("select") => MacroParamKind::Select(
+                    captures
+                        .get(3)
+                        .map(|list| {
+                            list.as_str()
+                                .split(',')
+                                .map(|item| String::from(item.trim()))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                ),
+                Some("property") => MacroParamKind::Property,
+                _ => MacroParamKind::Text,
+            };
+
+            let default = match (captures.get(4), &kind) {
+                (Some(default), _) => String::from(default.as_str()),
+                (None, MacroParamKind::Number) => String::from("0"),
+                (None, MacroParamKind::Select(list)) => list.first().cloned().unwrap_or_default(),
+                (None, _) => String::new(),
+            };
+
+            params.push(Self {
+                name,
+                kind,
+                default,
+            });
+        }
+
+        params
+    }
+
+    pub fn substitute(text: &str, params: &[Self], values: &[String]) -> String {
+        let values: HashMap<_, _> = params
+            .iter()
+            .zip(values.iter())
+            .map(|(param, value)| (param.name.clone(), value))
+            .collect();
+
+        MACRO_PARAM
+            .replace_all(text, |captures: &Captures| {
+                let value = values
+                    .get(&param_name(captures))
+                    .map(|value| String::clone(value))
+                    .unwrap_or_default();
+                match captures.get(2).map(|kind| kind.as_str()) {
+                    Some("property") if !value.is_empty() => format!("{{{}}}", value),
+                    _ => value,
+                }
+            })
+            .to_string()
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn kind(&self) -> &MacroParamKind {
+        &self.kind
+    }
+
+    pub fn default(&self) -> &String {
+        &self.default
+    }
+}
+
+fn param_name(captures: &Captures) -> String {
+    captures
+        .get(1)
+        .map(|name| String::from(name.as_str().trim()))
+        .unwrap_or_default()
+}
@@ -1,6 +1,8 @@
+use super::super::property;
+use super::parser::ExprError;
 use super::{
-    Argument, BlockMut, Command, CommandResult, GameSystemClass, Message, MessageToken, Property,
-    Reference,
+    Argument, BlockMut, Command, CommandResult, Expr, GameSystemClass, Message, MessageToken,
+    Property, Reference,
 };
 use std::collections::HashMap;
 
@@ -119,13 +121,43 @@ fn map_reference(
     let option =
         option.map(|option| map_message(props, refs, var_nums, descriptions, option).to_string());
 
-    let target = if let Some(value) = props.iter().find_map(|prop| {
-        prop.map(|prop| prop.ref_value(name.iter().collect(), args.iter().collect()))
-            .unwrap_or_default()
+    let target = match reference_target(&text, option.as_ref(), || {
+        property::find_value(props, name.iter().collect(), args.iter().collect())
     }) {
-        Message::from_str(&value.to_string_with_option(option.as_ref()))
-    } else {
-        refs(&text)
+        ReferenceTarget::Value(value) => {
+            let value = if option.as_ref().map(String::as_str) == Some("formula") {
+                Ok(value)
+            } else {
+                value.compute(props)
+            };
+            match value {
+                Ok(value) => {
+                    Message::from_str_or_text(&value.to_string_with_option(option.as_ref()))
+                }
+                Err(err) => error_message(err.to_string()),
+            }
+        }
+        ReferenceTarget::Expr(expr) => {
+            let message = refs(&text);
+            if message.len() > 0 {
+                message
+            } else {
+                match expr.eval(&mut |reference| ref_number(props, refs, reference)) {
+                    Ok(value) => Message::from(vec![MessageToken::Text(value.to_string())]),
+                    Err(ExprError::MissingReference(..)) if props.is_empty() => message,
+                    Err(err) => error_message(err.to_string()),
+                }
+            }
+        }
+        ReferenceTarget::Missing => {
+            let message = refs(&text);
+            // プロパティを持たない発言者の場合は、従来どおり参照先が無ければ空にする
+            if message.len() > 0 || props.is_empty() {
+                message
+            } else {
+                error_message(format!("{}が見つかりません", text))
+            }
+        }
     };
 
     let message = map_message(props, refs, var_nums, descriptions, target);
@@ -133,6 +165,62 @@ fn map_reference(
     message
 }
 
+enum ReferenceTarget {
+    Value(property::Value),
+    Expr(Expr),
+    Missing,
+}
+
+fn reference_target(
+    text: &str,
+    option: Option<&String>,
+    find: impl FnOnce() -> Option<property::Value>,
+) -> ReferenceTarget {
+    // 演算子を含む名前のプロパティもあるため、式として評価するより先に名前のまま探す
+    if option.map_or(true, |option| is_known_option(option)) {
+        if let Some(value) = find() {
+            return ReferenceTarget::Value(value);
+        }
+    }
+
+    match Expr::from_str(text) {
+        Some(expr) if !expr.is_reference() => ReferenceTarget::Expr(expr),
+        _ => ReferenceTarget::Missing,
+    }
+}
+
+fn is_known_option(option: &str) -> bool {
+    match option {
+        "val" | "min" | "max" | "mid" | "idx" | "formula" => true,
+        _ => option.parse::<usize>().is_ok(),
+    }
+}
+
+fn ref_number(
+    props: &Vec<BlockMut<Property>>,
+    refs: &mut dyn FnMut(&String) -> Message,
+    reference: &super::parser::ExprReference,
+) -> Option<f64> {
+    let name = reference.name.iter().collect::<Vec<_>>();
+
+    if let Some(value) = property::find_value(props, name, vec![]) {
+        value
+            .compute(props)
+            .ok()
+            .and_then(|value| value.to_number_with_option(reference.option.as_ref()))
+    } else {
+        refs(&reference.to_string()).to_string().trim().parse().ok()
+    }
+}
+
+fn error_message(text: String) -> Message {
+    Message::from(vec![MessageToken::Command(Command {
+        name: Message::from(vec![MessageToken::Text(String::from("error"))]),
+        args: vec![],
+        text: Message::from(vec![MessageToken::Text(text)]),
+    })])
+}
+
 fn map_argument(
     props: &Vec<BlockMut<Property>>,
     refs: &mut dyn FnMut(&String) -> Message,
@@ -152,3 +240,41 @@ fn map_argument(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(text: &str, option: Option<&str>, names: &[&str]) -> ReferenceTarget {
+        let option = option.map(String::from);
+        let value = if names.contains(&text) {
+            Some(property::Value::Number(1.0))
+        } else {
+            None
+        };
+        reference_target(text, option.as_ref(), || value)
+    }
+
+    #[test]
+    fn test_reference_with_operator_in_name() {
+        let names = ["HP/MP", "攻撃-近接"];
+
+        assert!(matches!(
+            target("HP/MP", None, &names),
+            ReferenceTarget::Value(..)
+        ));
+        assert!(matches!(
+            target("攻撃-近接", None, &names),
+            ReferenceTarget::Value(..)
+        ));
+        assert!(matches!(
+            target("HP/MP", None, &[]),
+            ReferenceTarget::Expr(..)
+        ));
+        assert!(matches!(
+            target("HP.max - HP", Some("max - HP"), &["HP"]),
+            ReferenceTarget::Expr(..)
+        ));
+        assert!(matches!(target("笑", None, &[]), ReferenceTarget::Missing));
+    }
+}
@@ -4,19 +4,30 @@ use super::util::prelude::*;
 use super::super::resource::ImageData;
 use super::util::{Pack, PackDepth};
 use super::Property;
-use super::{BlockMut, BlockRef};
+use super::{BlockMut, BlockRef, Character};
 use crate::libs::bcdice::js::{CommandResult, GameSystemClass};
 use std::collections::HashMap;
 
+pub mod dice_roll;
+pub mod macro_param;
 pub mod map;
 pub mod parser;
+pub mod property_command;
 pub mod roll;
 
+pub use dice_roll::DiceProof;
+pub use dice_roll::DiceRand;
+pub use dice_roll::DiceRoll;
+pub use macro_param::MacroParam;
+pub use macro_param::MacroParamKind;
 pub use parser::Argument;
 pub use parser::Command;
+pub use parser::Expr;
 pub use parser::Message;
 pub use parser::MessageToken;
+pub use parser::ParseError;
 pub use parser::Reference;
+pub use property_command::PropertyCommand;
 
 pub fn map(
     props: &Vec<BlockMut<Property>>,
@@ -29,10 +40,37 @@ pub fn map(
     (message, descriptions)
 }
 
-pub fn roll(game_system_class: &GameSystemClass, message: &Message) -> Vec<CommandResult> {
+pub fn roll(
+    game_system_class: Option<&GameSystemClass>,
+    message: Message,
+) -> (Message, Vec<CommandResult>) {
+    roll_with(|command| eval(game_system_class, command), message)
+}
+
+pub fn eval(game_system_class: Option<&GameSystemClass>, command: &str) -> Option<CommandResult> {
+    if let Some(game_system_class) = game_system_class {
+        game_system_class.eval(command)
+    } else {
+        crate::dicebot::eval(command)
+    }
+}
+
+// 受け取った側が同じ評価器で振り直せるように、評価に使ったシステムを返す
+pub fn system_id(game_system_class: Option<&GameSystemClass>) -> String {
+    if let Some(game_system_class) = game_system_class {
+        game_system_class.id().clone()
+    } else {
+        crate::dicebot::selected().unwrap_or_else(|| String::from("sainome:DiceBot"))
+    }
+}
+
+pub fn roll_with(
+    mut eval: impl FnMut(&str) -> Option<CommandResult>,
+    message: Message,
+) -> (Message, Vec<CommandResult>) {
     let mut command_results = vec![];
-    roll::roll_message(game_system_class, &mut command_results, message);
-    command_results
+    let message = roll::roll_message(&mut eval, &mut command_results, message);
+    (message, command_results)
 }
 
 #[async_trait(?Send)]
@@ -282,6 +320,10 @@ impl Sender {
         self.icon.as_ref()
     }
 
+    pub fn set_icon(&mut self, icon: Option<BlockRef<ImageData>>) {
+        self.icon = icon;
+    }
+
     pub fn name(&self) -> &String {
         &self.name
     }
@@ -291,12 +333,27 @@ impl Sender {
     }
 }
 
+impl Clone for Sender {
+    fn clone(&self) -> Self {
+        Self {
+            client_id: Rc::clone(&self.client_id),
+            icon: self.icon.as_ref().map(BlockRef::clone),
+            name: self.name.clone(),
+            kind: self.kind,
+        }
+    }
+}
+
 block! {
     [pub ChatMessage(constructor, pack)]
     (sender): Sender;
     (timestamp): chrono::DateTime<chrono::Utc>;
     (message): Message;
     reference: Option<BlockRef<Self>> = None;
+    is_edited: bool = false;
+    dice_roll: Option<DiceRoll> = None;
+    source: String = String::new();
+    character: Option<BlockMut<Character>> = None;
 }
 
 impl ChatMessage {
@@ -311,4 +368,45 @@ impl ChatMessage {
     pub fn message(&self) -> &Message {
         &self.message
     }
+
+    pub fn set_message(&mut self, message: Message) {
+        self.message = message;
+        self.is_edited = true;
+    }
+
+    pub fn is_edited(&self) -> bool {
+        self.is_edited
+    }
+
+    pub fn reference(&self) -> Option<&BlockRef<Self>> {
+        self.reference.as_ref()
+    }
+
+    pub fn set_reference(&mut self, reference: Option<BlockRef<Self>>) {
+        self.reference = reference;
+    }
+
+    pub fn source(&self) -> &String {
+        &self.source
+    }
+
+    pub fn set_source(&mut self, source: String) {
+        self.source = source;
+    }
+
+    pub fn character(&self) -> Option<&BlockMut<Character>> {
+        self.character.as_ref()
+    }
+
+    pub fn set_character(&mut self, character: Option<BlockMut<Character>>) {
+        self.character = character;
+    }
+
+    pub fn dice_roll(&self) -> Option<&DiceRoll> {
+        self.dice_roll.as_ref()
+    }
+
+    pub fn set_dice_roll(&mut self, dice_roll: Option<DiceRoll>) {
+        self.dice_roll = dice_roll;
+    }
 }
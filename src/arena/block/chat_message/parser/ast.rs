@@ -1,3 +1,4 @@
+use super::ParseError;
 use crate::libs::bcdice::js::CommandResult;
 
 #[derive(Debug, Clone)]
@@ -35,8 +36,38 @@ impl Message {
         Self(msg_tokens)
     }
 
-    pub fn from_str(text: &str) -> Self {
-        super::message_parser::message(text).unwrap()
+    pub fn from_str(text: &str) -> Result<Self, ParseError> {
+        super::message_parser::message(text).map_err(ParseError::from)
+    }
+
+    pub fn from_str_or_text(text: &str) -> Self {
+        Self::from_str(text)
+            .unwrap_or_else(|_| Self::from(vec![MessageToken::Text(String::from(text))]))
+    }
+
+    pub fn to_plain_text(&self) -> String {
+        self.iter()
+            .map(|message_token| match message_token {
+                MessageToken::Text(text) => text.clone(),
+                MessageToken::Reference(reference) => format!("{}", reference),
+                MessageToken::Command(cmd) => {
+                    let cmd_name = cmd.name.to_string();
+                    if cmd_name == "fas" || cmd_name == "far" || cmd_name == "fab" {
+                        String::new()
+                    } else if cmd_name == "rb" {
+                        let ruby: Vec<_> = cmd
+                            .args
+                            .iter()
+                            .map(|arg| arg.value.to_plain_text())
+                            .collect();
+                        format!("{}《{}》", cmd.text.to_plain_text(), ruby.join(""))
+                    } else {
+                        cmd.text.to_plain_text()
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("")
     }
 
     pub fn map(self, f: impl FnMut(MessageToken) -> Message) -> Self {
@@ -88,6 +119,63 @@ impl Message {
     }
 }
 
+impl Message {
+    // Displayと異なり、テキスト中の \ { } をエスケープしてfrom_strで元に戻せる形にする
+    pub fn to_source(&self) -> String {
+        self.iter()
+            .map(MessageToken::to_source)
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
+
+impl MessageToken {
+    pub fn to_source(&self) -> String {
+        match self {
+            Self::Text(text) => text
+                .replace('\\', r"\\")
+                .replace('{', r"\{")
+                .replace('}', r"\}"),
+            Self::Command(command) => command.to_source(),
+            Self::Reference(reference) => reference.to_source(),
+        }
+    }
+}
+
+impl Command {
+    pub fn to_source(&self) -> String {
+        if self.args.len() > 0 {
+            format!(
+                "{{\\{}[{}]{}}}",
+                self.name.to_source(),
+                self.args
+                    .iter()
+                    .map(Argument::to_source)
+                    .collect::<Vec<_>>()
+                    .join(","),
+                self.text.to_source()
+            )
+        } else {
+            let text = self.text.to_source();
+            if text.len() > 0 {
+                format!("{{\\{} {}}}", self.name.to_source(), text)
+            } else {
+                format!("{{\\{}}}", self.name.to_source())
+            }
+        }
+    }
+}
+
+impl Argument {
+    pub fn to_source(&self) -> String {
+        if let Some(option) = &self.option {
+            format!("{}={}", self.value.to_source(), option.to_source())
+        } else {
+            self.value.to_source()
+        }
+    }
+}
+
 impl std::ops::Deref for Message {
     type Target = Vec<MessageToken>;
 
@@ -150,6 +238,36 @@ impl Reference {
 
         format!("{}{}{}", name, args, option)
     }
+
+    pub fn to_source(&self) -> String {
+        let name = self
+            .name
+            .iter()
+            .map(Message::to_source)
+            .collect::<Vec<_>>()
+            .join("::");
+
+        let args = if self.args.len() > 0 {
+            format!(
+                "[{}]",
+                self.args
+                    .iter()
+                    .map(Argument::to_source)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )
+        } else {
+            String::from("")
+        };
+
+        let option = if let Some(option) = &self.option {
+            format!(".{}", option.to_source())
+        } else {
+            String::from("")
+        };
+
+        format!("{{{}{}{}}}", name, args, option)
+    }
 }
 
 impl std::fmt::Display for Message {
@@ -214,3 +332,27 @@ impl std::fmt::Display for Argument {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_source_round_trip() {
+        let text = r"a\{b\}c\\ {\b 強調\{\}} {HP} {\rb[かな]仮名}";
+        let message = Message::from_str(text).unwrap();
+
+        assert_eq!(message.to_source(), text);
+        assert_eq!(
+            Message::from_str(&message.to_source()).unwrap().to_string(),
+            message.to_string()
+        );
+    }
+
+    #[test]
+    fn test_display_is_plain_text() {
+        let message = Message::from_str(r"a\{b\}c\\").unwrap();
+
+        assert_eq!(message.to_string(), r"a{b}c\");
+    }
+}
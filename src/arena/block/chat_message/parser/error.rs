@@ -0,0 +1,48 @@
+use peg::str::LineCol;
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    pub expected: Vec<String>,
+}
+
+impl ParseError {
+    pub fn range_in<'a>(&self, text: &'a str) -> (&'a str, &'a str, &'a str) {
+        let offset = self.offset.min(text.len());
+        let (before, rest) = text.split_at(offset);
+        let len = rest.chars().next().map(char::len_utf8).unwrap_or(0);
+        let (target, after) = rest.split_at(len);
+        (before, target, after)
+    }
+}
+
+impl From<peg::error::ParseError<LineCol>> for ParseError {
+    fn from(err: peg::error::ParseError<LineCol>) -> Self {
+        let mut expected: Vec<_> = err.expected.tokens().map(String::from).collect();
+        expected.sort();
+        Self {
+            line: err.location.line,
+            column: err.location.column,
+            offset: err.location.offset,
+            expected,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.expected.is_empty() {
+            write!(f, "{}行{}文字目：構文エラー", self.line, self.column)
+        } else {
+            write!(
+                f,
+                "{}行{}文字目：{}が必要です",
+                self.line,
+                self.column,
+                self.expected.join("、")
+            )
+        }
+    }
+}
@@ -0,0 +1,241 @@
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Number(f64),
+    Reference(ExprReference),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone)]
+pub struct ExprReference {
+    pub name: Vec<String>,
+    pub option: Option<String>,
+}
+
+pub enum ExprError {
+    MissingReference(String),
+    DivisionByZero,
+    UnknownFunction(String),
+    CircularReference(String),
+    InvalidFormula(String),
+}
+
+impl Expr {
+    pub fn from_str(text: &str) -> Option<Self> {
+        expr_parser::expr(text).ok()
+    }
+
+    pub fn is_reference(&self) -> bool {
+        match self {
+            Self::Reference(..) => true,
+            _ => false,
+        }
+    }
+
+    pub fn eval(
+        &self,
+        resolve: &mut impl FnMut(&ExprReference) -> Option<f64>,
+    ) -> Result<f64, ExprError> {
+        self.eval_with(&mut |reference| {
+            resolve(reference).ok_or_else(|| ExprError::MissingReference(reference.to_string()))
+        })
+    }
+
+    pub fn eval_with(
+        &self,
+        resolve: &mut impl FnMut(&ExprReference) -> Result<f64, ExprError>,
+    ) -> Result<f64, ExprError> {
+        match self {
+            Self::Number(x) => Ok(*x),
+            Self::Reference(reference) => resolve(reference),
+            Self::Neg(x) => Ok(-x.eval_with(resolve)?),
+            Self::Add(x, y) => Ok(x.eval_with(resolve)? + y.eval_with(resolve)?),
+            Self::Sub(x, y) => Ok(x.eval_with(resolve)? - y.eval_with(resolve)?),
+            Self::Mul(x, y) => Ok(x.eval_with(resolve)? * y.eval_with(resolve)?),
+            Self::Div(x, y) => {
+                let x = x.eval_with(resolve)?;
+                let y = y.eval_with(resolve)?;
+                if y == 0.0 {
+                    Err(ExprError::DivisionByZero)
+                } else {
+                    Ok(x / y)
+                }
+            }
+            Self::Call(name, args) => {
+                let mut vals = vec![];
+                for arg in args {
+                    vals.push(arg.eval_with(resolve)?);
+                }
+                match (name.as_str(), vals.as_slice()) {
+                    ("floor", [x]) => Ok(x.floor()),
+                    ("ceil", [x]) => Ok(x.ceil()),
+                    ("round", [x]) => Ok(x.round()),
+                    ("abs", [x]) => Ok(x.abs()),
+                    ("min", [x, ys @ ..]) => Ok(ys.iter().fold(*x, |a, b| a.min(*b))),
+                    ("max", [x, ys @ ..]) => Ok(ys.iter().fold(*x, |a, b| a.max(*b))),
+                    _ => Err(ExprError::UnknownFunction(name.clone())),
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ExprReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(option) = &self.option {
+            write!(f, "{}.{}", self.name.join("::"), option)
+        } else {
+            write!(f, "{}", self.name.join("::"))
+        }
+    }
+}
+
+impl std::fmt::Display for ExprError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingReference(name) => write!(f, "{}が見つかりません", name),
+            Self::DivisionByZero => write!(f, "0で割ることはできません"),
+            Self::UnknownFunction(name) => write!(f, "{}という関数はありません", name),
+            Self::CircularReference(name) => write!(f, "{}が循環参照しています", name),
+            Self::InvalidFormula(formula) => write!(f, "{}は計算式として不正です", formula),
+        }
+    }
+}
+
+peg::parser! {
+    grammar expr_parser() for str {
+        pub rule expr() -> Expr
+            = _ e:arithmetic() _ { e }
+
+        rule arithmetic() -> Expr
+            = precedence! {
+                x:(@) _ "+" _ y:@ { Expr::Add(Box::new(x), Box::new(y)) }
+                x:(@) _ "-" _ y:@ { Expr::Sub(Box::new(x), Box::new(y)) }
+                --
+                x:(@) _ "*" _ y:@ { Expr::Mul(Box::new(x), Box::new(y)) }
+                x:(@) _ "/" _ y:@ { Expr::Div(Box::new(x), Box::new(y)) }
+                --
+                "-" _ x:@ { Expr::Neg(Box::new(x)) }
+                --
+                n:number() { Expr::Number(n) }
+                c:call() { c }
+                r:reference() { Expr::Reference(r) }
+                "(" _ e:arithmetic() _ ")" { e }
+            }
+
+        rule number() -> f64
+            = n:$(['0'..='9']+ ("." ['0'..='9']+)?) {? n.parse().or(Err("number")) }
+
+        rule call() -> Expr
+            = name:$(['a'..='z']+) _ "(" _ args:(arithmetic() ** (_ "," _)) _ ")"
+                { Expr::Call(String::from(name), args) }
+
+        rule reference() -> ExprReference
+            = name:reference_name() ++ "::" option:("." o:reference_option() { o })?
+                { ExprReference { name, option } }
+
+        rule reference_name() -> String
+            = n:$(!(['0'..='9'] / reserved()) [_] (!reserved() [_])*) { String::from(n) }
+
+        rule reference_option() -> String
+            = o:$(['a'..='z']+) { String::from(o) }
+
+        rule reserved()
+            = ['+' | '-' | '*' | '/' | '(' | ')' | '.' | ':' | ',' | ' ' | '\t' | '\n']
+
+        rule _()
+            = [' ' | '\t' | '\n']*
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str) -> Result<f64, ExprError> {
+        let expr = Expr::from_str(text).expect(text);
+        expr.eval(&mut |reference| match (
+            reference.name.join("::").as_str(),
+            reference.option.as_deref(),
+        ) {
+            ("HP", None) => Some(7.0),
+            ("HP", Some("max")) => Some(20.0),
+            ("技能::剣", None) => Some(3.0),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_option_operands() {
+        let expr = Expr::from_str("HP.max - HP").unwrap();
+        match &expr {
+            Expr::Sub(x, y) => {
+                assert!(
+                    matches!(x.as_ref(), Expr::Reference(r) if r.option.as_deref() == Some("max"))
+                );
+                assert!(matches!(y.as_ref(), Expr::Reference(r) if r.option.is_none()));
+            }
+            _ => panic!("{:?}", expr),
+        }
+
+        assert_eq!(eval("HP.max - HP").ok(), Some(13.0));
+        assert_eq!(eval("HP.max/2").ok(), Some(10.0));
+        assert_eq!(eval("技能::剣 + 1").ok(), Some(4.0));
+        assert!(Expr::from_str("HP.max").unwrap().is_reference());
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(eval("1 + 2 * 3").ok(), Some(7.0));
+        assert_eq!(eval("(1 + 2) * 3").ok(), Some(9.0));
+        assert_eq!(eval("10 - 4 - 3").ok(), Some(3.0));
+        assert_eq!(eval("12 / 3 / 2").ok(), Some(2.0));
+        assert_eq!(eval("-2 * 3 + 1").ok(), Some(-5.0));
+        assert_eq!(eval("max(1, HP, 2) + floor(7 / 2)").ok(), Some(10.0));
+    }
+
+    #[test]
+    fn test_call() {
+        assert_eq!(eval("floor(7 / 2)").ok(), Some(3.0));
+        assert_eq!(eval("floor(-7 / 2)").ok(), Some(-4.0));
+        assert_eq!(eval("ceil(7 / 2)").ok(), Some(4.0));
+        assert_eq!(eval("round(2.5)").ok(), Some(3.0));
+        assert_eq!(eval("round(2.4)").ok(), Some(2.0));
+        assert_eq!(eval("abs(1 - HP)").ok(), Some(6.0));
+        assert_eq!(eval("min(HP, HP.max, 10)").ok(), Some(7.0));
+        assert_eq!(eval("max(HP)").ok(), Some(7.0));
+        assert_eq!(eval("max( HP.max , min(1, 2) )").ok(), Some(20.0));
+        assert_eq!(eval("floor(HP.max / 6) + 1").ok(), Some(4.0));
+
+        assert!(matches!(
+            eval("sqrt(4)"),
+            Err(ExprError::UnknownFunction(name)) if name == "sqrt"
+        ));
+        assert!(matches!(
+            eval("floor(1, 2)"),
+            Err(ExprError::UnknownFunction(..))
+        ));
+        assert!(matches!(eval("min()"), Err(ExprError::UnknownFunction(..))));
+        assert!(matches!(
+            eval("floor(MP)"),
+            Err(ExprError::MissingReference(..))
+        ));
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert!(matches!(eval("1 / 0"), Err(ExprError::DivisionByZero)));
+        assert!(matches!(
+            eval("HP / (HP - 7)"),
+            Err(ExprError::DivisionByZero)
+        ));
+        assert!(matches!(
+            eval("MP + 1"),
+            Err(ExprError::MissingReference(..))
+        ));
+    }
+}
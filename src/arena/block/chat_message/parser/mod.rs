@@ -1,10 +1,16 @@
 mod ast;
+mod error;
+mod expr;
 
 pub use ast::Argument;
 pub use ast::Command;
 pub use ast::Message;
 pub use ast::MessageToken;
 pub use ast::Reference;
+pub use error::ParseError;
+pub use expr::Expr;
+pub use expr::ExprError;
+pub use expr::ExprReference;
 pub use message_parser::*;
 
 peg::parser! {
@@ -22,9 +28,12 @@ peg::parser! {
                 r"\n" { MessageToken::Text(String::from("\n")) }
                 r"\" "\n" { MessageToken::Text(String::from("")) }
                 --
-                t:$([_]) { MessageToken::Text(String::from(t)) }
+                t:$(!block_head() [_]) { MessageToken::Text(String::from(t)) }
             }
 
+        rule block_head()
+            = r"{\" / "{" (!['{' | '}' | '[' | '\n'] [_])* "["
+
         rule curly_block() -> MessageToken
             = precedence! {
                 command:command() { MessageToken::Command(command) }
@@ -0,0 +1,244 @@
+use super::super::property::{self, NumberValue, Value};
+use super::{BlockMut, Expr, Property};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref PROPERTY_COMMAND: Regex =
+        Regex::new(r"\A:\s*([^.=+\-*/\s]+(?:::[^.=+\-*/\s]+)*)(?:\.([a-z]+))?\s*([=+\-*/])\s*(.*?)\s*\z")
+            .unwrap();
+}
+
+pub enum Operator {
+    Set,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+pub struct PropertyCommand {
+    name: Vec<String>,
+    option: Option<String>,
+    operator: Operator,
+    value: String,
+}
+
+pub struct PropertyCommandResult {
+    pub property: BlockMut<Property>,
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+impl PropertyCommand {
+    pub fn from_str(text: &str) -> Option<Self> {
+        let captures = PROPERTY_COMMAND.captures(text)?;
+        let name = captures
+            .get(1)?
+            .as_str()
+            .split("::")
+            .map(String::from)
+            .collect();
+        let option = captures.get(2).map(|option| String::from(option.as_str()));
+        let operator = match captures.get(3)?.as_str() {
+            "=" => Operator::Set,
+            "+" => Operator::Add,
+            "-" => Operator::Sub,
+            "*" => Operator::Mul,
+            "/" => Operator::Div,
+            _ => return None,
+        };
+        let value = String::from(captures.get(4)?.as_str());
+
+        Some(Self {
+            name,
+            option,
+            operator,
+            value,
+        })
+    }
+
+    pub fn parse_all(text: &str) -> Option<Vec<Self>> {
+        let mut cmds = vec![];
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            cmds.push(Self::from_str(line.trim())?);
+        }
+        if cmds.is_empty() {
+            None
+        } else {
+            Some(cmds)
+        }
+    }
+
+    pub fn name(&self) -> String {
+        if let Some(option) = &self.option {
+            format!("{}.{}", self.name.join("::"), option)
+        } else {
+            self.name.join("::")
+        }
+    }
+
+    pub fn apply(&self, props: &Vec<BlockMut<Property>>) -> Result<PropertyCommandResult, String> {
+        let name: Vec<_> = self.name.iter().collect();
+        let mut property = find_property(props, &name)
+            .ok_or_else(|| format!("{}が見つかりません", self.name()))?;
+
+        let before = property
+            .map(|property| property.data().get_value0().map(Value::clone))
+            .unwrap_or(None)
+            .ok_or_else(|| format!("{}に値がありません", self.name()))?;
+
+        let rhs = Expr::from_str(&self.value).and_then(|expr| {
+            expr.eval(&mut |reference| {
+                let name: Vec<_> = reference.name.iter().collect();
+                property::find_value(props, name, vec![])
+                    .and_then(|value| value.compute(props).ok())
+                    .and_then(|value| value.to_number_with_option(reference.option.as_ref()))
+            })
+            .ok()
+        });
+
+        let after = self
+            .apply_to_value(&before, rhs)
+            .ok_or_else(|| format!("{}を{}に変更できません", self.name(), self.value))?;
+
+        let option = self.option.as_ref();
+        let result = PropertyCommandResult {
+            property: BlockMut::clone(&property),
+            name: self.name(),
+            before: before.to_string_with_option(option),
+            after: after.to_string_with_option(option),
+        };
+
+        property.update(|property: &mut Property| {
+            if let Some(value) = property.data_mut().get_value0_mut() {
+                *value = after;
+            }
+        });
+
+        Ok(result)
+    }
+
+    fn apply_to_value(&self, value: &Value, rhs: Option<NumberValue>) -> Option<Value> {
+        let option = self.option.as_ref().map(String::as_str);
+        match (value, option) {
+            (Value::Number(val), None) | (Value::Number(val), Some("val")) => {
+                Some(Value::Number(self.calc(*val, rhs?)))
+            }
+            (Value::NumberMinMax(val, min, max), None)
+            | (Value::NumberMinMax(val, min, max), Some("val")) => {
+                Some(Value::NumberMinMax(self.calc(*val, rhs?), *min, *max))
+            }
+            (Value::NumberMinMax(val, min, max), Some("min")) => {
+                Some(Value::NumberMinMax(*val, self.calc(*min, rhs?), *max))
+            }
+            (Value::NumberMinMax(val, min, max), Some("max")) => {
+                Some(Value::NumberMinMax(*val, *min, self.calc(*max, rhs?)))
+            }
+            (Value::NumberMid(val, mid), None) | (Value::NumberMid(val, mid), Some("val")) => {
+                Some(Value::NumberMid(self.calc(*val, rhs?), *mid))
+            }
+            (Value::NumberMid(val, mid), Some("mid")) => {
+                Some(Value::NumberMid(*val, self.calc(*mid, rhs?)))
+            }
+            (Value::Normal(val), None) | (Value::Normal(val), Some("val")) => {
+                Some(Value::Normal(self.calc_text(val, rhs)?))
+            }
+            (Value::Note(val), None) | (Value::Note(val), Some("val")) => {
+                Some(Value::Note(self.calc_text(val, rhs)?))
+            }
+            (Value::Check(_), None) | (Value::Check(_), Some("val")) => match self.operator {
+                Operator::Set => match self.value.as_str() {
+                    "true" | "1" | "on" => Some(Value::Check(true)),
+                    "false" | "0" | "off" => Some(Value::Check(false)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            (Value::Select(idx, list), None) | (Value::Select(idx, list), Some("val")) => {
+                match self.operator {
+                    Operator::Set => list
+                        .iter()
+                        .position(|item| *item == self.value)
+                        .map(|idx| Value::Select(idx, list.clone())),
+                    _ => {
+                        let idx = self.calc(*idx as NumberValue, rhs?);
+                        if idx >= 0.0 && (idx as usize) < list.len() {
+                            Some(Value::Select(idx as usize, list.clone()))
+                        } else {
+                            None
+                        }
+                    }
+                }
+            }
+            (Value::Select(_, list), Some("idx")) => {
+                let idx = rhs?;
+                if idx >= 0.0 && (idx as usize) < list.len() {
+                    Some(Value::Select(idx as usize, list.clone()))
+                } else {
+                    None
+                }
+            }
+            (Value::Formula(_), Some("formula")) => match self.operator {
+                Operator::Set => Some(Value::Formula(self.value.clone())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn calc(&self, lhs: NumberValue, rhs: NumberValue) -> NumberValue {
+        match self.operator {
+            Operator::Set => rhs,
+            Operator::Add => lhs + rhs,
+            Operator::Sub => lhs - rhs,
+            Operator::Mul => lhs * rhs,
+            Operator::Div => {
+                if rhs == 0.0 {
+                    lhs
+                } else {
+                    lhs / rhs
+                }
+            }
+        }
+    }
+
+    fn calc_text(&self, lhs: &String, rhs: Option<NumberValue>) -> Option<String> {
+        match self.operator {
+            Operator::Set => Some(self.value.clone()),
+            _ => {
+                let lhs = lhs.trim().parse::<NumberValue>().ok()?;
+                Some(self.calc(lhs, rhs?).to_string())
+            }
+        }
+    }
+}
+
+fn find_property(props: &Vec<BlockMut<Property>>, name: &[&String]) -> Option<BlockMut<Property>> {
+    if name.is_empty() {
+        return None;
+    }
+
+    for prop in props {
+        let found = prop
+            .map(|property| {
+                if *property.name() == *name[0] {
+                    if name.len() == 1 {
+                        Some(BlockMut::clone(prop))
+                    } else {
+                        find_property(property.children(), &name[1..])
+                    }
+                } else {
+                    find_property(property.children(), name)
+                }
+            })
+            .unwrap_or(None);
+
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    None
+}
@@ -1,57 +1,129 @@
-use super::{Argument, Command, CommandResult, GameSystemClass, Message, MessageToken, Reference};
+use super::{Argument, Command, CommandResult, Message, MessageToken, Reference};
 
 pub fn roll_message(
-    game_system_class: &GameSystemClass,
+    eval: &mut impl FnMut(&str) -> Option<CommandResult>,
     command_results: &mut Vec<CommandResult>,
-    message: &Message,
-) {
-    for token in message.iter() {
-        roll_token(game_system_class, command_results, token);
-    }
+    message: Message,
+) -> Message {
+    message
+        .map(|token| roll_token(eval, command_results, token))
+        .flatten()
+}
+
+fn roll_inline(
+    eval: &mut impl FnMut(&str) -> Option<CommandResult>,
+    command_results: &mut Vec<CommandResult>,
+    message: Message,
+) -> Message {
+    message
+        .map(|token| match token {
+            MessageToken::Command(command) => roll_command(eval, command_results, command),
+            _ => Message::from(vec![token]),
+        })
+        .flatten()
 }
 
 fn roll_token(
-    game_system_class: &GameSystemClass,
+    eval: &mut impl FnMut(&str) -> Option<CommandResult>,
     command_results: &mut Vec<CommandResult>,
-    token: &MessageToken,
-) {
+    token: MessageToken,
+) -> Message {
     match token {
-        MessageToken::Text(text) => roll_text(game_system_class, command_results, text),
-        MessageToken::Reference(reference) => {
-            roll_reference(game_system_class, command_results, reference)
-        }
-        MessageToken::Command(command) => roll_command(game_system_class, command_results, command),
+        MessageToken::Text(text) => roll_text(eval, command_results, text),
+        MessageToken::Reference(reference) => roll_reference(eval, command_results, reference),
+        MessageToken::Command(command) => roll_command(eval, command_results, command),
     }
 }
 
 fn roll_text(
-    game_system_class: &GameSystemClass,
+    eval: &mut impl FnMut(&str) -> Option<CommandResult>,
     command_results: &mut Vec<CommandResult>,
-    text: &String,
-) {
-    if let Some(cmd_result) = game_system_class.eval(&text) {
+    text: String,
+) -> Message {
+    if let Some(cmd_result) = eval(&text) {
         command_results.push(cmd_result);
     }
+    Message::from(vec![MessageToken::Text(text)])
 }
 
 fn roll_command(
-    game_system_class: &GameSystemClass,
+    eval: &mut impl FnMut(&str) -> Option<CommandResult>,
     command_results: &mut Vec<CommandResult>,
-    cmd: &Command,
-) {
-    roll_message(game_system_class, command_results, &cmd.text);
+    cmd: Command,
+) -> Message {
+    let Command { name, args, text } = cmd;
+
+    if name.to_string() == "roll" && args.len() == 0 {
+        let text = roll_inline(eval, command_results, text);
+        let expr = text.to_string();
+
+        return if let Some(cmd_result) = eval(&expr) {
+            let value = roll_result_value(&cmd_result);
+            command_results.push(cmd_result);
+            Message::from(vec![MessageToken::Command(Command {
+                name,
+                args: vec![Argument {
+                    value: text,
+                    option: None,
+                }],
+                text: Message::from(vec![MessageToken::Text(value)]),
+            })])
+        } else {
+            Message::from(vec![MessageToken::Command(Command {
+                name,
+                args,
+                text,
+            })])
+        };
+    }
+
+    let text = if name.to_string() == "roll" {
+        text
+    } else {
+        roll_message(eval, command_results, text)
+    };
+    let args = args
+        .into_iter()
+        .map(|arg| roll_argument(eval, command_results, arg))
+        .collect();
+
+    Message::from(vec![MessageToken::Command(Command { name, args, text })])
 }
 
 fn roll_reference(
-    _game_system_class: &GameSystemClass,
-    _command_results: &mut Vec<CommandResult>,
-    _reference: &Reference,
-) {
+    eval: &mut impl FnMut(&str) -> Option<CommandResult>,
+    command_results: &mut Vec<CommandResult>,
+    reference: Reference,
+) -> Message {
+    let Reference { name, args, option } = reference;
+
+    let args = args
+        .into_iter()
+        .map(|arg| roll_argument(eval, command_results, arg))
+        .collect();
+    let option = option.map(|option| roll_inline(eval, command_results, option));
+
+    Message::from(vec![MessageToken::Reference(Reference { name, args, option })])
 }
 
 fn roll_argument(
-    _game_system_class: &GameSystemClass,
-    _command_results: &mut Vec<CommandResult>,
-    _argument: &Argument,
-) {
+    eval: &mut impl FnMut(&str) -> Option<CommandResult>,
+    command_results: &mut Vec<CommandResult>,
+    argument: Argument,
+) -> Argument {
+    let Argument { value, option } = argument;
+
+    let value = roll_inline(eval, command_results, value);
+    let option = option.map(|option| roll_inline(eval, command_results, option));
+
+    Argument { value, option }
+}
+
+fn roll_result_value(cmd_result: &CommandResult) -> String {
+    cmd_result
+        .text
+        .rsplit('＞')
+        .next()
+        .map(|value| String::from(value.trim()))
+        .unwrap_or_else(|| cmd_result.text.clone())
 }
@@ -0,0 +1,197 @@
+#[allow(unused_imports)]
+use super::util::prelude::*;
+use super::util::{Pack, PackDepth};
+use super::BlockMut;
+use super::Character;
+use crate::libs::color::Pallet;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DurationUnit {
+    Round,
+    Turn,
+}
+
+#[async_trait(?Send)]
+impl Pack for DurationUnit {
+    async fn pack(&self, _: PackDepth) -> JsValue {
+        match self {
+            Self::Round => JsValue::from("Round"),
+            Self::Turn => JsValue::from("Turn"),
+        }
+    }
+
+    async fn unpack(data: &JsValue, _arena: ArenaMut) -> Option<Box<Self>> {
+        let data = data.as_string()?;
+        match data.as_str() {
+            "Round" => Some(Box::new(Self::Round)),
+            "Turn" => Some(Box::new(Self::Turn)),
+            _ => None,
+        }
+    }
+}
+
+pub struct Preset {
+    pub name: &'static str,
+    pub icon: &'static str,
+    pub color: Pallet,
+}
+
+pub fn presets() -> Vec<Preset> {
+    vec![
+        Preset {
+            name: "毒",
+            icon: "fa-skull-crossbones",
+            color: Pallet::green(7),
+        },
+        Preset {
+            name: "スタン",
+            icon: "fa-bolt",
+            color: Pallet::yellow(7),
+        },
+        Preset {
+            name: "転倒",
+            icon: "fa-person-falling",
+            color: Pallet::orange(7),
+        },
+        Preset {
+            name: "炎上",
+            icon: "fa-fire",
+            color: Pallet::red(7),
+        },
+        Preset {
+            name: "拘束",
+            icon: "fa-link",
+            color: Pallet::gray(7),
+        },
+        Preset {
+            name: "強化",
+            icon: "fa-arrow-up",
+            color: Pallet::blue(7),
+        },
+    ]
+}
+
+pub const ICONS: [&str; 12] = [
+    "fa-circle-exclamation",
+    "fa-skull-crossbones",
+    "fa-bolt",
+    "fa-person-falling",
+    "fa-fire",
+    "fa-snowflake",
+    "fa-link",
+    "fa-eye-slash",
+    "fa-heart-crack",
+    "fa-shield-halved",
+    "fa-arrow-up",
+    "fa-arrow-down",
+];
+
+block! {
+    [pub Condition(constructor, pack)]
+    (character): BlockMut<Character>;
+    name: String = String::from("状態異常");
+    icon: String = String::from("fa-circle-exclamation");
+    color: Pallet = Pallet::red(7);
+    duration: Option<u32> = None;
+    duration_unit: DurationUnit = DurationUnit::Round;
+}
+
+impl Condition {
+    pub fn from_preset(character: BlockMut<Character>, preset: &Preset) -> Self {
+        let mut condition = Self::new(character);
+        condition.name = String::from(preset.name);
+        condition.icon = String::from(preset.icon);
+        condition.color = preset.color.clone();
+        condition
+    }
+
+    pub fn character(&self) -> &BlockMut<Character> {
+        &self.character
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn icon(&self) -> &String {
+        &self.icon
+    }
+
+    pub fn set_icon(&mut self, icon: String) {
+        self.icon = icon;
+    }
+
+    pub fn color(&self) -> &Pallet {
+        &self.color
+    }
+
+    pub fn set_color(&mut self, color: Pallet) {
+        self.color = color;
+    }
+
+    pub fn duration(&self) -> Option<u32> {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: Option<u32>) {
+        self.duration = duration;
+    }
+
+    pub fn duration_unit(&self) -> DurationUnit {
+        self.duration_unit
+    }
+
+    pub fn set_duration_unit(&mut self, duration_unit: DurationUnit) {
+        self.duration_unit = duration_unit;
+    }
+
+    pub fn label(&self) -> String {
+        match (self.duration, self.duration_unit) {
+            (Some(duration), DurationUnit::Round) => format!("{} {}R", self.name, duration),
+            (Some(duration), DurationUnit::Turn) => format!("{} {}T", self.name, duration),
+            (None, _) => self.name.clone(),
+        }
+    }
+
+    // 残りが1から0になったときだけ終了とする（0のまま経過しても再び終了しない）
+    pub fn tick(&mut self) -> bool {
+        match self.duration {
+            Some(duration) if duration > 0 => {
+                self.duration = Some(duration - 1);
+                duration == 1
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_expires_once() {
+        let mut condition = Condition::new(BlockMut::none());
+        condition.set_duration(Some(2));
+
+        assert!(!condition.tick());
+        assert_eq!(condition.duration(), Some(1));
+        assert!(condition.tick());
+        assert_eq!(condition.duration(), Some(0));
+        assert!(!condition.tick());
+        assert!(!condition.tick());
+        assert_eq!(condition.duration(), Some(0));
+    }
+
+    #[test]
+    fn test_tick_without_duration() {
+        let mut condition = Condition::new(BlockMut::none());
+
+        assert!(!condition.tick());
+        assert_eq!(condition.duration(), None);
+    }
+}
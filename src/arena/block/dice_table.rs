@@ -0,0 +1,147 @@
+#[allow(unused_imports)]
+use super::util::prelude::*;
+use super::util::{Pack, PackDepth};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref DICE: Regex = Regex::new(r"\A\s*(\d*)[dD](\d+)\s*(?:([+-])\s*(\d+))?\s*\z").unwrap();
+}
+
+block! {
+    [pub DiceTableItem(constructor, pack)]
+    (min): u32;
+    (max): u32;
+    text: String = String::new();
+}
+
+impl DiceTableItem {
+    pub fn min(&self) -> u32 {
+        self.min
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    pub fn text(&self) -> &String {
+        &self.text
+    }
+}
+
+block! {
+    [pub DiceTable(constructor, pack)]
+    name: String = String::from("ダイス表");
+    dice: String = String::from("1D6");
+    items: Vec<DiceTableItem> = (1..=6).map(|n| DiceTableItem::new(n, n)).collect();
+}
+
+pub struct DiceTableRoll {
+    pub rands: Vec<(u32, u32)>,
+    pub value: u32,
+    pub text: Option<String>,
+}
+
+impl DiceTable {
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn dice(&self) -> &String {
+        &self.dice
+    }
+
+    pub fn set_dice(&mut self, dice: String) {
+        self.dice = dice;
+    }
+
+    pub fn items(&self) -> &Vec<DiceTableItem> {
+        &self.items
+    }
+
+    pub fn push_item(&mut self) {
+        let min = self.items.last().map(|item| item.max + 1).unwrap_or(1);
+        self.items.push(DiceTableItem::new(min, min));
+    }
+
+    pub fn remove_item(&mut self, idx: usize) {
+        if idx < self.items.len() {
+            self.items.remove(idx);
+        }
+    }
+
+    pub fn set_item_range(&mut self, idx: usize, min: u32, max: u32) {
+        if let Some(item) = self.items.get_mut(idx) {
+            item.min = min.min(max);
+            item.max = min.max(max);
+        }
+    }
+
+    pub fn set_item_text(&mut self, idx: usize, text: String) {
+        if let Some(item) = self.items.get_mut(idx) {
+            item.text = text;
+        }
+    }
+
+    pub fn is_valid_dice(dice: &str) -> bool {
+        DICE.is_match(dice)
+    }
+
+    pub fn roll(&self, mut rand: impl FnMut(u32) -> u32) -> Option<DiceTableRoll> {
+        let captures = DICE.captures(&self.dice)?;
+        let num = captures
+            .get(1)
+            .and_then(|num| num.as_str().parse().ok())
+            .unwrap_or(1);
+        let sides: u32 = captures.get(2)?.as_str().parse().ok()?;
+        let modifier: i64 = captures
+            .get(4)
+            .and_then(|modifier| modifier.as_str().parse().ok())
+            .map(|modifier: i64| {
+                if captures.get(3).map(|op| op.as_str()) == Some("-") {
+                    -modifier
+                } else {
+                    modifier
+                }
+            })
+            .unwrap_or(0);
+
+        let (rands, value) = if sides == 66 && num == 1 {
+            let tens = rand(6);
+            let ones = rand(6);
+            (vec![(6, tens), (6, ones)], tens * 10 + ones)
+        } else {
+            let rands: Vec<_> = (0..num).map(|_| (sides, rand(sides))).collect();
+            let value = rands.iter().map(|(_, value)| *value).sum();
+            (rands, value)
+        };
+        let value = (value as i64 + modifier).max(0) as u32;
+
+        let text = self
+            .items
+            .iter()
+            .find(|item| item.min <= value && value <= item.max)
+            .map(|item| item.text.clone());
+
+        Some(DiceTableRoll { rands, value, text })
+    }
+
+    pub fn header(&self, result: &DiceTableRoll) -> String {
+        format!(
+            "{}({}) ＞ {}[{}] ＞ ",
+            self.name,
+            self.dice.to_uppercase(),
+            result.value,
+            result
+                .rands
+                .iter()
+                .map(|(_, value)| value.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    }
+}
@@ -0,0 +1,190 @@
+#[allow(unused_imports)]
+use super::util::prelude::*;
+use super::util::{Pack, PackDepth};
+use super::BlockMut;
+use super::{property, Character};
+use crate::libs::random_id::U128Id;
+
+block! {
+    [pub InitiativeEntry(constructor, pack)]
+    (character): BlockMut<Character>;
+    value: f64 = 0.0;
+}
+
+impl InitiativeEntry {
+    pub fn character(&self) -> &BlockMut<Character> {
+        &self.character
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+block! {
+    [pub Initiative(constructor, pack)]
+    entries: Vec<InitiativeEntry> = vec![];
+    sort_key: Option<String> = None;
+    dice: String = String::from("2D6");
+    round: u32 = 1;
+    turn: usize = 0;
+    reached_round: u32 = 1;
+    reached_turn: usize = 0;
+}
+
+impl Initiative {
+    pub fn entries(&self) -> &Vec<InitiativeEntry> {
+        &self.entries
+    }
+
+    pub fn sort_key(&self) -> Option<&String> {
+        self.sort_key.as_ref()
+    }
+
+    pub fn set_sort_key(&mut self, sort_key: Option<String>) {
+        self.sort_key = sort_key;
+        self.sort();
+    }
+
+    pub fn dice(&self) -> &String {
+        &self.dice
+    }
+
+    pub fn set_dice(&mut self, dice: String) {
+        self.dice = dice;
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn current(&self) -> Option<&InitiativeEntry> {
+        self.entries.get(self.turn)
+    }
+
+    pub fn contains(&self, block_id: &U128Id) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.character.id() == *block_id)
+    }
+
+    pub fn push_character(&mut self, character: BlockMut<Character>) {
+        if !self.contains(&character.id()) {
+            self.entries.push(InitiativeEntry::new(character));
+            self.sort();
+        }
+    }
+
+    pub fn remove_entry(&mut self, idx: usize) {
+        if idx < self.entries.len() {
+            self.entries.remove(idx);
+            if idx < self.turn {
+                self.turn -= 1;
+            }
+            if idx < self.reached_turn {
+                self.reached_turn -= 1;
+            }
+            if self.turn >= self.entries.len() {
+                self.turn = 0;
+            }
+        }
+    }
+
+    pub fn set_value(&mut self, idx: usize, value: f64) {
+        if let Some(entry) = self.entries.get_mut(idx) {
+            entry.value = value;
+        }
+        self.sort();
+    }
+
+    // 一度も進んだことのない手番に進んだときだけtrueを返す
+    pub fn next(&mut self) -> bool {
+        if self.entries.len() == 0 {
+            return false;
+        }
+        self.turn += 1;
+        if self.turn >= self.entries.len() {
+            self.turn = 0;
+            self.round += 1;
+        }
+
+        let is_reached = (self.round, self.turn) > (self.reached_round, self.reached_turn);
+        if is_reached {
+            self.reached_round = self.round;
+            self.reached_turn = self.turn;
+        }
+        is_reached
+    }
+
+    pub fn prev(&mut self) {
+        if self.entries.len() == 0 {
+            return;
+        }
+        if self.turn > 0 {
+            self.turn -= 1;
+        } else if self.round > 1 {
+            self.round -= 1;
+            self.turn = self.entries.len() - 1;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.round = 1;
+        self.turn = 0;
+        self.reached_round = 1;
+        self.reached_turn = 0;
+    }
+
+    pub fn sort(&mut self) -> bool {
+        let order = self.order();
+        let current = self.current().map(|entry| entry.character.id());
+
+        if let Some(sort_key) = &self.sort_key {
+            for entry in &mut self.entries {
+                if let Some(value) = entry
+                    .character
+                    .map(|character| Self::property_value(character, sort_key))
+                    .unwrap_or(None)
+                {
+                    entry.value = value;
+                }
+            }
+        }
+
+        self.entries.sort_by(|a, b| {
+            b.value
+                .partial_cmp(&a.value)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        if let Some(current) = current {
+            if let Some(turn) = self
+                .entries
+                .iter()
+                .position(|entry| entry.character.id() == current)
+            {
+                self.turn = turn;
+            }
+        }
+
+        self.order() != order
+    }
+
+    fn order(&self) -> Vec<(U128Id, f64)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.character.id(), entry.value))
+            .collect()
+    }
+
+    fn property_value(character: &Character, sort_key: &String) -> Option<f64> {
+        let props = character.properties();
+        let value = property::find_value(props, vec![sort_key], vec![])?;
+        let value = value.compute(props).ok()?;
+        value.to_number_with_option(None)
+    }
+}
@@ -5,7 +5,10 @@ mods! {
     pub chat_channel::ChatChannel;
     pub chat_message::ChatMessage;
     pub chat::Chat;
+    pub condition::Condition;
     pub craftboard::Craftboard;
+    pub dice_table::DiceTable;
+    pub initiative::Initiative;
     pub layer_group::LayerGroup;
     pub property::Property;
     pub scene::Scene;
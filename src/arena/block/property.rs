@@ -1,3 +1,4 @@
+use super::chat_message::parser::{Expr, ExprError};
 #[allow(unused_imports)]
 use super::util::prelude::*;
 use super::util::{Pack, PackDepth};
@@ -17,6 +18,7 @@ pub enum Value {
     Note(String),
     Check(bool),
     Select(usize, Vec<String>),
+    Formula(String),
 }
 
 impl Value {
@@ -30,6 +32,7 @@ impl Value {
             (Self::NumberMinMax(val, ..), Some("val")) => val.to_string(),
             (Self::NumberMinMax(_, min, ..), Some("min")) => min.to_string(),
             (Self::NumberMinMax(_, _, max), Some("max")) => max.to_string(),
+            (Self::NumberMinMax(_, min, max), Some("mid")) => ((min + max) / 2.0).to_string(),
             (Self::NumberMid(val, ..), None) => val.to_string(),
             (Self::NumberMid(val, ..), Some("val")) => val.to_string(),
             (Self::NumberMid(_, mid), Some("mid")) => mid.to_string(),
@@ -56,10 +59,30 @@ impl Value {
                 .map(|val| val.clone())
                 .unwrap_or_else(|| String::new()),
 
+            (Self::Formula(formula), Some("formula")) => formula.clone(),
+
             _ => String::new(),
         }
     }
 
+    pub fn to_number_with_option(&self, option: Option<&String>) -> Option<NumberValue> {
+        match (self, option.map(|option| option.as_str())) {
+            (Self::Normal(..), _) | (Self::Note(..), _) => self
+                .to_string_with_option(option)
+                .trim()
+                .parse::<NumberValue>()
+                .ok(),
+            (Self::Select(..), Some("idx")) | (Self::Select(..), None) => {
+                self.to_string_with_option(Some(&String::from("idx"))).parse().ok()
+            }
+            (Self::Check(val), None) | (Self::Check(val), Some("val")) => {
+                Some(if *val { 1.0 } else { 0.0 })
+            }
+            (Self::Formula(..), _) => None,
+            _ => self.to_string_with_option(option).parse().ok(),
+        }
+    }
+
     pub fn to_number(&self) -> Self {
         match self {
             Self::Number(val) => Self::Number(*val),
@@ -75,6 +98,11 @@ impl Value {
                 .unwrap_or_else(|_| Self::Number(0.0)),
             Self::Check(val) => Self::Number(if *val { 1.0 } else { 0.0 }),
             Self::Select(idx, ..) => Self::Number(*idx as NumberValue),
+            Self::Formula(val) => val
+                .trim()
+                .parse::<NumberValue>()
+                .map(|val| Self::Number(val))
+                .unwrap_or_else(|_| Self::Number(0.0)),
         }
     }
 
@@ -95,6 +123,11 @@ impl Value {
             Self::Select(idx, list) => {
                 Self::NumberMinMax(*idx as NumberValue, 0.0, (list.len() - 1) as NumberMax)
             }
+            Self::Formula(val) => val
+                .trim()
+                .parse::<NumberValue>()
+                .map(|val| Self::NumberMinMax(val, 0.0, 100.0))
+                .unwrap_or_else(|_| Self::NumberMinMax(0.0, 0.0, 100.0)),
         }
     }
 
@@ -115,6 +148,11 @@ impl Value {
             Self::Select(idx, list) => {
                 Self::NumberMid(*idx as NumberValue, (list.len() - 1) as NumberMax / 2.0)
             }
+            Self::Formula(val) => val
+                .trim()
+                .parse::<NumberValue>()
+                .map(|val| Self::NumberMid(val, 10.0))
+                .unwrap_or_else(|_| Self::NumberMid(0.0, 10.0)),
         }
     }
 
@@ -127,6 +165,7 @@ impl Value {
             Self::Note(val) => Self::Normal(val.clone()),
             Self::Check(val) => Self::Normal(format!("{}", *val)),
             Self::Select(idx, ..) => Self::Normal(format!("{}", *idx)),
+            Self::Formula(val) => Self::Normal(val.clone()),
         }
     }
 
@@ -139,6 +178,7 @@ impl Value {
             Self::Note(val) => Self::Note(val.clone()),
             Self::Check(val) => Self::Note(format!("{}", *val)),
             Self::Select(idx, ..) => Self::Note(format!("{}", *idx)),
+            Self::Formula(val) => Self::Note(val.clone()),
         }
     }
 
@@ -151,6 +191,7 @@ impl Value {
             Self::Note(val) => Self::Check(*val == "true"),
             Self::Check(val) => Self::Check(*val),
             Self::Select(idx, ..) => Self::Check(*idx != 0),
+            Self::Formula(val) => Self::Check(*val == "true"),
         }
     }
 
@@ -166,8 +207,77 @@ impl Value {
                 vec![String::from("false"), String::from("true")],
             ),
             Self::Select(idx, list) => Self::Select(*idx, list.clone()),
+            Self::Formula(val) => Self::Select(0, vec![val.clone()]),
+        }
+    }
+
+    pub fn to_formula(&self) -> Self {
+        match self {
+            Self::Number(val) => Self::Formula(format!("{}", *val)),
+            Self::NumberMinMax(val, ..) => Self::Formula(format!("{}", *val)),
+            Self::NumberMid(val, ..) => Self::Formula(format!("{}", *val)),
+            Self::Normal(val) => Self::Formula(val.clone()),
+            Self::Note(val) => Self::Formula(val.clone()),
+            Self::Check(val) => Self::Formula(String::from(if *val { "1" } else { "0" })),
+            Self::Select(idx, ..) => Self::Formula(format!("{}", *idx)),
+            Self::Formula(val) => Self::Formula(val.clone()),
         }
     }
+
+    pub fn compute(self, props: &Vec<BlockMut<Property>>) -> Result<Self, ExprError> {
+        match self {
+            Self::Formula(formula) => Ok(Self::Number(eval_formula(props, &formula, &mut vec![])?)),
+            value => Ok(value),
+        }
+    }
+}
+
+fn eval_formula(
+    props: &Vec<BlockMut<Property>>,
+    formula: &String,
+    visiting: &mut Vec<String>,
+) -> Result<NumberValue, ExprError> {
+    eval_formula_with(&|name| find_value(props, name, vec![]), formula, visiting)
+}
+
+fn eval_formula_with(
+    find: &impl Fn(Vec<&String>) -> Option<Value>,
+    formula: &String,
+    visiting: &mut Vec<String>,
+) -> Result<NumberValue, ExprError> {
+    let expr = Expr::from_str(formula).ok_or_else(|| ExprError::InvalidFormula(formula.clone()))?;
+
+    expr.eval_with(&mut |reference| {
+        let key = reference.to_string();
+        if visiting.contains(&key) {
+            return Err(ExprError::CircularReference(key));
+        }
+
+        let name: Vec<_> = reference.name.iter().collect();
+        let value = find(name).ok_or_else(|| ExprError::MissingReference(key.clone()))?;
+
+        if let Value::Formula(formula) = &value {
+            visiting.push(key);
+            let res = eval_formula_with(find, formula, visiting);
+            visiting.pop();
+            res
+        } else {
+            value
+                .to_number_with_option(reference.option.as_ref())
+                .ok_or_else(|| ExprError::MissingReference(key))
+        }
+    })
+}
+
+pub fn find_value(
+    props: &Vec<BlockMut<Property>>,
+    name: Vec<&String>,
+    args: Vec<&(String, Option<String>)>,
+) -> Option<Value> {
+    props.iter().find_map(|prop| {
+        prop.map(|prop| prop.ref_value(name.clone(), args.clone()))
+            .unwrap_or_default()
+    })
 }
 
 impl std::fmt::Display for Value {
@@ -183,6 +293,7 @@ impl std::fmt::Display for Value {
                 .get(*idx)
                 .map(|val| write!(f, "{}", val))
                 .unwrap_or_else(|| write!(f, "")),
+            Self::Formula(val) => write!(f, "{}", val),
         }
     }
 }
@@ -228,6 +339,11 @@ impl Pack for Value {
                 "_val": array![*v, s.pack(pack_depth).await]
             })
             .into(),
+            Self::Formula(v) => (object! {
+                "_tag": "Formula",
+                "_val": v
+            })
+            .into(),
         }
     }
 
@@ -274,6 +390,7 @@ impl Pack for Value {
                 }
                 Some(Box::new(Self::Select(val_0, list)))
             }
+            "Formula" => val.as_string().map(|v| Box::new(Self::Formula(v))),
             _ => None,
         }
     }
@@ -393,6 +510,10 @@ impl Data {
         self.get_value1(0)
     }
 
+    pub fn get_value0_mut(&mut self) -> Option<&mut Value> {
+        self.values.iter_mut().find_map(|cols| cols.first_mut())
+    }
+
     pub fn get_value1(&self, mut idx: usize) -> Option<&Value> {
         for row_offset in 0..self.values.len() {
             if let Some(value) = self.get_value2(row_offset, idx) {
@@ -497,6 +618,15 @@ block! {
     data: Data = Data::new();
     view: PropertyView = PropertyView::List;
     children: Vec<BlockMut<Self>> = vec![];
+    is_shown_on_token: bool = false;
+    token_viewers: Vec<String> = vec![];
+}
+
+pub struct TokenGauge {
+    pub name: String,
+    pub value: NumberValue,
+    pub min: NumberMin,
+    pub max: NumberMax,
 }
 
 impl Property {
@@ -542,6 +672,50 @@ impl Property {
         }
     }
 
+    pub fn is_shown_on_token(&self) -> bool {
+        self.is_shown_on_token
+    }
+
+    pub fn set_is_shown_on_token(&mut self, is_shown_on_token: bool) {
+        self.is_shown_on_token = is_shown_on_token;
+    }
+
+    pub fn token_viewers(&self) -> &Vec<String> {
+        &self.token_viewers
+    }
+
+    pub fn set_token_viewers(&mut self, token_viewers: Vec<String>) {
+        self.token_viewers = token_viewers;
+    }
+
+    pub fn is_visible_on_token_to(&self, client_id: &String) -> bool {
+        self.is_shown_on_token
+            && (self.token_viewers.is_empty() || self.token_viewers.contains(client_id))
+    }
+
+    pub fn token_gauges(&self, client_id: &String) -> Vec<TokenGauge> {
+        let mut gauges = vec![];
+
+        if self.is_visible_on_token_to(client_id) {
+            if let Some(Value::NumberMinMax(value, min, max)) = self.data.get_value0() {
+                gauges.push(TokenGauge {
+                    name: self.name.clone(),
+                    value: *value,
+                    min: *min,
+                    max: *max,
+                });
+            }
+        }
+
+        for child in &self.children {
+            if let Some(mut child_gauges) = child.map(|child| child.token_gauges(client_id)) {
+                gauges.append(&mut child_gauges);
+            }
+        }
+
+        gauges
+    }
+
     pub fn ref_value(
         &self,
         mut name: Vec<&String>,
@@ -583,3 +757,65 @@ impl Property {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn eval(values: &[(&str, Value)], formula: &str) -> Result<NumberValue, ExprError> {
+        let values = values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect::<HashMap<_, _>>();
+        let find = |name: Vec<&String>| {
+            let name = name.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+            values.get(&name.join("::")).cloned()
+        };
+        eval_formula_with(&find, &String::from(formula), &mut vec![])
+    }
+
+    #[test]
+    fn test_formula_reference() {
+        let values = [
+            ("HP", Value::NumberMinMax(7.0, 0.0, 20.0)),
+            ("器用度", Value::Number(15.0)),
+            ("器用度B", Value::Formula(String::from("floor(器用度 / 6)"))),
+            ("命中", Value::Formula(String::from("器用度B + 2"))),
+        ];
+
+        assert_eq!(eval(&values, "HP.max - HP").ok(), Some(13.0));
+        assert_eq!(eval(&values, "器用度B").ok(), Some(2.0));
+        assert_eq!(eval(&values, "命中 * 2").ok(), Some(8.0));
+        assert!(matches!(
+            eval(&values, "MP + 1"),
+            Err(ExprError::MissingReference(..))
+        ));
+        assert!(matches!(
+            eval(&values, "HP +"),
+            Err(ExprError::InvalidFormula(..))
+        ));
+    }
+
+    #[test]
+    fn test_formula_circular_reference() {
+        let values = [
+            ("A", Value::Formula(String::from("B + 1"))),
+            ("B", Value::Formula(String::from("C + 1"))),
+            ("C", Value::Formula(String::from("A + 1"))),
+            ("D", Value::Formula(String::from("D"))),
+            ("E", Value::Formula(String::from("F + F"))),
+            ("F", Value::Number(1.0)),
+        ];
+
+        assert!(matches!(
+            eval(&values, "A"),
+            Err(ExprError::CircularReference(..))
+        ));
+        assert!(matches!(
+            eval(&values, "D + 1"),
+            Err(ExprError::CircularReference(..))
+        ));
+        assert_eq!(eval(&values, "E").ok(), Some(2.0));
+    }
+}
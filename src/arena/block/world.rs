@@ -5,7 +5,7 @@ use super::super::Access;
 use super::util::prelude::*;
 use super::util::{Pack, PackDepth};
 use super::{BlockMut, BlockRef};
-use super::{Character, Scene, TerranTexture};
+use super::{Character, Condition, DiceTable, Initiative, Scene, TerranTexture};
 use crate::libs::random_id::U128Id;
 use std::collections::HashSet;
 
@@ -39,6 +39,9 @@ block! {
     image_data_resources: Vec<BlockRef<ImageData>> = vec![];
     block_texture_resources: Vec<BlockRef<BlockTexture>> = vec![];
     terran_texture_blocks: Vec<BlockMut<TerranTexture>> = vec![];
+    dice_tables: Vec<BlockMut<DiceTable>> = vec![];
+    initiative: BlockMut<Initiative> = BlockMut::<Initiative>::none();
+    conditions: Vec<BlockMut<Condition>> = vec![];
 }
 
 impl World {
@@ -58,6 +61,11 @@ impl World {
         {
             self.characters.remove(character_idx);
         }
+        self.conditions.retain(|condition| {
+            condition
+                .map(|condition| condition.character().id() != *block_id)
+                .unwrap_or(true)
+        });
     }
 
     pub fn scenes(&self) -> &Vec<BlockMut<Scene>> {
@@ -114,4 +122,60 @@ impl World {
     pub fn push_terran_texture_block(&mut self, block_texture: BlockMut<TerranTexture>) {
         self.terran_texture_blocks.push(block_texture);
     }
+
+    pub fn dice_tables(&self) -> &Vec<BlockMut<DiceTable>> {
+        &self.dice_tables
+    }
+
+    pub fn push_dice_table(&mut self, dice_table: BlockMut<DiceTable>) {
+        self.dice_tables.push(dice_table);
+    }
+
+    pub fn remove_dice_table(&mut self, block_id: &U128Id) {
+        if let Some(dice_table_idx) = self
+            .dice_tables
+            .iter()
+            .position(|dice_table| dice_table.id() == *block_id)
+        {
+            self.dice_tables.remove(dice_table_idx);
+        }
+    }
+
+    pub fn initiative(&self) -> &BlockMut<Initiative> {
+        &self.initiative
+    }
+
+    pub fn set_initiative(&mut self, initiative: BlockMut<Initiative>) {
+        self.initiative = initiative;
+    }
+
+    pub fn conditions(&self) -> &Vec<BlockMut<Condition>> {
+        &self.conditions
+    }
+
+    pub fn conditions_of(&self, character_id: &U128Id) -> Vec<BlockMut<Condition>> {
+        self.conditions
+            .iter()
+            .filter(|condition| {
+                condition
+                    .map(|condition| condition.character().id() == *character_id)
+                    .unwrap_or(false)
+            })
+            .map(BlockMut::clone)
+            .collect()
+    }
+
+    pub fn push_condition(&mut self, condition: BlockMut<Condition>) {
+        self.conditions.push(condition);
+    }
+
+    pub fn remove_condition(&mut self, block_id: &U128Id) {
+        if let Some(condition_idx) = self
+            .conditions
+            .iter()
+            .position(|condition| condition.id() == *block_id)
+        {
+            self.conditions.remove(condition_idx);
+        }
+    }
 }
@@ -18,7 +18,10 @@ arena! {
     pub block::Chat;
     pub block::ChatChannel;
     pub block::ChatMessage;
+    pub block::Condition;
     pub block::Craftboard;
+    pub block::DiceTable;
+    pub block::Initiative;
     pub block::LayerGroup;
     pub block::Property;
     pub block::Scene;
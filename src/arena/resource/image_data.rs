@@ -26,6 +26,10 @@ impl ImageData {
         &self.url
     }
 
+    pub fn blob(&self) -> &Rc<web_sys::Blob> {
+        &self.blob
+    }
+
     pub fn size(&self) -> &[f64; 2] {
         &self.size
     }
@@ -119,13 +119,13 @@ macro_rules! block {
                         let $p_c_name = *$p_c_name;
                     )*
                     $(
-                        let $p_d_name = if let Some(item) = data.get(stringify!($p_d_name)) {
-                            <$p_d_type as Pack>::unpack(&item, ArenaMut::clone(&arena)).await
+                        // 後から追加されたフィールドは古いデータに含まれないため、宣言した既定値で補う
+                        let $p_d_name: $p_d_type = if let Some(item) = data.get(stringify!($p_d_name)) {
+                            let item = <$p_d_type as Pack>::unpack(&item, ArenaMut::clone(&arena)).await;
+                            *unwrap!(item; None)
                         } else {
-                            None
+                            $p_default
                         };
-                        let $p_d_name = unwrap!($p_d_name; None);
-                        let $p_d_name = *$p_d_name;
                     )*
                     let this = Self {
                         $($p_c_name,)*
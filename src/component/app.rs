@@ -30,6 +30,7 @@ pub struct RoomPageData {
     meshroom: Rc<skyway::MeshRoom>,
     table_db: Rc<web_sys::IdbDatabase>,
     bcdice_loader: Rc<DynamicLoader>,
+    bcdice_api_root: Option<Rc<String>>,
 }
 
 pub enum Msg {
@@ -145,7 +146,8 @@ impl Render<Html> for App {
                         room: Rc::clone(&room_data.meshroom),
                         room_id: room_id,
                         client_id: Rc::clone(&common_data.client_id),
-                        bcdice_loader: Rc::clone(&room_data.bcdice_loader)
+                        bcdice_loader: Rc::clone(&room_data.bcdice_loader),
+                        bcdice_api_root: room_data.bcdice_api_root.as_ref().map(Rc::clone)
                     },
                     Sub::none()
                 )
@@ -163,7 +165,8 @@ impl Render<Html> for App {
                         room: Rc::clone(&room_data.meshroom),
                         room_id: room_id,
                         client_id: Rc::clone(&common_data.client_id),
-                        bcdice_loader: Rc::clone(&room_data.bcdice_loader)
+                        bcdice_loader: Rc::clone(&room_data.bcdice_loader),
+                        bcdice_api_root: room_data.bcdice_api_root.as_ref().map(Rc::clone)
                     },
                     Sub::none()
                 )
@@ -203,6 +206,11 @@ impl App {
     }
 
     fn render_room_initializer(&self, common: &CommonPageData, room_id: &Rc<String>) -> Html {
+        let bcdice_api_root = common
+            .config
+            .bcdice
+            .as_ref()
+            .map(|bcdice| Rc::new(bcdice.api_root.clone()));
         RoomInisializer::empty(
             self,
             None,
@@ -226,6 +234,7 @@ impl App {
                         meshroom,
                         table_db: Rc::new(table_db),
                         bcdice_loader: Rc::new(DynamicLoader::new()),
+                        bcdice_api_root: bcdice_api_root.as_ref().map(Rc::clone),
                     },
                 ),
             }),
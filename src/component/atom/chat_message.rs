@@ -1,11 +1,18 @@
 use crate::arena::block;
+use crate::libs::color::Pallet;
 use isaribi::{
     style,
     styled::{Style, Styled},
 };
 use nusa::prelude::*;
+use std::cell::Cell;
+
+thread_local! {
+    static IS_COMMAND_STYLE_INSERTED: Cell<bool> = Cell::new(false);
+}
 
 pub fn div(attrs: Attributes, events: Events, message: &block::chat_message::Message) -> Html {
+    ChatMessage::insert_command_style();
     Html::div(
         attrs.class(ChatMessage::class("base")),
         events,
@@ -13,9 +20,138 @@ pub fn div(attrs: Attributes, events: Events, message: &block::chat_message::Mes
     )
 }
 
+pub fn parse_error(
+    attrs: Attributes,
+    events: Events,
+    text: &str,
+    error: &block::chat_message::ParseError,
+) -> Html {
+    let (before, target, after) = error.range_in(text);
+    let before = before.rsplit('\n').next().unwrap_or("");
+    let after = after.split('\n').next().unwrap_or("");
+    let target = match target {
+        "" | "\n" => "↵",
+        _ => target,
+    };
+
+    ChatMessage::styled(Html::div(
+        attrs.class(ChatMessage::class("parse-error")),
+        events,
+        vec![
+            Html::div(
+                Attributes::new().class(ChatMessage::class("parse-error-message")),
+                Events::new(),
+                vec![Html::text(error.to_string())],
+            ),
+            Html::div(
+                Attributes::new().class(ChatMessage::class("parse-error-line")),
+                Events::new(),
+                vec![
+                    Html::text(before),
+                    Html::span(
+                        Attributes::new().class(ChatMessage::class("parse-error-mark")),
+                        Events::new(),
+                        vec![Html::text(target)],
+                    ),
+                    Html::text(after),
+                ],
+            ),
+        ],
+    ))
+}
+
+/// CSS for the `data-cmd` attributes of rendered messages, scoped to `scope`.
+/// Shared by the chat view and the exported chat log.
+pub fn command_style(scope: &str) -> String {
+    let mut rules = vec![
+        (
+            String::from("box"),
+            format!(
+                "display: block; overflow-y: scroll; max-height: 15em; padding-left: .35rem; border-left: .35rem solid {};",
+                Pallet::gray(3)
+            ),
+        ),
+        (
+            String::from("roll"),
+            format!(
+                "font-weight: bold; padding: 0 .15em; border-bottom: .1rem dashed {};",
+                Pallet::gray(5)
+            ),
+        ),
+        (
+            String::from("error"),
+            format!("color: {} !important; font-size: 0.9em;", Pallet::red(7)),
+        ),
+        (String::from("div"), String::from("display: block;")),
+        (String::from("span"), String::from("display: inline-block;")),
+        (
+            String::from("grid"),
+            String::from("display: grid; grid-template-columns: repeat(12, 1fr); grid-auto-rows: max-content; column-gap: 0.25ch; row-gap: 0.375em;"),
+        ),
+    ];
+
+    for n in 1..=12 {
+        rules.push((format!("{}fr", n), format!("grid-area: auto / span {};", n)));
+    }
+
+    for (name, rule) in [
+        ("sans-serif", "font-family: sans-serif;"),
+        ("serif", "font-family: serif;"),
+        ("mono", "font-family: monospace;"),
+        ("bold", "font-weight: bold;"),
+        ("nb", "word-break: keep-all; white-space: nowrap;"),
+        ("left", "text-align: left;"),
+        ("right", "text-align: right;"),
+        ("center", "text-align: center;"),
+        ("large", "font-size: 1.25em;"),
+        ("huge", "font-size: 1.5em;"),
+    ] {
+        rules.push((String::from(name), String::from(rule)));
+    }
+
+    for (name, fg, bg) in [
+        ("light", Pallet::gray(0), Pallet::gray(9)),
+        ("dark", Pallet::gray(9), Pallet::gray(0)),
+        ("red", Pallet::red(7), Pallet::gray(0)),
+        ("orange", Pallet::orange(5), Pallet::gray(0)),
+        ("yellow", Pallet::yellow(8), Pallet::gray(0)),
+        ("green", Pallet::green(7), Pallet::gray(0)),
+        ("blue", Pallet::blue(5), Pallet::gray(0)),
+        ("purple", Pallet::purple(5), Pallet::gray(0)),
+        ("pink", Pallet::pink(7), Pallet::gray(0)),
+    ] {
+        rules.push((
+            String::from(name),
+            format!("color: {} !important; background-color: {};", fg, bg),
+        ));
+        rules.push((
+            format!("bg-{}", name),
+            format!("color: {}; background-color: {} !important;", bg, fg),
+        ));
+    }
+
+    rules
+        .into_iter()
+        .map(|(cmd, rule)| format!("{} [data-cmd~='{}'] {{ {} }}", scope, cmd, rule))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 pub struct ChatMessage {}
 
 impl ChatMessage {
+    fn insert_command_style() {
+        if IS_COMMAND_STYLE_INSERTED.with(|is_inserted| is_inserted.replace(true)) {
+            return;
+        }
+
+        let document = unwrap!(web_sys::window().and_then(|window| window.document()));
+        let head = unwrap!(document.query_selector("head").ok().flatten());
+        let style = unwrap!(document.create_element("style").ok());
+        style.set_text_content(Some(&command_style(&format!(".{}", Self::class("base")))));
+        let _ = head.append_child(&style);
+    }
+
     fn render(message: &block::chat_message::Message) -> Vec<Html> {
         ChatMessage::styled(
             message
@@ -55,6 +191,15 @@ impl ChatMessage {
                         Events::new(),
                         Self::render(&cmd.text),
                     )
+                } else if cmd_name == "roll" {
+                    let args: Vec<_> = cmd.args.iter().map(|arg| format!("{}", arg)).collect();
+                    Html::span(
+                        Attributes::new()
+                            .string("data-cmd", cmd_name)
+                            .title(args.join(",")),
+                        Events::new(),
+                        Self::render(&cmd.text),
+                    )
                 } else if cmd_name == "rb" {
                     Html::ruby(
                         Attributes::new(),
@@ -95,207 +240,27 @@ impl Styled for ChatMessage {
                 "user-select": "text";
             }
 
-            ".base [data-cmd~='box']" {
-                "display": "block";
-                "overflow-y": "scroll";
-                "max-height": "15em";
-                "padding-left": ".35rem";
-                "border-left": format!(".35rem solid {}", crate::libs::color::Pallet::gray(3));
-            }
-
-            ".base [data-cmd~='div']" {
-                "display": "block";
-            }
-
-            ".base [data-cmd~='span']" {
-                "display": "inline-block";
-            }
-
-            ".base [data-cmd~='grid']" {
+            ".parse-error" {
                 "display": "grid";
-                "grid-template-columns": "repeat(12, 1fr)";
                 "grid-auto-rows": "max-content";
-                "column-gap": "0.25ch";
-                "row-gap": "0.375em";
-            }
-
-            ".base [data-cmd~='1fr']" {
-                "grid-area": "auto / span 1";
-            }
-
-            ".base [data-cmd~='2fr']" {
-                "grid-area": "auto / span 2";
-            }
-
-            ".base [data-cmd~='3fr']" {
-                "grid-area": "auto / span 3";
-            }
-
-            ".base [data-cmd~='4fr']" {
-                "grid-area": "auto / span 4";
-            }
-
-            ".base [data-cmd~='5fr']" {
-                "grid-area": "auto / span 5";
-            }
-
-            ".base [data-cmd~='6fr']" {
-                "grid-area": "auto / span 6";
-            }
-
-            ".base [data-cmd~='7fr']" {
-                "grid-area": "auto / span 7";
-            }
-
-            ".base [data-cmd~='8fr']" {
-                "grid-area": "auto / span 8";
-            }
-
-            ".base [data-cmd~='9fr']" {
-                "grid-area": "auto / span 9";
-            }
-
-            ".base [data-cmd~='10fr']" {
-                "grid-area": "auto / span 10";
-            }
-
-            ".base [data-cmd~='11fr']" {
-                "grid-area": "auto / span 11";
-            }
-
-            ".base [data-cmd~='12fr']" {
-                "grid-area": "auto / span 12";
-            }
-
-            ".base [data-cmd~='sans-serif']" {
-                "font-family": "sans-serif";
+                "row-gap": ".15rem";
+                "font-size": "0.9em";
+                "overflow": "hidden";
             }
 
-            ".base [data-cmd~='serif']" {
-                "font-family": "serif";
+            ".parse-error-message" {
+                "color": crate::libs::color::Pallet::red(7);
             }
 
-            ".base [data-cmd~='mono']" {
+            ".parse-error-line" {
                 "font-family": "monospace";
+                "white-space": "pre";
+                "overflow-x": "auto";
             }
 
-            ".base [data-cmd~='bold']" {
-                "font-weight": "bold";
-            }
-
-            ".base [data-cmd~='nb']" {
-                "word-break": "keep-all";
-                "white-space": "nowrap";
-            }
-
-            ".base [data-cmd~='left']" {
-                "text-align": "left";
-            }
-
-            ".base [data-cmd~='right']" {
-                "text-align": "right";
-            }
-
-            ".base [data-cmd~='center']" {
-                "text-align": "center";
-            }
-
-            ".base [data-cmd~='large']" {
-                "font-size": "1.25em";
-            }
-
-            ".base [data-cmd~='huge']" {
-                "font-size": "1.5em";
-            }
-
-            ".base [data-cmd~='light']" {
-                "color": format!("{} !important",crate::libs::color::Pallet::gray(0));
-                "background-color": crate::libs::color::Pallet::gray(9);
-            }
-
-            ".base [data-cmd~='dark']" {
-                "color": format!("{} !important",crate::libs::color::Pallet::gray(9));
-                "background-color": crate::libs::color::Pallet::gray(0);
-            }
-
-            ".base [data-cmd~='red']" {
-                "color": format!("{} !important",crate::libs::color::Pallet::red(7));
-                "background-color": crate::libs::color::Pallet::gray(0);
-            }
-
-            ".base [data-cmd~='orange']" {
-                "color":format!("{} !important", crate::libs::color::Pallet::orange(5));
-                "background-color": crate::libs::color::Pallet::gray(0);
-            }
-
-            ".base [data-cmd~='yellow']" {
-                "color":format!("{} !important", crate::libs::color::Pallet::yellow(8));
-                "background-color": crate::libs::color::Pallet::gray(0);
-            }
-
-            ".base [data-cmd~='green']" {
-                "color": format!("{} !important",crate::libs::color::Pallet::green(7));
-                "background-color": crate::libs::color::Pallet::gray(0);
-            }
-
-            ".base [data-cmd~='blue']" {
-                "color":format!("{} !important", crate::libs::color::Pallet::blue(5));
-                "background-color": crate::libs::color::Pallet::gray(0);
-            }
-
-            ".base [data-cmd~='purple']" {
-                "color": format!("{} !important",crate::libs::color::Pallet::purple(5));
-                "background-color": crate::libs::color::Pallet::gray(0);
-            }
-
-            ".base [data-cmd~='pink']" {
-                "color": format!("{} !important",crate::libs::color::Pallet::pink(7));
-                "background-color": crate::libs::color::Pallet::gray(0);
-            }
-
-            ".base [data-cmd~='bg-light']" {
-                "color": crate::libs::color::Pallet::gray(9);
-                "background-color": format!("{} !important", crate::libs::color::Pallet::gray(0));
-            }
-
-            ".base [data-cmd~='bg-dark']" {
-                "color": crate::libs::color::Pallet::gray(0);
-                "background-color": format!("{} !important",crate::libs::color::Pallet::gray(9));
-            }
-
-            ".base [data-cmd~='bg-red']" {
-                "color": crate::libs::color::Pallet::gray(0);
-                "background-color": format!("{} !important",crate::libs::color::Pallet::red(7));
-            }
-
-            ".base [data-cmd~='bg-orange']" {
-                "color": crate::libs::color::Pallet::gray(0);
-                "background-color": format!("{} !important",crate::libs::color::Pallet::orange(5));
-            }
-
-            ".base [data-cmd~='bg-yellow']" {
-                "color": crate::libs::color::Pallet::gray(0);
-                "background-color": format!("{} !important",crate::libs::color::Pallet::yellow(8));
-            }
-
-            ".base [data-cmd~='bg-green']" {
-                "color": crate::libs::color::Pallet::gray(0);
-                "background-color": format!("{} !important",crate::libs::color::Pallet::green(7));
-            }
-
-            ".base [data-cmd~='bg-blue']" {
-                "color": crate::libs::color::Pallet::gray(0);
-                "background-color": format!("{} !important",crate::libs::color::Pallet::blue(5));
-            }
-
-            ".base [data-cmd~='bg-purple']" {
-                "color": crate::libs::color::Pallet::gray(0);
-                "background-color": crate::libs::color::Pallet::purple(5);
-            }
-
-            ".base [data-cmd~='bg-pink']" {
+            ".parse-error-mark" {
                 "color": crate::libs::color::Pallet::gray(0);
-                "background-color": crate::libs::color::Pallet::pink(7);
+                "background-color": crate::libs::color::Pallet::red(7);
             }
         }
     }
@@ -1,3 +1,6 @@
+use super::atom::btn::Btn;
+use super::atom::fa;
+use crate::arena::block::character::sheet::{self, Mapping, Sheet};
 use crate::arena::resource::{self, LoadFrom};
 use isaribi::{
     style,
@@ -7,25 +10,32 @@ use kagura::prelude::*;
 use nusa::prelude::*;
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
 
 pub struct Props {
     pub ok_to_catch_file: bool,
+    pub sheet_mappings: Rc<Vec<Mapping>>,
 }
 
 pub enum Msg {
     NoOp,
     Sub(On),
     SetIsShowingOverlay(bool),
-    LoadFiles(Vec<web_sys::File>),
+    LoadFiles(Vec<web_sys::File>, [f64; 2]),
+    SetMessage(Option<String>),
 }
 
 pub enum On {
     LoadImageData(resource::ImageData),
+    LoadCharacterSheet(Sheet, [f64; 2]),
+    LoadSheetMapping(Mapping),
 }
 
 pub struct FileCatcher {
     is_showing_overlay: bool,
     ok_to_catch_file: bool,
+    sheet_mappings: Rc<Vec<Mapping>>,
+    message: Option<String>,
 }
 
 impl Component for FileCatcher {
@@ -41,6 +51,8 @@ impl Constructor for FileCatcher {
         Self {
             is_showing_overlay: false,
             ok_to_catch_file: props.ok_to_catch_file,
+            sheet_mappings: props.sheet_mappings,
+            message: None,
         }
     }
 }
@@ -48,6 +60,7 @@ impl Constructor for FileCatcher {
 impl Update for FileCatcher {
     fn on_load(mut self: Pin<&mut Self>, props: Props) -> Cmd<Self> {
         self.ok_to_catch_file = props.ok_to_catch_file;
+        self.sheet_mappings = props.sheet_mappings;
         Cmd::none()
     }
 
@@ -59,8 +72,13 @@ impl Update for FileCatcher {
                 self.is_showing_overlay = is_showing_overlay;
                 Cmd::none()
             }
-            Msg::LoadFiles(files) => {
+            Msg::SetMessage(message) => {
+                self.message = message;
+                Cmd::none()
+            }
+            Msg::LoadFiles(files, page_coord) => {
                 self.is_showing_overlay = false;
+                self.message = None;
                 let mut cmds = vec![];
                 for file in files {
                     let file_type = file.type_();
@@ -81,6 +99,49 @@ impl Update for FileCatcher {
                                 }
                             })
                         });
+                    } else if file_type == "application/json" || file.name().ends_with(".json") {
+                        cmds.push({
+                            let file = Rc::clone(&file);
+                            let sheet_mappings = Rc::clone(&self.sheet_mappings);
+                            Cmd::task(async move {
+                                let text =
+                                    unwrap!(JsFuture::from(file.text()).await.ok(); Cmd::none());
+                                let text = unwrap!(text.as_string(); Cmd::none());
+                                match sheet::import(&sheet_mappings, &text) {
+                                    Ok(sheet) => {
+                                        Cmd::submit(On::LoadCharacterSheet(sheet, page_coord))
+                                    }
+                                    Err(err) => Cmd::chain(Msg::SetMessage(Some(format!(
+                                        "{}をキャラクターシートとして読み込めません：{}",
+                                        file.name(),
+                                        err
+                                    )))),
+                                }
+                            })
+                        });
+                    } else if file.name().ends_with(".toml") {
+                        cmds.push({
+                            let file = Rc::clone(&file);
+                            Cmd::task(async move {
+                                let text =
+                                    unwrap!(JsFuture::from(file.text()).await.ok(); Cmd::none());
+                                let text = unwrap!(text.as_string(); Cmd::none());
+                                match Mapping::parse(&text) {
+                                    Ok(mapping) => Cmd::list(vec![
+                                        Cmd::chain(Msg::SetMessage(Some(format!(
+                                            "キャラクターシートの対応表「{}」を追加しました",
+                                            mapping.name
+                                        )))),
+                                        Cmd::submit(On::LoadSheetMapping(mapping)),
+                                    ]),
+                                    Err(err) => Cmd::chain(Msg::SetMessage(Some(format!(
+                                        "{}は対応表として不正です：{}",
+                                        file.name(),
+                                        err
+                                    )))),
+                                }
+                            })
+                        });
                     }
                 }
 
@@ -122,7 +183,7 @@ impl Render<Html> for FileCatcher {
                             files.push(file);
                         }
                     }
-                    Msg::LoadFiles(files)
+                    Msg::LoadFiles(files, [e.page_x() as f64, e.page_y() as f64])
                 }),
             vec![
                 Html::fragment(children),
@@ -135,6 +196,23 @@ impl Render<Html> for FileCatcher {
                 } else {
                     Html::none()
                 },
+                self.message
+                    .as_ref()
+                    .map(|message| {
+                        Html::div(
+                            Attributes::new().class(Self::class("message")),
+                            Events::new(),
+                            vec![
+                                Html::text(message),
+                                Btn::secondary(
+                                    Attributes::new(),
+                                    Events::new().on_click(self, |_| Msg::SetMessage(None)),
+                                    vec![fa::fas_i("fa-xmark")],
+                                ),
+                            ],
+                        )
+                    })
+                    .unwrap_or(Html::none()),
             ],
         ))
     }
@@ -152,6 +230,22 @@ impl Styled for FileCatcher {
                 "background-color": crate::libs::color::Pallet::gray(9).a(30);
                 "z-index": super::constant::z_index::OVERLAY;
             }
+
+            ".message" {
+                "position": "fixed";
+                "bottom": "1rem";
+                "left": "50%";
+                "transform": "translateX(-50%)";
+                "display": "grid";
+                "grid-template-columns": "1fr max-content";
+                "align-items": "center";
+                "column-gap": ".5rem";
+                "padding": ".5rem 1rem";
+                "max-width": "80vw";
+                "background-color": crate::libs::color::Pallet::gray(9);
+                "color": crate::libs::color::Pallet::gray(0);
+                "z-index": super::constant::z_index::OVERLAY;
+            }
         }
     }
 }
@@ -1,5 +1,6 @@
 use super::page::room::{self, Room};
 use crate::arena::{block, Arena, BlockKind, BlockMut, BlockRef, Pack, PackDepth, Untyped};
+use crate::dicebot::exchange::DiceExchange;
 use crate::libs::bcdice::js::DynamicLoader;
 use crate::libs::js_object::Object;
 use crate::libs::random_id::U128Id;
@@ -20,6 +21,7 @@ pub struct Props {
     pub room_id: Rc<String>,
     pub client_id: Rc<String>,
     pub bcdice_loader: Rc<DynamicLoader>,
+    pub bcdice_api_root: Option<Rc<String>>,
 }
 
 pub enum Msg {
@@ -40,6 +42,7 @@ pub enum Msg {
     },
     ReceiveGetBlockResponse {
         data: JsValue,
+        is_posted: bool,
     },
 }
 
@@ -49,17 +52,20 @@ pub struct SkywayConnecter {
     arena: Arena,
     client_id: Rc<String>,
     bcdice_loader: Rc<DynamicLoader>,
+    bcdice_api_root: Option<Rc<String>>,
     annot_room_id: Rc<String>,
 
     peer: Rc<Peer>,
     room: Rc<MeshRoom>,
     connections: HashMap<Rc<String>, Rc<DataConnection>>,
+    dice_exchange: Rc<DiceExchange>,
     rquesting_arena_blocks: Rc<RefCell<HashSet<U128Id>>>,
 
     world: Option<BlockMut<block::World>>,
     chat: Option<BlockMut<block::Chat>>,
 
     update_blocks: Rc<RefCell<HashSet<U128Id>>>,
+    posted_blocks: Rc<RefCell<HashSet<U128Id>>>,
 }
 
 impl Component for SkywayConnecter {
@@ -76,8 +82,13 @@ impl Constructor for SkywayConnecter {
             arena: Arena::new(),
             client_id: props.client_id,
             bcdice_loader: props.bcdice_loader,
+            bcdice_api_root: props.bcdice_api_root,
             annot_room_id: Rc::new(format!("skyway/{}", props.room_id)),
 
+            dice_exchange: Rc::new(DiceExchange::new(
+                Rc::clone(&props.room),
+                Rc::clone(&props.peer_id),
+            )),
             peer: props.peer,
             room: props.room,
             connections: HashMap::new(),
@@ -87,6 +98,7 @@ impl Constructor for SkywayConnecter {
             chat: None,
 
             update_blocks: Rc::new(RefCell::new(HashSet::new())),
+            posted_blocks: Rc::new(RefCell::new(HashSet::new())),
         }
     }
 }
@@ -132,6 +144,18 @@ impl Update for SkywayConnecter {
                 }) as Box<dyn FnMut(JsValue)>);
                 self.room.on("peerJoin", Some(a.as_ref().unchecked_ref()));
                 a.forget();
+
+                let a = Closure::wrap(Box::new({
+                    let dice_exchange = Rc::clone(&self.dice_exchange);
+                    move |peer_id: JsValue| {
+                        if let Some(peer_id) = peer_id.as_string() {
+                            crate::debug::log_2("peerLeave", peer_id.as_str());
+                            dice_exchange.remove_member(&peer_id);
+                        }
+                    }
+                }) as Box<dyn FnMut(JsValue)>);
+                self.room.on("peerLeave", Some(a.as_ref().unchecked_ref()));
+                a.forget();
             })),
             Cmd::batch(kagura::util::Batch::new(|mut resolve| {
                 let a = Closure::wrap(Box::new(move |connection: JsValue| {
@@ -153,15 +177,25 @@ impl Update for SkywayConnecter {
 
                 let a = Closure::wrap(Box::new({
                     let resolve = Rc::clone(&resolve);
+                    let dice_exchange = Rc::clone(&self.dice_exchange);
                     move |data: JsValue| {
-                        let data = unwrap!(data.dyn_ref::<Object>().and_then(|x| x.get("data")));
+                        let data = unwrap!(data.dyn_ref::<Object>());
+                        let src = unwrap!(data.get("src").and_then(|x| x.as_string()));
+                        let data = unwrap!(data.get("data"));
                         match skyway::Msg::from(&data) {
                             skyway::Msg::PostBlock(data) => {
                                 crate::debug::log_2("PostBlock", &data);
                                 resolve.borrow_mut()(Cmd::chain(Msg::ReceiveGetBlockResponse {
                                     data,
+                                    is_posted: true,
                                 }));
                             }
+                            skyway::Msg::DiceCommit { roll, commitment } => {
+                                dice_exchange.receive_commit(src, roll, commitment);
+                            }
+                            skyway::Msg::DiceEntropy { roll, entropy } => {
+                                dice_exchange.receive_entropy(src, roll, entropy);
+                            }
                             _ => {}
                         }
                     }
@@ -196,6 +230,7 @@ impl Update for SkywayConnecter {
             } => {
                 self.connections
                     .insert(Rc::clone(&peer_id), Rc::clone(&connection));
+                self.dice_exchange.add_member(connection.remote_id());
                 Cmd::list(vec![
                     self.batch_connection(peer_id, Rc::clone(&connection)),
                     if send_arena {
@@ -251,13 +286,17 @@ impl Update for SkywayConnecter {
                 }
             }
 
-            Msg::ReceiveGetBlockResponse { data } => {
+            Msg::ReceiveGetBlockResponse { data, is_posted } => {
                 let arena = self.arena.as_mut();
                 let update_blocks = Rc::clone(&self.update_blocks);
+                let posted_blocks = Rc::clone(&self.posted_blocks);
 
                 Cmd::task(async move {
                     if let Some(block) = BlockMut::<Untyped>::unpack(&data, arena).await {
                         update_blocks.borrow_mut().insert(block.id());
+                        if is_posted {
+                            posted_blocks.borrow_mut().insert(block.id());
+                        }
                         match block.kind() {
                             BlockKind::World => {
                                 return Cmd::chain(Msg::SetWorld(block.type_as::<block::World>()));
@@ -287,11 +326,14 @@ impl Render<Html> for SkywayConnecter {
                 annot_room_id: Rc::clone(&self.annot_room_id),
 
                 bcdice_loader: Rc::clone(&self.bcdice_loader),
+                bcdice_api_root: self.bcdice_api_root.as_ref().map(Rc::clone),
+                dice_exchange: Rc::clone(&self.dice_exchange),
 
                 world: self.world.as_ref().map(|world| BlockMut::clone(&world)),
                 chat: self.chat.as_ref().map(|chat| BlockMut::clone(&chat)),
 
                 update_blocks: self.update_blocks.borrow_mut().drain().collect(),
+                posted_blocks: self.posted_blocks.borrow_mut().drain().collect(),
             },
             Sub::map(|sub| match sub {
                 room::On::UpdateBlocks { insert, update } => Msg::UpdateBlocks { insert, update },
@@ -339,7 +381,10 @@ impl SkywayConnecter {
                     }
                     skyway::Msg::GetBlockResponse(data) => {
                         crate::debug::log_2("GetBlockResponse", &data);
-                        resolve.borrow_mut()(Cmd::chain(Msg::ReceiveGetBlockResponse { data }));
+                        resolve.borrow_mut()(Cmd::chain(Msg::ReceiveGetBlockResponse {
+                            data,
+                            is_posted: false,
+                        }));
                     }
                     _ => {}
                 }
@@ -13,6 +13,7 @@ use isaribi::{
 use kagura::prelude::*;
 use nusa::prelude::*;
 use std::collections::HashSet;
+use std::rc::Rc;
 
 mod node;
 use node::Node;
@@ -22,6 +23,8 @@ pub use node::ViewMode;
 pub struct Props {
     pub arena: ArenaMut,
     pub data: BlockMut<block::Property>,
+    pub properties: Vec<BlockMut<block::Property>>,
+    pub client_id: Rc<String>,
 }
 
 pub enum Msg {
@@ -42,6 +45,8 @@ pub enum On {
 pub struct BlockProp {
     arena: ArenaMut,
     prop: BlockMut<block::Property>,
+    properties: Vec<BlockMut<block::Property>>,
+    client_id: Rc<String>,
     view_mode: ViewMode,
 }
 
@@ -58,6 +63,8 @@ impl Constructor for BlockProp {
         Self {
             arena: props.arena,
             prop: props.data,
+            properties: props.properties,
+            client_id: props.client_id,
             view_mode: ViewMode::View,
         }
     }
@@ -67,6 +74,8 @@ impl Update for BlockProp {
     fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
         self.arena = props.arena;
         self.prop = props.data;
+        self.properties = props.properties;
+        self.client_id = props.client_id;
         Cmd::none()
     }
 
@@ -95,6 +104,8 @@ impl Render<Html> for BlockProp {
                     node::Props {
                         arena: ArenaMut::clone(&self.arena),
                         data: BlockMut::clone(&self.prop),
+                        properties: self.properties.clone(),
+                        client_id: Rc::clone(&self.client_id),
                         view_mode: self.view_mode,
                     },
                     Sub::map(|sub| match sub {
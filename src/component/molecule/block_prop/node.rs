@@ -14,10 +14,13 @@ use isaribi::{
 use kagura::prelude::*;
 use nusa::prelude::*;
 use std::collections::HashSet;
+use std::rc::Rc;
 
 pub struct Props {
     pub arena: ArenaMut,
     pub data: BlockMut<block::Property>,
+    pub properties: Vec<BlockMut<block::Property>>,
+    pub client_id: Rc<String>,
     pub view_mode: ViewMode,
 }
 
@@ -36,6 +39,7 @@ pub enum Msg {
     PushRow,
     SetPropertyView(block::property::PropertyView),
     SetDataView(block::property::DataView),
+    SetTokenVisibility(Option<Vec<String>>),
     SetValue(usize, usize, block::property::Value),
     RemoveChild(U128Id),
     RemoveValue(usize, usize),
@@ -54,6 +58,8 @@ pub enum On {
 pub struct Node {
     arena: ArenaMut,
     prop: BlockMut<block::Property>,
+    properties: Vec<BlockMut<block::Property>>,
+    client_id: Rc<String>,
     view_mode: ViewMode,
 }
 
@@ -70,6 +76,8 @@ impl Constructor for Node {
         Self {
             arena: props.arena,
             prop: props.data,
+            properties: props.properties,
+            client_id: props.client_id,
             view_mode: props.view_mode,
         }
     }
@@ -79,6 +87,8 @@ impl Update for Node {
     fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
         self.arena = props.arena;
         self.prop = props.data;
+        self.properties = props.properties;
+        self.client_id = props.client_id;
         self.view_mode = props.view_mode;
         Cmd::none()
     }
@@ -152,6 +162,17 @@ impl Update for Node {
                     update: set! {self.prop.id()},
                 })
             }
+            Msg::SetTokenVisibility(token_viewers) => {
+                self.prop.update(|prop| {
+                    prop.set_is_shown_on_token(token_viewers.is_some());
+                    prop.set_token_viewers(token_viewers.unwrap_or_default());
+                });
+
+                Cmd::submit(On::UpdateBlocks {
+                    insert: set! {},
+                    update: set! {self.prop.id()},
+                })
+            }
             Msg::SetValue(row_idx, col_idx, value) => {
                 self.prop.update(|prop| {
                     prop.data_mut().set_value(row_idx, col_idx, value);
@@ -226,6 +247,8 @@ impl Node {
                             Props {
                                 arena: ArenaMut::clone(&self.arena),
                                 data: BlockMut::clone(&child_prop),
+                                properties: self.properties.clone(),
+                                client_id: Rc::clone(&self.client_id),
                                 view_mode: self.view_mode,
                             },
                             Sub::map(|sub| match sub {
@@ -271,6 +294,19 @@ impl Node {
                     Events::new().on_input(self, |name| Msg::SetName(name)),
                     vec![],
                 ),
+                if prop.is_shown_on_token() {
+                    Html::span(
+                        Attributes::new().class(Self::class("token-visibility")),
+                        Events::new(),
+                        vec![fa::fas_i(if prop.token_viewers().is_empty() {
+                            "fa-eye"
+                        } else {
+                            "fa-user-lock"
+                        })],
+                    )
+                } else {
+                    Html::none()
+                },
                 Dropdown::new(
                     self,
                     None,
@@ -349,6 +385,40 @@ impl Node {
                                     ),
                                 ],
                             ),
+                            Html::span(
+                                Attributes::new()
+                                    .class(Dropdown::class("menu-heading"))
+                                    .class(Btn::class_name(&btn::Variant::LightLikeMenu)),
+                                Events::new(),
+                                vec![Text::span("トークンに表示")],
+                            ),
+                            Btn::group(
+                                Attributes::new(),
+                                Events::new(),
+                                vec![
+                                    Btn::light(
+                                        Attributes::new(),
+                                        Events::new()
+                                            .on_click(self, |_| Msg::SetTokenVisibility(None)),
+                                        vec![Html::text("非表示")],
+                                    ),
+                                    Btn::light(
+                                        Attributes::new(),
+                                        Events::new().on_click(self, |_| {
+                                            Msg::SetTokenVisibility(Some(vec![]))
+                                        }),
+                                        vec![Html::text("全員")],
+                                    ),
+                                    Btn::light(
+                                        Attributes::new(),
+                                        Events::new().on_click(self, {
+                                            let client_id = self.client_id.as_ref().clone();
+                                            move |_| Msg::SetTokenVisibility(Some(vec![client_id]))
+                                        }),
+                                        vec![Html::text("自分のみ")],
+                                    ),
+                                ],
+                            ),
                         ],
                     ),
                 ),
@@ -483,6 +553,10 @@ impl Node {
         col_idx: usize,
         value: &block::property::Value,
     ) -> Html {
+        let computed = value
+            .clone()
+            .compute(&self.properties)
+            .unwrap_or_else(|_| value.clone());
         Html::div(
             attrs.class(Self::class("value")),
             Events::new(),
@@ -505,6 +579,9 @@ impl Node {
                     }
                     block::property::Value::Check(v) => self.render_value_as_check(v),
                     block::property::Value::Select(v, l) => self.render_value_as_select(v, l),
+                    block::property::Value::Formula(v) => {
+                        self.render_value_as_formula(row_idx, col_idx, v)
+                    }
                 },
                 match &self.view_mode {
                     ViewMode::Edit => Dropdown::new(
@@ -529,7 +606,7 @@ impl Node {
                                 Btn::menu_as_light(
                                     Attributes::new(),
                                     Events::new().on_click(self, {
-                                        let value = value.to_number();
+                                        let value = computed.to_number();
                                         move |_| Msg::SetValue(row_idx, col_idx, value)
                                     }),
                                     vec![Html::text("数値")],
@@ -537,7 +614,7 @@ impl Node {
                                 Btn::menu_as_light(
                                     Attributes::new(),
                                     Events::new().on_click(self, {
-                                        let value = value.to_number_min_max();
+                                        let value = computed.to_number_min_max();
                                         move |_| Msg::SetValue(row_idx, col_idx, value)
                                     }),
                                     vec![Html::text("リソース")],
@@ -545,7 +622,7 @@ impl Node {
                                 Btn::menu_as_light(
                                     Attributes::new(),
                                     Events::new().on_click(self, {
-                                        let value = value.to_number_mid();
+                                        let value = computed.to_number_mid();
                                         move |_| Msg::SetValue(row_idx, col_idx, value)
                                     }),
                                     vec![Html::text("カウンター")],
@@ -569,7 +646,7 @@ impl Node {
                                 Btn::menu_as_light(
                                     Attributes::new(),
                                     Events::new().on_click(self, {
-                                        let value = value.to_check();
+                                        let value = computed.to_check();
                                         move |_| Msg::SetValue(row_idx, col_idx, value)
                                     }),
                                     vec![Html::text("チェックボックス")],
@@ -582,6 +659,14 @@ impl Node {
                                     }),
                                     vec![Html::text("セレクトリスト")],
                                 ),
+                                Btn::menu_as_light(
+                                    Attributes::new(),
+                                    Events::new().on_click(self, {
+                                        let value = value.to_formula();
+                                        move |_| Msg::SetValue(row_idx, col_idx, value)
+                                    }),
+                                    vec![Html::text("計算式")],
+                                ),
                             ],
                         ),
                     ),
@@ -710,6 +795,37 @@ impl Node {
         )
     }
 
+    fn render_value_as_formula(&self, row_idx: usize, col_idx: usize, value: &String) -> Html {
+        let computed = block::property::Value::Formula(value.clone()).compute(&self.properties);
+        Html::div(
+            Attributes::new().class(Self::class("formula")),
+            Events::new(),
+            vec![
+                match &self.view_mode {
+                    ViewMode::Edit => Html::input(
+                        Attributes::new().type_("text").value(value),
+                        Events::new().on_input(self, move |value| {
+                            Msg::SetValue(row_idx, col_idx, block::property::Value::Formula(value))
+                        }),
+                        vec![],
+                    ),
+                    ViewMode::View => Text::span(value),
+                },
+                match computed {
+                    Ok(computed) => Text::span(format!("＝ {}", computed)),
+                    Err(err) => Html::span(
+                        Attributes::new().class(Self::class("formula-error")),
+                        Events::new(),
+                        vec![
+                            fa::fas_i("fa-triangle-exclamation"),
+                            Html::text(format!(" {}", err)),
+                        ],
+                    ),
+                },
+            ],
+        )
+    }
+
     fn render_value_as_check(&self, value: &bool) -> Html {
         Html::div(Attributes::new(), Events::new(), vec![])
     }
@@ -752,6 +868,12 @@ impl Styled for Node {
                 "display": "none";
             }
 
+            ".token-visibility" {
+                "line-height": "1.5";
+                "padding": ".5em";
+                "color": format!("{}", crate::libs::color::Pallet::gray(5));
+            }
+
             ".data.view-list" {
                 "display": "flex";
                 "flex-direction": "column";
@@ -769,6 +891,17 @@ impl Styled for Node {
                 "flex-grow": "1";
             }
 
+            ".formula" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content";
+                "align-items": "center";
+                "column-gap": ".35rem";
+            }
+
+            ".formula-error" {
+                "color": crate::libs::color::Pallet::red(7);
+            }
+
             ".children" {
                 "display": "flex";
             }
@@ -0,0 +1,162 @@
+use super::atom::{btn::Btn, fa};
+use crate::arena::{block, ArenaMut, BlockMut};
+use crate::libs::random_id::U128Id;
+use isaribi::{
+    style,
+    styled::{Style, Styled},
+};
+use kagura::prelude::*;
+use nusa::prelude::*;
+use std::collections::HashSet;
+
+pub struct Props {
+    pub arena: ArenaMut,
+    pub world: BlockMut<block::World>,
+}
+
+pub enum Msg {
+    Sub(On),
+    CreateDiceTable,
+    RemoveDiceTable(U128Id),
+}
+
+pub enum On {
+    OpenDiceTable(U128Id),
+    UpdateBlocks {
+        insert: HashSet<U128Id>,
+        update: HashSet<U128Id>,
+    },
+}
+
+pub struct DiceTableList {
+    arena: ArenaMut,
+    world: BlockMut<block::World>,
+}
+
+impl Component for DiceTableList {
+    type Props = Props;
+    type Msg = Msg;
+    type Event = On;
+}
+
+impl HtmlComponent for DiceTableList {}
+
+impl Constructor for DiceTableList {
+    fn constructor(props: Self::Props) -> Self {
+        Self {
+            arena: props.arena,
+            world: props.world,
+        }
+    }
+}
+
+impl Update for DiceTableList {
+    fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
+        self.arena = props.arena;
+        self.world = props.world;
+        Cmd::none()
+    }
+
+    fn update(mut self: Pin<&mut Self>, msg: Msg) -> Cmd<Self> {
+        match msg {
+            Msg::Sub(sub) => Cmd::submit(sub),
+            Msg::CreateDiceTable => {
+                let dice_table = self.arena.insert(block::DiceTable::new());
+                let dice_table_id = dice_table.id();
+                self.world.update(|world| {
+                    world.push_dice_table(dice_table);
+                });
+
+                Cmd::list(vec![
+                    Cmd::submit(On::UpdateBlocks {
+                        insert: set! { dice_table_id },
+                        update: set! { self.world.id() },
+                    }),
+                    Cmd::submit(On::OpenDiceTable(dice_table_id)),
+                ])
+            }
+            Msg::RemoveDiceTable(dice_table_id) => {
+                self.world.update(|world| {
+                    world.remove_dice_table(&dice_table_id);
+                });
+
+                Cmd::submit(On::UpdateBlocks {
+                    insert: set! {},
+                    update: set! { self.world.id() },
+                })
+            }
+        }
+    }
+}
+
+impl Render<Html> for DiceTableList {
+    type Children = ();
+    fn render(&self, _: Self::Children) -> Html {
+        Self::styled(Html::div(
+            Attributes::new().class(Self::class("base")),
+            Events::new(),
+            self.world
+                .map(|world| {
+                    world
+                        .dice_tables()
+                        .iter()
+                        .filter_map(|dice_table| self.render_dice_table(dice_table))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .chain(vec![Btn::secondary(
+                    Attributes::new().class(Self::class("create")),
+                    Events::new().on_click(self, |_| Msg::CreateDiceTable),
+                    vec![fa::fas_i("fa-plus"), Html::text(" ダイス表を作成")],
+                )])
+                .collect(),
+        ))
+    }
+}
+
+impl DiceTableList {
+    fn render_dice_table(&self, dice_table: &BlockMut<block::DiceTable>) -> Option<Html> {
+        let dice_table_id = dice_table.id();
+        dice_table.map(|dice_table| {
+            Html::fragment(vec![
+                Btn::menu(
+                    Attributes::new(),
+                    Events::new().on_click(self, {
+                        let dice_table_id = U128Id::clone(&dice_table_id);
+                        move |_| Msg::Sub(On::OpenDiceTable(dice_table_id))
+                    }),
+                    vec![
+                        fa::fas_i("fa-table-list"),
+                        Html::text(format!(" {}（{}）", dice_table.name(), dice_table.dice())),
+                    ],
+                ),
+                Btn::danger(
+                    Attributes::new(),
+                    Events::new().on_click(self, move |_| Msg::RemoveDiceTable(dice_table_id)),
+                    vec![fa::fas_i("fa-trash")],
+                ),
+            ])
+        })
+    }
+}
+
+impl Styled for DiceTableList {
+    fn style() -> Style {
+        style! {
+            ".base" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content";
+                "grid-auto-rows": "max-content";
+                "row-gap": ".35rem";
+                "column-gap": ".35rem";
+                "padding-left": ".35rem";
+                "padding-right": ".35rem";
+            }
+
+            ".create" {
+                "grid-column": "1 / -1";
+            }
+        }
+    }
+}
@@ -1,9 +1,13 @@
 pub mod component_list;
+pub mod dice_table_list;
 pub mod modal_chat_capture;
+pub mod modal_chat_export;
+pub mod modal_chat_macro;
 pub mod modal_chat_user;
 pub mod modal_chatpallet;
 pub mod modal_create_block_texture;
 pub mod modal_create_terran_texture;
+pub mod modal_dice_stats;
 pub mod modal_dicebot;
 pub mod modal_notification;
 pub mod modal_resource;
@@ -14,6 +18,8 @@ pub mod room_modeless_boxblock;
 pub mod room_modeless_character;
 pub mod room_modeless_chat;
 pub mod room_modeless_craftboard;
+pub mod room_modeless_dice_table;
+pub mod room_modeless_initiative;
 pub mod room_modeless_textboard;
 pub mod scene_list;
 pub mod tab_modeless_container;
@@ -0,0 +1,265 @@
+use crate::arena::{block, resource, BlockMut};
+use crate::component::atom::chat_message;
+use crate::libs::color::Pallet;
+use crate::libs::random_id::U128Id;
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+pub async fn to_html(channels: &Vec<BlockMut<block::ChatChannel>>) -> String {
+    let icons = load_icons(channels).await;
+
+    let body = channels
+        .iter()
+        .filter_map(|channel| channel.map(|channel| render_channel(channel, &icons)))
+        .collect::<Vec<_>>()
+        .join("");
+
+    format!(
+        concat!(
+            r#"<!DOCTYPE html>"#,
+            r#"<html lang="ja">"#,
+            r#"<head>"#,
+            r#"<meta charset="utf-8">"#,
+            r#"<title>{}</title>"#,
+            r#"<style>{}</style>"#,
+            r#"</head>"#,
+            r#"<body>{}</body>"#,
+            r#"</html>"#
+        ),
+        escape(&title(channels)),
+        style(),
+        body
+    )
+}
+
+pub fn to_text(channels: &Vec<BlockMut<block::ChatChannel>>) -> String {
+    channels
+        .iter()
+        .filter_map(|channel| {
+            channel.map(|channel| {
+                let messages = channel
+                    .messages()
+                    .iter()
+                    .filter_map(|message| {
+                        message.map(|message| {
+                            format!(
+                                "{} {}：{}",
+                                message
+                                    .timestamp()
+                                    .with_timezone(&chrono::Local)
+                                    .format("%Y/%m/%d %H:%M:%S"),
+                                message.sender().name(),
+                                message.message().to_plain_text()
+                            )
+                        })
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("[# {}]\n{}\n", channel.name(), messages)
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn title(channels: &Vec<BlockMut<block::ChatChannel>>) -> String {
+    if channels.len() == 1 {
+        channels[0]
+            .map(|channel| channel.name().clone())
+            .unwrap_or_else(|| String::from("chat"))
+    } else {
+        String::from("chat")
+    }
+}
+
+async fn load_icons(
+    channels: &Vec<BlockMut<block::ChatChannel>>,
+) -> HashMap<U128Id, String> {
+    let mut blobs = HashMap::new();
+
+    for channel in channels {
+        channel.map(|channel| {
+            for message in channel.messages() {
+                message.map(|message| {
+                    if let Some(icon) = message.sender().icon() {
+                        let icon_id = icon.id();
+                        if !blobs.contains_key(&icon_id) {
+                            if let Some(blob) = icon
+                                .map(|icon: &resource::ImageData| std::rc::Rc::clone(icon.blob()))
+                            {
+                                blobs.insert(icon_id, blob);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    let mut icons = HashMap::new();
+
+    for (icon_id, blob) in blobs {
+        let data = JsFuture::from(blob.array_buffer())
+            .await
+            .ok()
+            .and_then(|data| data.dyn_into::<js_sys::ArrayBuffer>().ok());
+        if let Some(data) = data {
+            let data = js_sys::Uint8Array::new(&data).to_vec();
+            icons.insert(
+                icon_id,
+                format!("data:{};base64,{}", blob.type_(), base64::encode(&data)),
+            );
+        }
+    }
+
+    icons
+}
+
+fn render_channel(channel: &block::ChatChannel, icons: &HashMap<U128Id, String>) -> String {
+    let messages = channel
+        .messages()
+        .iter()
+        .filter_map(|message| message.map(|message| render_message(message, icons)))
+        .collect::<Vec<_>>()
+        .join("");
+
+    format!(
+        r#"<section class="channel"><h1># {}</h1>{}</section>"#,
+        escape(channel.name()),
+        messages
+    )
+}
+
+fn render_message(message: &block::ChatMessage, icons: &HashMap<U128Id, String>) -> String {
+    let sender = message.sender();
+
+    let icon = sender
+        .icon()
+        .and_then(|icon| icons.get(&icon.id()))
+        .map(|src| format!(r#"<img class="icon" src="{}">"#, src))
+        .unwrap_or_else(|| match sender.kind() {
+            block::chat_message::SenderKind::Normal => format!(
+                r#"<div class="icon">{}</div>"#,
+                escape(&sender.name().chars().take(1).collect::<String>())
+            ),
+            block::chat_message::SenderKind::System => String::from(r#"<div class="icon"></div>"#),
+        });
+
+    let kind = match sender.kind() {
+        block::chat_message::SenderKind::Normal => "normal",
+        block::chat_message::SenderKind::System => "system",
+    };
+
+    format!(
+        concat!(
+            r#"<div class="message {}">"#,
+            r#"{}"#,
+            r#"<div class="heading">"#,
+            r#"<span class="sender">{}</span>"#,
+            r#"<span class="timestamp">{}</span>"#,
+            r#"</div>"#,
+            r#"<div class="content">{}</div>"#,
+            r#"</div>"#
+        ),
+        kind,
+        icon,
+        escape(sender.name()),
+        message
+            .timestamp()
+            .with_timezone(&chrono::Local)
+            .format("%Y/%m/%d %H:%M:%S"),
+        render_tokens(message.message())
+    )
+}
+
+fn render_tokens(message: &block::chat_message::Message) -> String {
+    message
+        .iter()
+        .map(|message_token| render_token(message_token))
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn render_token(message_token: &block::chat_message::MessageToken) -> String {
+    match message_token {
+        block::chat_message::MessageToken::Text(text) => escape(text),
+        block::chat_message::MessageToken::Reference(reference) => {
+            escape(&format!("{}", reference))
+        }
+        block::chat_message::MessageToken::Command(cmd) => {
+            let cmd_name = cmd.name.to_string();
+            if cmd_name == "div"
+                || cmd_name == "grid"
+                || cmd_name == "span"
+                || cmd_name == "box"
+                || cmd_name == ""
+            {
+                let mut cmds: Vec<_> = cmd.args.iter().map(|arg| format!("{}", arg)).collect();
+                cmds.push(cmd_name.clone());
+                format!(
+                    r#"<span data-cmd="{}">{}</span>"#,
+                    escape(&cmds.join(" ")),
+                    render_tokens(&cmd.text)
+                )
+            } else if cmd_name == "fas" || cmd_name == "far" || cmd_name == "fab" {
+                let args: Vec<_> = cmd.args.iter().map(|arg| format!("{}", arg)).collect();
+                format!(
+                    r#"<i class="{} {}">{}</i>"#,
+                    escape(&cmd_name),
+                    escape(&args.join(" ")),
+                    render_tokens(&cmd.text)
+                )
+            } else if cmd_name == "roll" {
+                let args: Vec<_> = cmd.args.iter().map(|arg| format!("{}", arg)).collect();
+                format!(
+                    r#"<span data-cmd="roll" title="{}">{}</span>"#,
+                    escape(&args.join(",")),
+                    render_tokens(&cmd.text)
+                )
+            } else if cmd_name == "rb" {
+                let ruby: Vec<_> = cmd.args.iter().map(|arg| render_tokens(&arg.value)).collect();
+                format!(
+                    "<ruby>{}<rp>《</rp><rt>{}</rt><rp>》</rp></ruby>",
+                    render_tokens(&cmd.text),
+                    ruby.join("")
+                )
+            } else {
+                format!(
+                    r#"<span data-cmd="{}">{}</span>"#,
+                    escape(&cmd_name),
+                    render_tokens(&cmd.text)
+                )
+            }
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn style() -> String {
+    format!(
+        r#"
+body {{ margin: 0; padding: 1rem; font-family: sans-serif; color: {gray_9}; background-color: {gray_0}; }}
+.channel h1 {{ font-size: 1.25rem; border-bottom: .1rem solid {gray_6}; }}
+.message {{ display: grid; grid-template-columns: max-content 1fr; grid-template-rows: max-content 1fr; column-gap: .65rem; padding: .35rem 0; }}
+.icon {{ grid-row: span 2; width: 4.5rem; height: 4.5rem; line-height: 1.5; font-size: 3rem; text-align: center; align-self: start; object-fit: cover; object-position: top; }}
+.heading {{ display: flex; justify-content: space-between; border-bottom: .1rem solid {gray_6}; }}
+.sender {{ font-size: 1.1em; }}
+.timestamp {{ color: {gray_7}; }}
+.content {{ overflow: hidden; white-space: pre-wrap; user-select: text; }}
+{command}
+"#,
+        gray_0 = Pallet::gray(0),
+        gray_6 = Pallet::gray(6),
+        gray_7 = Pallet::gray(7),
+        gray_9 = Pallet::gray(9),
+        command = chat_message::command_style(".content")
+    )
+}
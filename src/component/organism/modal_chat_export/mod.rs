@@ -0,0 +1,254 @@
+use super::atom::{btn::Btn, text::Text};
+use super::molecule::modal::{self, Modal};
+use crate::arena::{block, BlockMut};
+use crate::libs::element::download;
+use crate::libs::random_id::U128Id;
+use isaribi::{
+    style,
+    styled::{Style, Styled},
+};
+use kagura::prelude::*;
+use nusa::prelude::*;
+use std::collections::HashSet;
+
+mod log;
+
+pub struct Props {
+    pub chat: BlockMut<block::Chat>,
+}
+
+pub enum Msg {
+    Sub(On),
+    SetIsSelected(U128Id, bool),
+    SetIsSelectedAll(bool),
+    ExportAsHtml,
+    ExportAsText,
+}
+
+pub enum On {
+    Close,
+}
+
+pub struct ModalChatExport {
+    chat: BlockMut<block::Chat>,
+    selected: HashSet<U128Id>,
+}
+
+impl Component for ModalChatExport {
+    type Props = Props;
+    type Msg = Msg;
+    type Event = On;
+}
+
+impl HtmlComponent for ModalChatExport {}
+
+impl Constructor for ModalChatExport {
+    fn constructor(props: Self::Props) -> Self {
+        let selected = Self::channel_ids(&props.chat);
+        Self {
+            chat: props.chat,
+            selected,
+        }
+    }
+}
+
+impl Update for ModalChatExport {
+    fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
+        self.chat = props.chat;
+        Cmd::none()
+    }
+
+    fn update(mut self: Pin<&mut Self>, msg: Self::Msg) -> Cmd<Self> {
+        match msg {
+            Msg::Sub(sub) => Cmd::submit(sub),
+            Msg::SetIsSelected(channel_id, is_selected) => {
+                if is_selected {
+                    self.selected.insert(channel_id);
+                } else {
+                    self.selected.remove(&channel_id);
+                }
+                Cmd::none()
+            }
+            Msg::SetIsSelectedAll(is_selected) => {
+                self.selected = if is_selected {
+                    Self::channel_ids(&self.chat)
+                } else {
+                    HashSet::new()
+                };
+                Cmd::none()
+            }
+            Msg::ExportAsHtml => {
+                let channels = self.selected_channels();
+                Cmd::task(async move {
+                    let html = log::to_html(&channels).await;
+                    download(
+                        &format!("{}.html", log::title(&channels)),
+                        "text/html;charset=utf-8",
+                        &html,
+                    );
+                    Cmd::none()
+                })
+            }
+            Msg::ExportAsText => {
+                let channels = self.selected_channels();
+                download(
+                    &format!("{}.txt", log::title(&channels)),
+                    "text/plain;charset=utf-8",
+                    &log::to_text(&channels),
+                );
+                Cmd::none()
+            }
+        }
+    }
+}
+
+impl ModalChatExport {
+    fn channel_ids(chat: &BlockMut<block::Chat>) -> HashSet<U128Id> {
+        chat.map(|chat| chat.channels().iter().map(|channel| channel.id()).collect())
+            .unwrap_or_default()
+    }
+
+    fn selected_channels(&self) -> Vec<BlockMut<block::ChatChannel>> {
+        self.chat
+            .map(|chat| {
+                chat.channels()
+                    .iter()
+                    .filter(|channel| self.selected.contains(&channel.id()))
+                    .map(BlockMut::clone)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Render<Html> for ModalChatExport {
+    type Children = ();
+    fn render(&self, _: Self::Children) -> Html {
+        let channel_num = self
+            .chat
+            .map(|chat| chat.channels().len())
+            .unwrap_or(0);
+        let is_selected_all = self.selected.len() == channel_num;
+
+        Self::styled(Modal::new(
+            self,
+            None,
+            modal::Props {},
+            Sub::map(|sub| match sub {
+                modal::On::Close => Msg::Sub(On::Close),
+            }),
+            (
+                String::from("チャットログを保存"),
+                String::from(""),
+                vec![Html::div(
+                    Attributes::new().class(Self::class("base")),
+                    Events::new(),
+                    vec![
+                        Html::div(
+                            Attributes::new().class(Self::class("list")),
+                            Events::new(),
+                            vec![self.render_toggle(
+                                is_selected_all,
+                                String::from("全てのチャンネル"),
+                                |is_selected| Msg::SetIsSelectedAll(is_selected),
+                            )]
+                            .into_iter()
+                            .chain(
+                                self.chat
+                                    .map(|chat| {
+                                        chat.channels()
+                                            .iter()
+                                            .map(|channel| {
+                                                let channel_id = channel.id();
+                                                self.render_toggle(
+                                                    self.selected.contains(&channel_id),
+                                                    channel
+                                                        .map(|channel| {
+                                                            format!("# {}", channel.name())
+                                                        })
+                                                        .unwrap_or(String::from("# ???")),
+                                                    move |is_selected| {
+                                                        Msg::SetIsSelected(channel_id, is_selected)
+                                                    },
+                                                )
+                                            })
+                                            .collect::<Vec<_>>()
+                                    })
+                                    .unwrap_or_default(),
+                            )
+                            .collect(),
+                        ),
+                        Html::div(
+                            Attributes::new().class(Self::class("controller")),
+                            Events::new(),
+                            vec![
+                                Text::span(format!("{}チャンネルを選択中", self.selected.len())),
+                                Btn::primary(
+                                    Attributes::new(),
+                                    Events::new().on_click(self, |_| Msg::ExportAsHtml),
+                                    vec![Html::text("HTML形式で保存")],
+                                ),
+                                Btn::primary(
+                                    Attributes::new(),
+                                    Events::new().on_click(self, |_| Msg::ExportAsText),
+                                    vec![Html::text("テキスト形式で保存")],
+                                ),
+                            ],
+                        ),
+                    ],
+                )],
+            ),
+        ))
+    }
+}
+
+impl ModalChatExport {
+    fn render_toggle(
+        &self,
+        is_selected: bool,
+        text: String,
+        msg: impl FnOnce(bool) -> Msg + 'static,
+    ) -> Html {
+        if is_selected {
+            Btn::menu_as_primary(
+                Attributes::new(),
+                Events::new().on_click(self, move |_| msg(false)),
+                vec![Html::text(text)],
+            )
+        } else {
+            Btn::menu(
+                Attributes::new(),
+                Events::new().on_click(self, move |_| msg(true)),
+                vec![Html::text(text)],
+            )
+        }
+    }
+}
+
+impl Styled for ModalChatExport {
+    fn style() -> Style {
+        style! {
+            ".base" {
+                "display": "grid";
+                "height": "100%";
+                "grid-template-rows": "1fr max-content";
+                "overflow-y": "hidden";
+            }
+
+            ".list" {
+                "display": "grid";
+                "grid-auto-rows": "max-content";
+                "padding": ".5em 1em";
+                "overflow-y": "scroll";
+            }
+
+            ".controller" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content max-content";
+                "column-gap": ".65rem";
+                "align-items": "center";
+                "padding": ".5em 1em";
+            }
+        }
+    }
+}
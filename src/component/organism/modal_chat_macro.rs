@@ -0,0 +1,215 @@
+use super::atom::{
+    btn::{self, Btn},
+    dropdown::{self, Dropdown},
+    text::Text,
+};
+use super::molecule::modal::{self, Modal};
+use crate::arena::block::chat_message::{MacroParam, MacroParamKind};
+use isaribi::{
+    style,
+    styled::{Style, Styled},
+};
+use kagura::prelude::*;
+use nusa::prelude::*;
+use std::rc::Rc;
+
+pub struct Props {
+    pub params: Rc<Vec<MacroParam>>,
+    pub properties: Rc<Vec<String>>,
+}
+
+pub enum Msg {
+    Cancel,
+    Send,
+    Input(usize, String),
+}
+
+pub enum On {
+    Cancel,
+    Send(Vec<String>),
+}
+
+pub struct ModalChatMacro {
+    params: Rc<Vec<MacroParam>>,
+    properties: Rc<Vec<String>>,
+    input: Vec<String>,
+}
+
+impl Component for ModalChatMacro {
+    type Props = Props;
+    type Msg = Msg;
+    type Event = On;
+}
+
+impl HtmlComponent for ModalChatMacro {}
+
+impl Constructor for ModalChatMacro {
+    fn constructor(props: Props) -> Self {
+        let input = Self::defaults(&props.params);
+
+        Self {
+            params: props.params,
+            properties: props.properties,
+            input,
+        }
+    }
+}
+
+impl Update for ModalChatMacro {
+    fn on_load(mut self: Pin<&mut Self>, props: Props) -> Cmd<Self> {
+        if !Rc::ptr_eq(&self.params, &props.params) {
+            self.input = Self::defaults(&props.params);
+        }
+        self.params = props.params;
+        self.properties = props.properties;
+        Cmd::none()
+    }
+
+    fn update(mut self: Pin<&mut Self>, msg: Self::Msg) -> Cmd<Self> {
+        match msg {
+            Msg::Cancel => Cmd::submit(On::Cancel),
+            Msg::Input(idx, data) => {
+                if let Some(input) = self.input.get_mut(idx) {
+                    *input = data;
+                }
+                Cmd::none()
+            }
+            Msg::Send => Cmd::submit(On::Send(self.input.drain(..).collect())),
+        }
+    }
+}
+
+impl ModalChatMacro {
+    fn defaults(params: &Vec<MacroParam>) -> Vec<String> {
+        params.iter().map(|param| param.default().clone()).collect()
+    }
+}
+
+impl Render<Html> for ModalChatMacro {
+    type Children = ();
+    fn render(&self, _: Self::Children) -> Html {
+        Self::styled(Modal::new(
+            self,
+            None,
+            modal::Props {},
+            Sub::map(|sub| match sub {
+                modal::On::Close => Msg::Cancel,
+            }),
+            (
+                String::from("チャットの送信"),
+                String::from(""),
+                vec![Html::div(
+                    Attributes::new().class(Self::class("base")),
+                    Events::new(),
+                    vec![
+                        Html::div(
+                            Attributes::new().class(Self::class("content")),
+                            Events::new(),
+                            vec![Html::div(
+                                Attributes::new()
+                                    .class(Self::class("container"))
+                                    .class(Self::class("key-value")),
+                                Events::new(),
+                                self.params
+                                    .iter()
+                                    .zip(self.input.iter())
+                                    .enumerate()
+                                    .map(|(idx, (param, value))| {
+                                        Html::fragment(vec![
+                                            Text::span(param.name()),
+                                            self.render_input(idx, param.kind(), value),
+                                        ])
+                                    })
+                                    .collect(),
+                            )],
+                        ),
+                        Html::div(
+                            Attributes::new().class(Self::class("container")),
+                            Events::new(),
+                            vec![Btn::primary(
+                                Attributes::new(),
+                                Events::new().on_click(self, |_| Msg::Send),
+                                vec![Html::text("送信")],
+                            )],
+                        ),
+                    ],
+                )],
+            ),
+        ))
+    }
+}
+
+impl ModalChatMacro {
+    fn render_input(&self, idx: usize, kind: &MacroParamKind, value: &String) -> Html {
+        match kind {
+            MacroParamKind::Text => Html::input(
+                Attributes::new().value(value),
+                Events::new().on_input(self, move |v| Msg::Input(idx, v)),
+                vec![],
+            ),
+            MacroParamKind::Number => Html::input(
+                Attributes::new().type_("number").value(value),
+                Events::new().on_input(self, move |v| Msg::Input(idx, v)),
+                vec![],
+            ),
+            MacroParamKind::Select(list) => self.render_select(idx, list, value),
+            MacroParamKind::Property => self.render_select(idx, &self.properties, value),
+        }
+    }
+
+    fn render_select(&self, idx: usize, list: &Vec<String>, value: &String) -> Html {
+        Dropdown::new(
+            self,
+            None,
+            dropdown::Props {
+                direction: dropdown::Direction::Bottom,
+                toggle_type: dropdown::ToggleType::Click,
+                variant: btn::Variant::DarkLikeMenu,
+            },
+            Sub::none(),
+            (
+                vec![Html::text(value)],
+                list.iter()
+                    .map(|item| {
+                        Btn::menu(
+                            Attributes::new(),
+                            Events::new().on_click(self, {
+                                let item = item.clone();
+                                move |_| Msg::Input(idx, item)
+                            }),
+                            vec![Html::text(item)],
+                        )
+                    })
+                    .collect(),
+            ),
+        )
+    }
+}
+
+impl Styled for ModalChatMacro {
+    fn style() -> Style {
+        style! {
+            ".base" {
+                "display": "grid";
+                "grid-template-rows": "1fr max-content";
+                "justify-items": "center";
+                "height": "100%";
+            }
+            ".content" {
+                "overflow-y": "scroll";
+                "width": "100%";
+            }
+            ".container" {
+                "padding": ".5em 1em";
+            }
+            ".key-value" {
+                "display": "grid";
+                "grid-template-columns": "max-content minmax(10rem, max-content)";
+                "justify-content": "center";
+                "align-items": "center";
+                "row-gap": ".65rem";
+                "column-gap": ".35rem";
+            }
+        }
+    }
+}
@@ -0,0 +1,410 @@
+use super::atom::{
+    btn::{self, Btn},
+    dropdown::{self, Dropdown},
+    text::Text,
+};
+use super::molecule::modal::{self, Modal};
+use crate::arena::{block, BlockMut};
+use crate::libs::color::Pallet;
+use crate::libs::element::download;
+use isaribi::{
+    style,
+    styled::{Style, Styled},
+};
+use kagura::prelude::*;
+use nusa::prelude::*;
+
+mod stats;
+
+pub struct Props {
+    pub chat: BlockMut<block::Chat>,
+}
+
+pub enum Msg {
+    Sub(On),
+    SetSession(Option<String>),
+    SetRoller(Option<String>),
+    ExportAsCsv,
+}
+
+pub enum On {
+    Close,
+}
+
+pub struct ModalDiceStats {
+    chat: BlockMut<block::Chat>,
+    rolls: Vec<stats::Roll>,
+    session: Option<String>,
+    roller: Option<String>,
+}
+
+impl Component for ModalDiceStats {
+    type Props = Props;
+    type Msg = Msg;
+    type Event = On;
+}
+
+impl HtmlComponent for ModalDiceStats {}
+
+impl Constructor for ModalDiceStats {
+    fn constructor(props: Self::Props) -> Self {
+        let rolls = stats::collect(&props.chat);
+        Self {
+            chat: props.chat,
+            rolls,
+            session: None,
+            roller: None,
+        }
+    }
+}
+
+impl Update for ModalDiceStats {
+    fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
+        self.rolls = stats::collect(&props.chat);
+        self.chat = props.chat;
+        Cmd::none()
+    }
+
+    fn update(mut self: Pin<&mut Self>, msg: Self::Msg) -> Cmd<Self> {
+        match msg {
+            Msg::Sub(sub) => Cmd::submit(sub),
+            Msg::SetSession(session) => {
+                self.session = session;
+                Cmd::none()
+            }
+            Msg::SetRoller(roller) => {
+                self.roller = roller;
+                Cmd::none()
+            }
+            Msg::ExportAsCsv => {
+                download(
+                    "dice.csv",
+                    "text/csv;charset=utf-8",
+                    &stats::to_csv(self.filtered_rolls()),
+                );
+                Cmd::none()
+            }
+        }
+    }
+}
+
+impl ModalDiceStats {
+    fn filtered_rolls(&self) -> impl Iterator<Item = &stats::Roll> {
+        self.rolls.iter().filter(move |roll| {
+            self.session
+                .as_ref()
+                .map(|session| *session == roll.session())
+                .unwrap_or(true)
+                && self
+                    .roller
+                    .as_ref()
+                    .map(|roller| *roller == roll.roller)
+                    .unwrap_or(true)
+        })
+    }
+}
+
+impl Render<Html> for ModalDiceStats {
+    type Children = ();
+    fn render(&self, _: Self::Children) -> Html {
+        Self::styled(Modal::new(
+            self,
+            None,
+            modal::Props {},
+            Sub::map(|sub| match sub {
+                modal::On::Close => Msg::Sub(On::Close),
+            }),
+            (
+                String::from("ダイス統計"),
+                String::from(""),
+                vec![Html::div(
+                    Attributes::new().class(Self::class("base")),
+                    Events::new(),
+                    vec![
+                        Html::div(
+                            Attributes::new().class(Self::class("content")),
+                            Events::new(),
+                            vec![
+                                self.render_filter(),
+                                self.render_roller_stats(),
+                                self.render_die_stats(),
+                            ],
+                        ),
+                        Html::div(
+                            Attributes::new().class(Self::class("controller")),
+                            Events::new(),
+                            vec![
+                                Text::span(format!(
+                                    "{}回のロールを表示中",
+                                    self.filtered_rolls().count()
+                                )),
+                                Btn::primary(
+                                    Attributes::new(),
+                                    Events::new().on_click(self, |_| Msg::ExportAsCsv),
+                                    vec![Html::text("CSV形式で保存")],
+                                ),
+                            ],
+                        ),
+                    ],
+                )],
+            ),
+        ))
+    }
+}
+
+impl ModalDiceStats {
+    fn render_filter(&self) -> Html {
+        Html::div(
+            Attributes::new().class(Self::class("filter")),
+            Events::new(),
+            vec![
+                Text::span("セッション"),
+                self.render_select(&self.session, stats::sessions(&self.rolls), Msg::SetSession),
+                Text::span("送信者"),
+                self.render_select(&self.roller, stats::rollers(&self.rolls), Msg::SetRoller),
+            ],
+        )
+    }
+
+    fn render_select(
+        &self,
+        selected: &Option<String>,
+        items: Vec<String>,
+        msg: impl Fn(Option<String>) -> Msg + Clone + 'static,
+    ) -> Html {
+        Dropdown::new(
+            self,
+            None,
+            dropdown::Props {
+                direction: dropdown::Direction::Bottom,
+                toggle_type: dropdown::ToggleType::Click,
+                variant: btn::Variant::DarkLikeMenu,
+            },
+            Sub::none(),
+            (
+                vec![Html::text(
+                    selected.clone().unwrap_or_else(|| String::from("すべて")),
+                )],
+                vec![Btn::menu(
+                    Attributes::new(),
+                    Events::new().on_click(self, {
+                        let msg = msg.clone();
+                        move |_| msg(None)
+                    }),
+                    vec![Html::text("すべて")],
+                )]
+                .into_iter()
+                .chain(items.into_iter().map(|item| {
+                    Btn::menu(
+                        Attributes::new(),
+                        Events::new().on_click(self, {
+                            let msg = msg.clone();
+                            let item = item.clone();
+                            move |_| msg(Some(item))
+                        }),
+                        vec![Html::text(item)],
+                    )
+                }))
+                .collect(),
+            ),
+        )
+    }
+
+    fn render_roller_stats(&self) -> Html {
+        Html::div(
+            Attributes::new().class(Self::class("table")),
+            Events::new(),
+            vec![
+                "送信者",
+                "ロール",
+                "成功",
+                "失敗",
+                "クリティカル",
+                "ファンブル",
+            ]
+            .into_iter()
+            .map(|heading| {
+                Html::span(
+                    Attributes::new().class(Self::class("heading")),
+                    Events::new(),
+                    vec![Html::text(heading)],
+                )
+            })
+            .chain(
+                stats::roller_stats(self.filtered_rolls())
+                    .into_iter()
+                    .map(|stat| {
+                        vec![
+                            Text::span(stat.roller),
+                            Text::span(format!("{}", stat.count)),
+                            Text::span(format!("{}", stat.success)),
+                            Text::span(format!("{}", stat.failure)),
+                            Text::span(format!("{}", stat.critical)),
+                            Text::span(format!("{}", stat.fumble)),
+                        ]
+                    })
+                    .flatten(),
+            )
+            .collect(),
+        )
+    }
+
+    fn render_die_stats(&self) -> Html {
+        Html::div(
+            Attributes::new(),
+            Events::new(),
+            stats::die_stats(self.filtered_rolls())
+                .into_iter()
+                .map(|stat| self.render_die(stat))
+                .collect(),
+        )
+    }
+
+    fn render_die(&self, stat: stats::DieStats) -> Html {
+        let max = stat
+            .distribution
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        Html::div(
+            Attributes::new().class(Self::class("die")),
+            Events::new(),
+            vec![
+                Html::div(
+                    Attributes::new().class(Self::class("die-heading")),
+                    Events::new(),
+                    vec![
+                        Html::span(
+                            Attributes::new().class(Self::class("heading")),
+                            Events::new(),
+                            vec![Html::text(stat.name())],
+                        ),
+                        Text::span(format!(
+                            "{}個　平均 {:.2}（期待値 {:.2}）",
+                            stat.count,
+                            stat.average(),
+                            stat.expected()
+                        )),
+                    ],
+                ),
+                Html::div(
+                    Attributes::new().class(Self::class("distribution")),
+                    Events::new(),
+                    stat.distribution
+                        .iter()
+                        .map(|(label, count)| {
+                            Html::div(
+                                Attributes::new()
+                                    .class(Self::class("bar"))
+                                    .title(format!("{}：{}回", label, count)),
+                                Events::new(),
+                                vec![
+                                    Html::div(
+                                        Attributes::new().class(Self::class("bar-fill")).style(
+                                            "height",
+                                            format!("{}%", *count as f64 * 100.0 / max as f64),
+                                        ),
+                                        Events::new(),
+                                        vec![],
+                                    ),
+                                    Html::span(
+                                        Attributes::new().class(Self::class("bar-label")),
+                                        Events::new(),
+                                        vec![Html::text(label)],
+                                    ),
+                                ],
+                            )
+                        })
+                        .collect(),
+                ),
+            ],
+        )
+    }
+}
+
+impl Styled for ModalDiceStats {
+    fn style() -> Style {
+        style! {
+            ".base" {
+                "display": "grid";
+                "height": "100%";
+                "grid-template-rows": "1fr max-content";
+                "overflow-y": "hidden";
+            }
+
+            ".content" {
+                "display": "grid";
+                "grid-auto-rows": "max-content";
+                "row-gap": "1rem";
+                "padding": ".5em 1em";
+                "overflow-y": "scroll";
+            }
+
+            ".filter" {
+                "display": "grid";
+                "grid-template-columns": "max-content minmax(10rem, max-content) max-content minmax(10rem, max-content)";
+                "align-items": "center";
+                "column-gap": ".65rem";
+            }
+
+            ".table" {
+                "display": "grid";
+                "grid-template-columns": "1fr repeat(5, max-content)";
+                "column-gap": "1rem";
+                "row-gap": ".35rem";
+            }
+
+            ".heading" {
+                "font-weight": "bold";
+            }
+
+            ".die" {
+                "padding": ".5em 0";
+                "border-top": format!(".1rem solid {}", Pallet::gray(3));
+            }
+
+            ".die-heading" {
+                "display": "grid";
+                "grid-template-columns": "max-content 1fr";
+                "column-gap": "1rem";
+            }
+
+            ".distribution" {
+                "display": "grid";
+                "grid-auto-flow": "column";
+                "grid-auto-columns": "minmax(1.5rem, 1fr)";
+                "column-gap": ".15rem";
+                "height": "8rem";
+                "padding-top": ".35rem";
+            }
+
+            ".bar" {
+                "display": "grid";
+                "grid-template-rows": "1fr max-content";
+                "align-items": "end";
+                "justify-items": "center";
+            }
+
+            ".bar-fill" {
+                "width": "100%";
+                "background-color": Pallet::blue(5);
+            }
+
+            ".bar-label" {
+                "font-size": "0.8em";
+                "white-space": "nowrap";
+            }
+
+            ".controller" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content";
+                "column-gap": ".65rem";
+                "align-items": "center";
+                "padding": ".5em 1em";
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::arena::{block, BlockMut};
+use std::collections::BTreeMap;
+
+pub struct Roll {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub roller: String,
+    pub rands: Vec<(String, u32, u32)>,
+    pub success: bool,
+    pub failure: bool,
+    pub critical: bool,
+    pub fumble: bool,
+}
+
+pub struct DieStats {
+    pub kind: String,
+    pub sides: u32,
+    pub count: usize,
+    pub sum: u64,
+    pub distribution: Vec<(String, usize)>,
+}
+
+pub struct RollerStats {
+    pub roller: String,
+    pub count: usize,
+    pub success: usize,
+    pub failure: usize,
+    pub critical: usize,
+    pub fumble: usize,
+}
+
+impl Roll {
+    pub fn session(&self) -> String {
+        self.timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y/%m/%d")
+            .to_string()
+    }
+}
+
+impl DieStats {
+    pub fn name(&self) -> String {
+        match self.kind.as_str() {
+            "tens_d10" => String::from("D10（十の位）"),
+            "d9" => String::from("D9"),
+            _ => format!("D{}", self.sides),
+        }
+    }
+
+    pub fn average(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum as f64 / self.count as f64
+        }
+    }
+
+    pub fn expected(&self) -> f64 {
+        match self.kind.as_str() {
+            "tens_d10" => 45.0,
+            "d9" => 4.5,
+            _ => (self.sides as f64 + 1.0) / 2.0,
+        }
+    }
+}
+
+pub fn collect(chat: &BlockMut<block::Chat>) -> Vec<Roll> {
+    let mut rolls = vec![];
+
+    chat.map(|chat| {
+        for channel in chat.channels() {
+            channel.map(|channel| {
+                for message in channel.messages() {
+                    message.map(|message| {
+                        if let Some(dice_roll) = message.dice_roll() {
+                            rolls.push(Roll {
+                                timestamp: message.timestamp().clone(),
+                                roller: dice_roll.roller().name().clone(),
+                                rands: dice_roll
+                                    .rands()
+                                    .iter()
+                                    .map(|rand| (rand.kind().clone(), rand.sides(), rand.value()))
+                                    .collect(),
+                                success: dice_roll.success(),
+                                failure: dice_roll.failure(),
+                                critical: dice_roll.critical(),
+                                fumble: dice_roll.fumble(),
+                            });
+                        }
+                    });
+                }
+            });
+        }
+    });
+
+    rolls.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    rolls
+}
+
+pub fn sessions(rolls: &Vec<Roll>) -> Vec<String> {
+    let mut sessions: Vec<String> = vec![];
+    for roll in rolls {
+        let session = roll.session();
+        if !sessions.contains(&session) {
+            sessions.push(session);
+        }
+    }
+    sessions
+}
+
+pub fn rollers(rolls: &Vec<Roll>) -> Vec<String> {
+    let mut rollers: Vec<String> = vec![];
+    for roll in rolls {
+        if !rollers.contains(&roll.roller) {
+            rollers.push(roll.roller.clone());
+        }
+    }
+    rollers
+}
+
+pub fn die_stats<'a>(rolls: impl Iterator<Item = &'a Roll>) -> Vec<DieStats> {
+    let mut values: BTreeMap<(u32, String), Vec<u32>> = BTreeMap::new();
+
+    for roll in rolls {
+        for (kind, sides, value) in &roll.rands {
+            values
+                .entry((*sides, kind.clone()))
+                .or_insert_with(Vec::new)
+                .push(*value);
+        }
+    }
+
+    values
+        .into_iter()
+        .map(|((sides, kind), values)| DieStats {
+            count: values.len(),
+            sum: values.iter().map(|value| *value as u64).sum(),
+            distribution: distribution(&kind, sides, &values),
+            kind,
+            sides,
+        })
+        .collect()
+}
+
+pub fn roller_stats<'a>(rolls: impl Iterator<Item = &'a Roll>) -> Vec<RollerStats> {
+    let mut stats: Vec<RollerStats> = vec![];
+
+    for roll in rolls {
+        let idx = match stats.iter().position(|stat| stat.roller == roll.roller) {
+            Some(idx) => idx,
+            None => {
+                stats.push(RollerStats {
+                    roller: roll.roller.clone(),
+                    count: 0,
+                    success: 0,
+                    failure: 0,
+                    critical: 0,
+                    fumble: 0,
+                });
+                stats.len() - 1
+            }
+        };
+
+        let stat = &mut stats[idx];
+        stat.count += 1;
+        stat.success += roll.success as usize;
+        stat.failure += roll.failure as usize;
+        stat.critical += roll.critical as usize;
+        stat.fumble += roll.fumble as usize;
+    }
+
+    stats
+}
+
+fn distribution(kind: &str, sides: u32, values: &Vec<u32>) -> Vec<(String, usize)> {
+    let count = |from: u32, to: u32| {
+        values
+            .iter()
+            .filter(|value| from <= **value && **value <= to)
+            .count()
+    };
+
+    match kind {
+        "tens_d10" => (0..10)
+            .map(|n| (format!("{}", n * 10), count(n * 10, n * 10)))
+            .collect(),
+        "d9" => (0..10).map(|n| (format!("{}", n), count(n, n))).collect(),
+        _ if sides <= 20 => (1..=sides)
+            .map(|n| (format!("{}", n), count(n, n)))
+            .collect(),
+        _ => {
+            let step = (sides + 9) / 10;
+            (0..10)
+                .map(|n| (n * step + 1, ((n + 1) * step).min(sides)))
+                .filter(|(from, to)| from <= to)
+                .map(|(from, to)| (format!("{}-{}", from, to), count(from, to)))
+                .collect()
+        }
+    }
+}
+
+pub fn to_csv<'a>(rolls: impl Iterator<Item = &'a Roll>) -> String {
+    let mut lines = vec![String::from(
+        "timestamp,sender,kind,sides,value,success,failure,critical,fumble",
+    )];
+
+    for roll in rolls {
+        let timestamp = roll
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y/%m/%d %H:%M:%S");
+        for (kind, sides, value) in &roll.rands {
+            lines.push(format!(
+                "{},{},{},{},{},{},{},{},{}",
+                timestamp,
+                escape(&roll.roller),
+                escape(kind),
+                sides,
+                value,
+                roll.success,
+                roll.failure,
+                roll.critical,
+                roll.fumble
+            ));
+        }
+    }
+
+    lines.join("\r\n")
+}
+
+fn escape(text: &str) -> String {
+    if text.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        String::from(text)
+    }
+}
@@ -3,6 +3,8 @@ use super::atom::{
     heading::{self, Heading},
 };
 use super::molecule::modal::{self, Modal};
+use crate::dicebot;
+use crate::libs::bcdice::api;
 use crate::libs::bcdice::js::{DynamicLoader, GameSystemClass, GameSystemInfo};
 use crate::libs::js_object::Object;
 use isaribi::{
@@ -15,6 +17,7 @@ use std::rc::Rc;
 
 pub struct Props {
     pub bcdice_loader: Rc<DynamicLoader>,
+    pub bcdice_api_root: Option<Rc<String>>,
     pub selected_game_system: Option<String>,
 }
 
@@ -22,17 +25,25 @@ pub enum Msg {
     Sub(On),
     SetSelectedInitial(String),
     SetSelectedGameSystem(String),
+    SetApiSystems(Vec<api::GameSystem>),
     LoadSelectedGameSystem,
 }
 
 pub enum On {
     Close,
     SelectGameSystem { game_system_class: GameSystemClass },
+    SelectBuiltinSystem { id: String },
+    SelectApiGameSystem { api_game_system: api::GameSystemClass },
 }
 
+const BUILTIN: &str = "内蔵";
+const API: &str = "API";
+
 pub struct ModalDicebot {
     bcdice_loader: Rc<DynamicLoader>,
+    bcdice_api_root: Option<Rc<String>>,
     system_infos: Vec<(String, Vec<GameSystemInfo>)>,
+    api_systems: Vec<api::GameSystem>,
     selected_initial: String,
     selected_game_system: String,
     selected_game_system_name: String,
@@ -56,10 +67,12 @@ impl Constructor for ModalDicebot {
                 .unwrap_or_else(|| String::new())
         });
         let selected_game_system_name =
-            Self::get_selected_game_system_name(&system_infos, &selected_game_system);
+            Self::get_selected_game_system_name(&system_infos, &vec![], &selected_game_system);
         Self {
             bcdice_loader: props.bcdice_loader,
+            bcdice_api_root: props.bcdice_api_root,
             system_infos,
+            api_systems: vec![],
             selected_initial: String::new(),
             selected_game_system,
             selected_game_system_name,
@@ -68,8 +81,14 @@ impl Constructor for ModalDicebot {
 }
 
 impl Update for ModalDicebot {
+    fn on_assemble(self: Pin<&mut Self>) -> Cmd<Self> {
+        let root = unwrap!(self.bcdice_api_root.as_ref().map(Rc::clone); Cmd::none());
+        Cmd::task(async move { Cmd::chain(Msg::SetApiSystems(api::game_system(&root).await)) })
+    }
+
     fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
         self.bcdice_loader = props.bcdice_loader;
+        self.bcdice_api_root = props.bcdice_api_root;
         self.system_infos = Self::get_system_infos(self.bcdice_loader.available_game_systems());
         Cmd::none()
     }
@@ -85,12 +104,49 @@ impl Update for ModalDicebot {
                 self.selected_game_system = game_system;
                 self.selected_game_system_name = Self::get_selected_game_system_name(
                     &self.system_infos,
+                    &self.api_systems,
+                    &self.selected_game_system,
+                );
+                Cmd::none()
+            }
+            Msg::SetApiSystems(mut api_systems) => {
+                api_systems.retain(|api_system| {
+                    !self.system_infos.iter().any(|(_, system_infos)| {
+                        system_infos
+                            .iter()
+                            .any(|system_info| *system_info.id() == api_system.id)
+                    })
+                });
+                api_systems.sort_by(|a, b| a.sort_key.cmp(&b.sort_key));
+                self.api_systems = api_systems;
+                self.selected_game_system_name = Self::get_selected_game_system_name(
+                    &self.system_infos,
+                    &self.api_systems,
                     &self.selected_game_system,
                 );
                 Cmd::none()
             }
             Msg::LoadSelectedGameSystem => {
                 let game_system = self.selected_game_system.clone();
+                if dicebot::is_system(&game_system) {
+                    return Cmd::list(vec![
+                        Cmd::submit(On::SelectBuiltinSystem { id: game_system }),
+                        Cmd::submit(On::Close),
+                    ]);
+                }
+                if let Some(root) = self.api_root_of(&game_system) {
+                    return Cmd::task(async move {
+                        api::GameSystemClass::load(&root, &game_system)
+                            .await
+                            .map(|api_game_system| {
+                                Cmd::list(vec![
+                                    Cmd::submit(On::SelectApiGameSystem { api_game_system }),
+                                    Cmd::submit(On::Close),
+                                ])
+                            })
+                            .unwrap_or_else(|| Cmd::submit(On::Close))
+                    });
+                }
                 let bcdice_loader = Rc::clone(&self.bcdice_loader);
                 Cmd::task(async move {
                     bcdice_loader
@@ -140,10 +196,29 @@ impl ModalDicebot {
         categorised_system_infos
     }
 
+    fn api_root_of(&self, game_system: &String) -> Option<Rc<String>> {
+        if self
+            .api_systems
+            .iter()
+            .any(|api_system| api_system.id == *game_system)
+        {
+            self.bcdice_api_root.as_ref().map(Rc::clone)
+        } else {
+            None
+        }
+    }
+
     fn get_selected_game_system_name(
         system_infos: &Vec<(String, Vec<GameSystemInfo>)>,
+        api_systems: &Vec<api::GameSystem>,
         selected: &String,
     ) -> String {
+        for (id, name) in dicebot::systems() {
+            if id == *selected {
+                return name;
+            }
+        }
+
         for (_, system_infos) in system_infos {
             for system_info in system_infos {
                 if *system_info.id() == *selected {
@@ -152,6 +227,12 @@ impl ModalDicebot {
             }
         }
 
+        for api_system in api_systems {
+            if api_system.id == *selected {
+                return api_system.name.clone();
+            }
+        }
+
         String::from("")
     }
 }
@@ -195,14 +276,24 @@ impl Render<Html> for ModalDicebot {
                                 Html::div(
                                     Attributes::new().class(Self::class("list")),
                                     Events::new(),
-                                    self.system_infos
-                                        .iter()
-                                        .map(|(initial, ..)| {
+                                    Some(BUILTIN)
+                                        .into_iter()
+                                        .chain(
+                                            self.system_infos
+                                                .iter()
+                                                .map(|(initial, ..)| initial.as_str()),
+                                        )
+                                        .chain(if self.api_systems.is_empty() {
+                                            None
+                                        } else {
+                                            Some(API)
+                                        })
+                                        .map(|initial| {
                                             if *initial != *self.selected_initial {
                                                 Btn::menu(
                                                     Attributes::new(),
                                                     Events::new().on_click(self, {
-                                                        let initial = initial.clone();
+                                                        let initial = String::from(initial);
                                                         move |_| Msg::SetSelectedInitial(initial)
                                                     }),
                                                     vec![Html::text(initial)],
@@ -220,45 +311,38 @@ impl Render<Html> for ModalDicebot {
                                 Html::div(
                                     Attributes::new().class(Self::class("list")),
                                     Events::new(),
-                                    self.system_infos
-                                        .iter()
-                                        .filter_map(|(initial, game_system_infos)| {
-                                            if *initial == self.selected_initial {
-                                                Some(
-                                                    game_system_infos
-                                                        .iter()
-                                                        .map(|game_system_info| {
-                                                            if *game_system_info.id()
-                                                                != self.selected_game_system
-                                                            {
-                                                                Btn::menu(
-                                                                    Attributes::new(),
-                                                                    Events::new().on_click(self, {
-                                                                        let game_system = game_system_info.id().clone();
-                                                                        move |_| Msg::SetSelectedGameSystem(game_system)
-                                                                    }),
-                                                                    vec![Html::text(
-                                                                        game_system_info.name(),
-                                                                    )],
-                                                                )
-                                                            } else {
-                                                                Btn::menu_as_primary(
-                                                                    Attributes::new(),
-                                                                    Events::new(),
-                                                                    vec![Html::text(
-                                                                        game_system_info.name(),
-                                                                    )],
-                                                                )
-                                                            }
-                                                        })
-                                                        .collect::<Vec<_>>(),
+                                    if self.selected_initial == BUILTIN {
+                                        dicebot::systems()
+                                            .into_iter()
+                                            .map(|(id, name)| self.render_game_system(id, name))
+                                            .collect()
+                                    } else if self.selected_initial == API {
+                                        self.api_systems
+                                            .iter()
+                                            .map(|api_system| {
+                                                self.render_game_system(
+                                                    api_system.id.clone(),
+                                                    api_system.name.clone(),
                                                 )
-                                            } else {
-                                                None
-                                            }
-                                        })
-                                        .flatten()
-                                        .collect(),
+                                            })
+                                            .collect()
+                                    } else {
+                                        self.system_infos
+                                            .iter()
+                                            .filter(|(initial, ..)| {
+                                                *initial == self.selected_initial
+                                            })
+                                            .map(|(_, game_system_infos)| {
+                                                game_system_infos.iter().map(|game_system_info| {
+                                                    self.render_game_system(
+                                                        game_system_info.id().clone(),
+                                                        game_system_info.name().clone(),
+                                                    )
+                                                })
+                                            })
+                                            .flatten()
+                                            .collect()
+                                    },
                                 ),
                             ],
                         ),
@@ -278,6 +362,20 @@ impl Render<Html> for ModalDicebot {
     }
 }
 
+impl ModalDicebot {
+    fn render_game_system(&self, id: String, name: String) -> Html {
+        if id != self.selected_game_system {
+            Btn::menu(
+                Attributes::new(),
+                Events::new().on_click(self, move |_| Msg::SetSelectedGameSystem(id)),
+                vec![Html::text(name)],
+            )
+        } else {
+            Btn::menu_as_primary(Attributes::new(), Events::new(), vec![Html::text(name)])
+        }
+    }
+}
+
 impl Styled for ModalDicebot {
     fn style() -> Style {
         style! {
@@ -2,8 +2,12 @@ use super::organism::room_modeless_boxblock::{self, RoomModelessBoxblock};
 use super::organism::room_modeless_character::{self, RoomModelessCharacter};
 use super::organism::room_modeless_chat::{self, RoomModelessChat};
 use super::organism::room_modeless_craftboard::{self, RoomModelessCraftboard};
+use super::organism::room_modeless_dice_table::{self, RoomModelessDiceTable};
+use super::organism::room_modeless_initiative::{self, RoomModelessInitiative};
 use super::organism::room_modeless_textboard::{self, RoomModelessTextboard};
 use crate::arena::{block, ArenaMut, BlockMut};
+use crate::dicebot::exchange::DiceExchange;
+use crate::libs::bcdice::api;
 use crate::libs::bcdice::js::GameSystemClass;
 use crate::libs::random_id::U128Id;
 use isaribi::{
@@ -31,10 +35,20 @@ pub enum ContentData {
         user: ChatUser,
         data: BlockMut<block::Chat>,
         game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
+        api_game_system: Rc<RefCell<Option<Rc<api::GameSystemClass>>>>,
+        dice_exchange: Rc<DiceExchange>,
     },
     Boxblock(block::boxblock::Block),
     Character(BlockMut<block::Character>),
     Craftboard(block::craftboard::Block),
+    DiceTable(BlockMut<block::DiceTable>),
+    Initiative {
+        data: BlockMut<block::Initiative>,
+        chat: BlockMut<block::Chat>,
+        game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
+        api_game_system: Rc<RefCell<Option<Rc<api::GameSystemClass>>>>,
+        dice_exchange: Rc<DiceExchange>,
+    },
     Textboard(block::textboard::Block),
 }
 
@@ -93,15 +107,20 @@ impl Render<Html> for RoomModeless {
                 user,
                 data,
                 game_system_class,
+                api_game_system,
+                dice_exchange,
             } => RoomModelessChat::empty(
                 self,
                 None,
                 room_modeless_chat::Props {
                     arena: ArenaMut::clone(&self.content.arena),
+                    world: BlockMut::clone(&self.content.world),
                     data: BlockMut::clone(&data),
                     user: ChatUser::clone(&user),
                     client_id: Rc::clone(&self.content.client_id),
                     game_system_class: Rc::clone(&game_system_class),
+                    api_game_system: Rc::clone(&api_game_system),
+                    dice_exchange: Rc::clone(&dice_exchange),
                 },
                 Sub::map(|sub| match sub {
                     room_modeless_chat::On::UpdateBlocks { insert, update } => {
@@ -130,6 +149,7 @@ impl Render<Html> for RoomModeless {
                     arena: ArenaMut::clone(&self.content.arena),
                     world: BlockMut::clone(&self.content.world),
                     data: BlockMut::clone(&character),
+                    client_id: Rc::clone(&self.content.client_id),
                 },
                 Sub::map(|sub| match sub {
                     room_modeless_character::On::UpdateBlocks { insert, update } => {
@@ -151,6 +171,45 @@ impl Render<Html> for RoomModeless {
                     }
                 }),
             ),
+            ContentData::DiceTable(dice_table) => RoomModelessDiceTable::empty(
+                self,
+                None,
+                room_modeless_dice_table::Props {
+                    arena: ArenaMut::clone(&self.content.arena),
+                    world: BlockMut::clone(&self.content.world),
+                    data: BlockMut::clone(&dice_table),
+                },
+                Sub::map(|sub| match sub {
+                    room_modeless_dice_table::On::UpdateBlocks { insert, update } => {
+                        Msg::Sub(On::UpdateBlocks { insert, update })
+                    }
+                }),
+            ),
+            ContentData::Initiative {
+                data,
+                chat,
+                game_system_class,
+                api_game_system,
+                dice_exchange,
+            } => RoomModelessInitiative::empty(
+                self,
+                None,
+                room_modeless_initiative::Props {
+                    arena: ArenaMut::clone(&self.content.arena),
+                    world: BlockMut::clone(&self.content.world),
+                    chat: BlockMut::clone(&chat),
+                    client_id: Rc::clone(&self.content.client_id),
+                    data: BlockMut::clone(&data),
+                    game_system_class: Rc::clone(&game_system_class),
+                    api_game_system: Rc::clone(&api_game_system),
+                    dice_exchange: Rc::clone(&dice_exchange),
+                },
+                Sub::map(|sub| match sub {
+                    room_modeless_initiative::On::UpdateBlocks { insert, update } => {
+                        Msg::Sub(On::UpdateBlocks { insert, update })
+                    }
+                }),
+            ),
             ContentData::Textboard(textboard) => RoomModelessTextboard::empty(
                 self,
                 None,
@@ -293,6 +352,24 @@ impl Render<Html> for TabName {
                     )
                 })
                 .unwrap_or(Html::none()),
+            ContentData::DiceTable(dice_table) => dice_table
+                .map(|dt| {
+                    Html::span(
+                        Attributes::new(),
+                        Events::new(),
+                        vec![
+                            fa::fas_i("fa-table-list"),
+                            Html::text(" "),
+                            Html::text(dt.name()),
+                        ],
+                    )
+                })
+                .unwrap_or(Html::none()),
+            ContentData::Initiative { .. } => Html::span(
+                Attributes::new(),
+                Events::new(),
+                vec![fa::fas_i("fa-list-ol"), Html::text(" イニシアチブ")],
+            ),
             ContentData::Textboard(textboard) => textboard
                 .map(|tb| {
                     Html::span(
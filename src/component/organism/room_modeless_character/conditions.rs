@@ -0,0 +1,451 @@
+use super::super::atom::{
+    btn::{self, Btn},
+    dropdown::{self, Dropdown},
+    fa,
+    text::Text,
+};
+use super::super::organism::popup_color_pallet::{self, PopupColorPallet};
+use crate::arena::{block, ArenaMut, BlockMut};
+use crate::libs::random_id::U128Id;
+use block::condition::DurationUnit;
+use isaribi::{
+    style,
+    styled::{Style, Styled},
+};
+use kagura::prelude::*;
+use nusa::prelude::*;
+use std::collections::HashSet;
+
+pub struct Props {
+    pub arena: ArenaMut,
+    pub world: BlockMut<block::World>,
+    pub character: BlockMut<block::Character>,
+}
+
+pub enum Msg {
+    Sub(On),
+    SetEditing(Option<U128Id>),
+    AddPreset(usize),
+    AddCustom,
+    Remove(U128Id),
+    SetName(U128Id, String),
+    SetIcon(U128Id, String),
+    SetColor(U128Id, crate::libs::color::Pallet),
+    SetDuration(U128Id, String),
+    SetDurationUnit(U128Id, DurationUnit),
+}
+
+pub enum On {
+    UpdateBlocks {
+        insert: HashSet<U128Id>,
+        update: HashSet<U128Id>,
+    },
+}
+
+pub struct Conditions {
+    arena: ArenaMut,
+    world: BlockMut<block::World>,
+    character: BlockMut<block::Character>,
+    editing: Option<U128Id>,
+}
+
+impl Component for Conditions {
+    type Props = Props;
+    type Msg = Msg;
+    type Event = On;
+}
+
+impl HtmlComponent for Conditions {}
+
+impl Constructor for Conditions {
+    fn constructor(props: Props) -> Self {
+        Self {
+            arena: props.arena,
+            world: props.world,
+            character: props.character,
+            editing: None,
+        }
+    }
+}
+
+impl Update for Conditions {
+    fn on_load(mut self: Pin<&mut Self>, props: Props) -> Cmd<Self> {
+        self.arena = props.arena;
+        self.world = props.world;
+        self.character = props.character;
+        Cmd::none()
+    }
+
+    fn update(mut self: Pin<&mut Self>, msg: Msg) -> Cmd<Self> {
+        match msg {
+            Msg::Sub(sub) => Cmd::submit(sub),
+            Msg::SetEditing(editing) => {
+                self.editing = editing;
+                Cmd::none()
+            }
+            Msg::AddPreset(idx) => {
+                let presets = block::condition::presets();
+                let preset = unwrap!(presets.get(idx); Cmd::none());
+                let condition =
+                    block::Condition::from_preset(BlockMut::clone(&self.character), preset);
+                self.push_condition(condition)
+            }
+            Msg::AddCustom => {
+                let condition = block::Condition::new(BlockMut::clone(&self.character));
+                let cmd = self.push_condition(condition);
+                self.editing = self
+                    .world
+                    .map(|world| world.conditions().last().map(|condition| condition.id()))
+                    .unwrap_or(None);
+                cmd
+            }
+            Msg::Remove(condition_id) => {
+                self.world.update(|world| {
+                    world.remove_condition(&condition_id);
+                });
+                if self.editing == Some(condition_id) {
+                    self.editing = None;
+                }
+                Cmd::submit(On::UpdateBlocks {
+                    insert: set! {},
+                    update: set! { self.world.id() },
+                })
+            }
+            Msg::SetName(condition_id, name) => self.update_condition(condition_id, |condition| {
+                condition.set_name(name);
+            }),
+            Msg::SetIcon(condition_id, icon) => self.update_condition(condition_id, |condition| {
+                condition.set_icon(icon);
+            }),
+            Msg::SetColor(condition_id, color) => {
+                self.update_condition(condition_id, |condition| {
+                    condition.set_color(color);
+                })
+            }
+            Msg::SetDuration(condition_id, duration) => {
+                let duration = if duration.is_empty() {
+                    None
+                } else {
+                    Some(unwrap!(duration.parse().ok(); Cmd::none()))
+                };
+                self.update_condition(condition_id, |condition| {
+                    condition.set_duration(duration);
+                })
+            }
+            Msg::SetDurationUnit(condition_id, duration_unit) => {
+                self.update_condition(condition_id, |condition| {
+                    condition.set_duration_unit(duration_unit);
+                })
+            }
+        }
+    }
+}
+
+impl Conditions {
+    fn push_condition(&mut self, condition: block::Condition) -> Cmd<Self> {
+        let condition = self.arena.insert(condition);
+        let condition_id = condition.id();
+        self.world.update(|world| {
+            world.push_condition(condition);
+        });
+        Cmd::submit(On::UpdateBlocks {
+            insert: set! { condition_id },
+            update: set! { self.world.id() },
+        })
+    }
+
+    fn update_condition(
+        &mut self,
+        condition_id: U128Id,
+        f: impl FnOnce(&mut block::Condition),
+    ) -> Cmd<Self> {
+        let mut condition =
+            unwrap!(self.arena.get_mut::<block::Condition>(&condition_id); Cmd::none());
+        condition.update(f);
+        Cmd::submit(On::UpdateBlocks {
+            insert: set! {},
+            update: set! { condition_id },
+        })
+    }
+}
+
+impl Render<Html> for Conditions {
+    type Children = ();
+    fn render(&self, _: Self::Children) -> Html {
+        let conditions = self
+            .world
+            .map(|world| world.conditions_of(&self.character.id()))
+            .unwrap_or_default();
+
+        let editing = self.editing.as_ref().and_then(|editing| {
+            conditions
+                .iter()
+                .find(|condition| condition.id() == *editing)
+                .map(BlockMut::clone)
+        });
+
+        Self::styled(Html::div(
+            Attributes::new().class(Self::class("base")),
+            Events::new(),
+            vec![
+                Html::div(
+                    Attributes::new().class(Self::class("badges")),
+                    Events::new(),
+                    conditions
+                        .iter()
+                        .filter_map(|condition| self.render_badge(condition))
+                        .chain(vec![self.render_add()])
+                        .collect(),
+                ),
+                editing
+                    .and_then(|condition| self.render_editor(&condition))
+                    .unwrap_or(Html::none()),
+            ],
+        ))
+    }
+}
+
+impl Conditions {
+    fn render_badge(&self, condition: &BlockMut<block::Condition>) -> Option<Html> {
+        let condition_id = condition.id();
+        let is_editing = self.editing.as_ref() == Some(&condition_id);
+        condition.map(|condition| {
+            Html::span(
+                Attributes::new()
+                    .class(Self::class("badge"))
+                    .style("background-color", condition.color().to_string())
+                    .style("color", Self::text_color(condition.color())),
+                Events::new().on_click(self, move |_| {
+                    Msg::SetEditing(if is_editing { None } else { Some(condition_id) })
+                }),
+                vec![
+                    fa::fas_i(condition.icon()),
+                    Html::text(format!(" {}", condition.label())),
+                ],
+            )
+        })
+    }
+
+    fn render_add(&self) -> Html {
+        Dropdown::new(
+            self,
+            None,
+            dropdown::Props {
+                direction: dropdown::Direction::Bottom,
+                toggle_type: dropdown::ToggleType::Click,
+                variant: btn::Variant::TransparentDark,
+            },
+            Sub::none(),
+            (
+                vec![fa::fas_i("fa-plus"), Html::text(" 状態異常")],
+                block::condition::presets()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, preset)| {
+                        Btn::menu(
+                            Attributes::new(),
+                            Events::new().on_click(self, move |_| Msg::AddPreset(idx)),
+                            vec![
+                                fa::fas_i(preset.icon),
+                                Html::text(format!(" {}", preset.name)),
+                            ],
+                        )
+                    })
+                    .chain(vec![Btn::menu(
+                        Attributes::new(),
+                        Events::new().on_click(self, |_| Msg::AddCustom),
+                        vec![fa::fas_i("fa-pen"), Html::text(" カスタム")],
+                    )])
+                    .collect(),
+            ),
+        )
+    }
+
+    fn render_editor(&self, condition: &BlockMut<block::Condition>) -> Option<Html> {
+        let condition_id = condition.id();
+        condition.map(|condition| {
+            Html::div(
+                Attributes::new()
+                    .class(Self::class("editor"))
+                    .class("pure-form"),
+                Events::new(),
+                vec![
+                    Text::span("名前"),
+                    Html::input(
+                        Attributes::new().value(condition.name()),
+                        Events::new().on_input(self, {
+                            let condition_id = U128Id::clone(&condition_id);
+                            move |name| Msg::SetName(condition_id, name)
+                        }),
+                        vec![],
+                    ),
+                    Text::span("アイコン"),
+                    Html::div(
+                        Attributes::new().class(Self::class("icons")),
+                        Events::new(),
+                        block::condition::ICONS
+                            .iter()
+                            .map(|icon| {
+                                let attrs = if *condition.icon() == *icon {
+                                    Attributes::new().class(Self::class("icon--selected"))
+                                } else {
+                                    Attributes::new()
+                                };
+                                Btn::light(
+                                    attrs,
+                                    Events::new().on_click(self, {
+                                        let condition_id = U128Id::clone(&condition_id);
+                                        move |_| Msg::SetIcon(condition_id, String::from(*icon))
+                                    }),
+                                    vec![fa::fas_i(*icon)],
+                                )
+                            })
+                            .collect(),
+                    ),
+                    Text::span("色"),
+                    PopupColorPallet::empty(
+                        self,
+                        None,
+                        popup_color_pallet::Props {
+                            direction: popup_color_pallet::Direction::Bottom,
+                            default_selected: condition.color().clone(),
+                        },
+                        Sub::map({
+                            let condition_id = U128Id::clone(&condition_id);
+                            move |sub| match sub {
+                                popup_color_pallet::On::SelectColor(color) => {
+                                    Msg::SetColor(U128Id::clone(&condition_id), color)
+                                }
+                            }
+                        }),
+                    ),
+                    Text::span("継続"),
+                    Html::div(
+                        Attributes::new().class(Self::class("duration")),
+                        Events::new(),
+                        vec![
+                            Html::input(
+                                Attributes::new()
+                                    .type_("number")
+                                    .string("min", "0")
+                                    .string("placeholder", "無期限")
+                                    .value(
+                                        condition
+                                            .duration()
+                                            .map(|duration| duration.to_string())
+                                            .unwrap_or_default(),
+                                    ),
+                                Events::new().on_input(self, {
+                                    let condition_id = U128Id::clone(&condition_id);
+                                    move |duration| Msg::SetDuration(condition_id, duration)
+                                }),
+                                vec![],
+                            ),
+                            Btn::group(
+                                Attributes::new(),
+                                Events::new(),
+                                vec![
+                                    self.render_duration_unit(
+                                        U128Id::clone(&condition_id),
+                                        condition.duration_unit(),
+                                        DurationUnit::Round,
+                                        "ラウンド",
+                                    ),
+                                    self.render_duration_unit(
+                                        U128Id::clone(&condition_id),
+                                        condition.duration_unit(),
+                                        DurationUnit::Turn,
+                                        "手番",
+                                    ),
+                                ],
+                            ),
+                            Btn::danger(
+                                Attributes::new(),
+                                Events::new().on_click(self, move |_| Msg::Remove(condition_id)),
+                                vec![fa::fas_i("fa-trash")],
+                            ),
+                        ],
+                    ),
+                ],
+            )
+        })
+    }
+
+    fn render_duration_unit(
+        &self,
+        condition_id: U128Id,
+        selected: DurationUnit,
+        duration_unit: DurationUnit,
+        text: &str,
+    ) -> Html {
+        let events = Events::new().on_click(self, move |_| {
+            Msg::SetDurationUnit(condition_id, duration_unit)
+        });
+        if selected == duration_unit {
+            Btn::primary(Attributes::new(), events, vec![Html::text(text)])
+        } else {
+            Btn::secondary(Attributes::new(), events, vec![Html::text(text)])
+        }
+    }
+
+    fn text_color(color: &crate::libs::color::Pallet) -> &'static str {
+        if color.to_color().v() > 0.9 {
+            "#000"
+        } else {
+            "#fff"
+        }
+    }
+}
+
+impl Styled for Conditions {
+    fn style() -> Style {
+        style! {
+            ".base" {
+                "display": "grid";
+                "grid-template-columns": "1fr";
+                "row-gap": ".35rem";
+                "padding-left": ".65rem";
+                "padding-right": ".65rem";
+            }
+
+            ".badges" {
+                "display": "flex";
+                "flex-wrap": "wrap";
+                "align-items": "center";
+                "gap": ".35rem";
+            }
+
+            ".badge" {
+                "padding": ".15rem .5rem";
+                "border-radius": "1rem";
+                "cursor": "pointer";
+                "white-space": "nowrap";
+            }
+
+            ".editor" {
+                "display": "grid";
+                "grid-template-columns": "max-content 1fr";
+                "align-items": "center";
+                "column-gap": ".35rem";
+                "row-gap": ".35rem";
+            }
+
+            ".icons" {
+                "display": "flex";
+                "flex-wrap": "wrap";
+                "gap": ".15rem";
+            }
+
+            ".icon--selected" {
+                "outline": format!("2px solid {}", crate::libs::color::Pallet::blue(5));
+            }
+
+            ".duration" {
+                "display": "grid";
+                "grid-template-columns": "6em max-content 1fr max-content";
+                "column-gap": ".35rem";
+                "align-items": "center";
+            }
+        }
+    }
+}
@@ -12,14 +12,18 @@ use isaribi::{
 use kagura::prelude::*;
 use nusa::prelude::*;
 use std::collections::HashSet;
+use std::rc::Rc;
 
+mod conditions;
 mod tab_0;
+use conditions::Conditions;
 use tab_0::Tab0;
 
 pub struct Props {
     pub arena: ArenaMut,
     pub world: BlockMut<block::World>,
     pub data: BlockMut<block::Character>,
+    pub client_id: Rc<String>,
 }
 
 pub enum Msg {
@@ -34,6 +38,7 @@ pub enum Msg {
     SetSize(f64),
     SetZOffset(f64),
     SetTexSize(f64),
+    SetSpeechDuration(f64),
     SetSelectedTextureIdx(usize),
     SetDescription(String),
     SetTextureImage(usize, Option<BlockRef<resource::ImageData>>),
@@ -54,6 +59,7 @@ pub struct RoomModelessCharacter {
     arena: ArenaMut,
     world: BlockMut<block::World>,
     character: BlockMut<block::Character>,
+    client_id: Rc<String>,
     selected_tab_idx: usize,
     showing_modal: ShowingModal,
 }
@@ -77,6 +83,7 @@ impl Constructor for RoomModelessCharacter {
             arena: props.arena,
             world: props.world,
             character: props.data,
+            client_id: props.client_id,
             selected_tab_idx: 0,
             showing_modal: ShowingModal::None,
         }
@@ -87,6 +94,7 @@ impl Update for RoomModelessCharacter {
     fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
         self.arena = props.arena;
         self.world = props.world;
+        self.client_id = props.client_id;
         if self.character.id() != props.data.id() {
             self.character = BlockMut::clone(&props.data);
         }
@@ -176,6 +184,16 @@ impl Update for RoomModelessCharacter {
                     update: set! { self.character.id() },
                 })
             }
+            Msg::SetSpeechDuration(speech_duration) => {
+                self.character.update(|character| {
+                    character.set_speech_duration(speech_duration);
+                });
+
+                Cmd::submit(On::UpdateBlocks {
+                    insert: set! {},
+                    update: set! { self.character.id() },
+                })
+            }
             Msg::SetSelectedTextureIdx(tex_idx) => {
                 self.character.update(|character| {
                     character.set_selected_texture_idx(tex_idx);
@@ -307,113 +325,142 @@ impl RoomModelessCharacter {
         Html::div(
             Attributes::new().class(Self::class("base")),
             Events::new(),
-            vec![TabMenu::new(
-                self,
-                None,
-                tab_menu::Props {
-                    selected: self.selected_tab_idx,
-                    controlled: true,
-                },
-                Sub::map({
-                    let prop_num = self
-                        .character
-                        .map(|character| character.properties().len())
-                        .unwrap_or(0);
-                    move |sub| match sub {
-                        tab_menu::On::ChangeSelectedTab(tab_idx) => {
-                            if tab_idx < prop_num + 1 {
-                                Msg::SetSelectedTabIdx(tab_idx)
-                            } else {
-                                Msg::NoOp
+            vec![
+                Conditions::empty(
+                    self,
+                    None,
+                    conditions::Props {
+                        arena: ArenaMut::clone(&self.arena),
+                        world: BlockMut::clone(&self.world),
+                        character: BlockMut::clone(&self.character),
+                    },
+                    Sub::map(|sub| match sub {
+                        conditions::On::UpdateBlocks { insert, update } => {
+                            Msg::Sub(On::UpdateBlocks { insert, update })
+                        }
+                    }),
+                ),
+                TabMenu::new(
+                    self,
+                    None,
+                    tab_menu::Props {
+                        selected: self.selected_tab_idx,
+                        controlled: true,
+                    },
+                    Sub::map({
+                        let prop_num = self
+                            .character
+                            .map(|character| character.properties().len())
+                            .unwrap_or(0);
+                        move |sub| match sub {
+                            tab_menu::On::ChangeSelectedTab(tab_idx) => {
+                                if tab_idx < prop_num + 1 {
+                                    Msg::SetSelectedTabIdx(tab_idx)
+                                } else {
+                                    Msg::NoOp
+                                }
                             }
                         }
-                    }
-                }),
-                (
-                    Attributes::new(),
-                    Events::new(),
-                    vec![
-                        vec![(
-                            Html::text(String::from("Common")),
-                            Tab0::empty(
-                                self,
-                                None,
-                                tab_0::Props {
-                                    character: BlockMut::clone(&self.character),
-                                },
-                                Sub::map(|sub| match sub {
-                                    tab_0::On::OpenModal(modal) => Msg::SetShowingModal(modal),
-                                    tab_0::On::PushTexture => Msg::PushTexture,
-                                    tab_0::On::SetColor(pallet) => Msg::SetColor(pallet),
-                                    tab_0::On::SetDescription(description) => {
-                                        Msg::SetDescription(description)
-                                    }
-                                    tab_0::On::SetDisplayName0(dn0) => Msg::SetDisplayName0(dn0),
-                                    tab_0::On::SetDisplayName1(dn1) => Msg::SetDisplayName1(dn1),
-                                    tab_0::On::SetName(name) => Msg::SetName(name),
-                                    tab_0::On::SetSelectedTextureIdx(tex_idx) => {
-                                        Msg::SetSelectedTextureIdx(tex_idx)
-                                    }
-                                    tab_0::On::SetSize(size) => Msg::SetSize(size),
-                                    tab_0::On::SetZOffset(z_offset) => Msg::SetZOffset(z_offset),
-                                    tab_0::On::SetTexSize(tex_size) => Msg::SetTexSize(tex_size),
-                                    tab_0::On::SetTextureName(tex_idx, tex_name) => {
-                                        Msg::SetTextureName(tex_idx, tex_name)
-                                    }
-                                }),
-                            ),
-                        )],
-                        self.character
-                            .map(|character| {
-                                character
-                                    .properties()
-                                    .iter()
-                                    .map(|prop| {
-                                        (
-                                            Html::text(
-                                                prop.map(|prop| prop.name().clone())
-                                                    .unwrap_or_else(|| String::from("")),
-                                            ),
-                                            BlockProp::new(
-                                                self,
-                                                None,
-                                                block_prop::Props {
-                                                    arena: ArenaMut::clone(&self.arena),
-                                                    data: BlockMut::clone(&prop),
-                                                },
-                                                Sub::map(|sub| match sub {
-                                                    block_prop::On::UpdateBlocks {
-                                                        update,
-                                                        insert,
-                                                    } => Msg::Sub(On::UpdateBlocks {
-                                                        update,
-                                                        insert,
+                    }),
+                    (
+                        Attributes::new(),
+                        Events::new(),
+                        vec![
+                            vec![(
+                                Html::text(String::from("Common")),
+                                Tab0::empty(
+                                    self,
+                                    None,
+                                    tab_0::Props {
+                                        character: BlockMut::clone(&self.character),
+                                    },
+                                    Sub::map(|sub| match sub {
+                                        tab_0::On::OpenModal(modal) => Msg::SetShowingModal(modal),
+                                        tab_0::On::PushTexture => Msg::PushTexture,
+                                        tab_0::On::SetColor(pallet) => Msg::SetColor(pallet),
+                                        tab_0::On::SetDescription(description) => {
+                                            Msg::SetDescription(description)
+                                        }
+                                        tab_0::On::SetDisplayName0(dn0) => {
+                                            Msg::SetDisplayName0(dn0)
+                                        }
+                                        tab_0::On::SetDisplayName1(dn1) => {
+                                            Msg::SetDisplayName1(dn1)
+                                        }
+                                        tab_0::On::SetName(name) => Msg::SetName(name),
+                                        tab_0::On::SetSelectedTextureIdx(tex_idx) => {
+                                            Msg::SetSelectedTextureIdx(tex_idx)
+                                        }
+                                        tab_0::On::SetSize(size) => Msg::SetSize(size),
+                                        tab_0::On::SetZOffset(z_offset) => {
+                                            Msg::SetZOffset(z_offset)
+                                        }
+                                        tab_0::On::SetTexSize(tex_size) => {
+                                            Msg::SetTexSize(tex_size)
+                                        }
+                                        tab_0::On::SetSpeechDuration(speech_duration) => {
+                                            Msg::SetSpeechDuration(speech_duration)
+                                        }
+                                        tab_0::On::SetTextureName(tex_idx, tex_name) => {
+                                            Msg::SetTextureName(tex_idx, tex_name)
+                                        }
+                                    }),
+                                ),
+                            )],
+                            self.character
+                                .map(|character| {
+                                    character
+                                        .properties()
+                                        .iter()
+                                        .map(|prop| {
+                                            (
+                                                Html::text(
+                                                    prop.map(|prop| prop.name().clone())
+                                                        .unwrap_or_else(|| String::from("")),
+                                                ),
+                                                BlockProp::new(
+                                                    self,
+                                                    None,
+                                                    block_prop::Props {
+                                                        arena: ArenaMut::clone(&self.arena),
+                                                        data: BlockMut::clone(&prop),
+                                                        properties: character.properties().clone(),
+                                                        client_id: Rc::clone(&self.client_id),
+                                                    },
+                                                    Sub::map(|sub| match sub {
+                                                        block_prop::On::UpdateBlocks {
+                                                            update,
+                                                            insert,
+                                                        } => Msg::Sub(On::UpdateBlocks {
+                                                            update,
+                                                            insert,
+                                                        }),
+                                                        block_prop::On::RemoveProperty {
+                                                            block_id,
+                                                        } => Msg::RemoveProperty(block_id),
                                                     }),
-                                                    block_prop::On::RemoveProperty { block_id } => {
-                                                        Msg::RemoveProperty(block_id)
-                                                    }
-                                                }),
-                                                (),
-                                            ),
-                                        )
-                                    })
-                                    .collect::<Vec<_>>()
-                            })
-                            .unwrap_or_default(),
-                        vec![(
-                            Html::span(
-                                Attributes::new(),
-                                Events::new().on_click(self, |_| Msg::AddProperty),
-                                vec![Html::text(String::from("追加"))],
-                            ),
-                            Html::none(),
-                        )],
-                    ]
-                    .into_iter()
-                    .flatten()
-                    .collect::<Vec<_>>(),
+                                                    (),
+                                                ),
+                                            )
+                                        })
+                                        .collect::<Vec<_>>()
+                                })
+                                .unwrap_or_default(),
+                            vec![(
+                                Html::span(
+                                    Attributes::new(),
+                                    Events::new().on_click(self, |_| Msg::AddProperty),
+                                    vec![Html::text(String::from("追加"))],
+                                ),
+                                Html::none(),
+                            )],
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>(),
+                    ),
                 ),
-            )],
+            ],
         )
     }
 }
@@ -422,9 +469,14 @@ impl Styled for RoomModelessCharacter {
     fn style() -> Style {
         style! {
             ".base" {
+                "display": "grid";
+                "grid-template-columns": "1fr";
+                "grid-template-rows": "max-content 1fr";
+                "row-gap": ".35rem";
                 "width": "100%";
                 "height": "100%";
                 "padding-top": ".65rem";
+                "overflow": "hidden";
             }
         }
     }
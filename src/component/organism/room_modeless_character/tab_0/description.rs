@@ -119,6 +119,8 @@ impl Description {
     }
 
     fn render_edit(&self, description: &String) -> Vec<Html> {
+        let parse_error = block::chat_message::Message::from_str(description).err();
+
         vec![
             Btn::primary(
                 Attributes::new(),
@@ -130,16 +132,36 @@ impl Description {
                 Events::new().on_input(self, |desc| Msg::SetEditingDescription(desc)),
                 vec![],
             ),
+            parse_error
+                .map(|error| {
+                    chat_message::parse_error(Attributes::new(), Events::new(), description, &error)
+                })
+                .unwrap_or_else(|| Html::none()),
         ]
     }
 
     fn render_view(&self, description: &block::chat_message::Message) -> Vec<Html> {
+        let parse_error = self
+            .character
+            .map(|character| {
+                let description = character.description();
+                description
+                    .parse_error()
+                    .map(|error| (description.raw().clone(), error))
+            })
+            .unwrap_or(None);
+
         vec![
             Btn::secondary(
                 Attributes::new(),
                 Events::new().on_click(self, |_| Msg::SetDescriptionAsEdit),
                 vec![Html::text("編集")],
             ),
+            parse_error
+                .map(|(raw, error)| {
+                    chat_message::parse_error(Attributes::new(), Events::new(), &raw, &error)
+                })
+                .unwrap_or_else(|| Html::none()),
             chat_message::div(Attributes::new(), Events::new(), description),
         ]
     }
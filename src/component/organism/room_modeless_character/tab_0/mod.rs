@@ -31,6 +31,8 @@ pub struct Props {
 pub enum Msg {
     NoOp,
     Sub(On),
+    ExportTemplateAsToml,
+    ExportTemplateAsJson,
 }
 
 pub enum On {
@@ -43,6 +45,7 @@ pub enum On {
     SetSize(f64),
     SetZOffset(f64),
     SetTexSize(f64),
+    SetSpeechDuration(f64),
     SetSelectedTextureIdx(usize),
     SetTextureName(usize, String),
     PushTexture,
@@ -85,6 +88,22 @@ impl Update for Tab0 {
         match msg {
             Msg::NoOp => Cmd::none(),
             Msg::Sub(event) => Cmd::submit(event),
+            Msg::ExportTemplateAsToml => {
+                let template = unwrap!(
+                    self.character.map(block::character::Template::from_character);
+                    Cmd::none()
+                );
+                template.download_as_toml();
+                Cmd::none()
+            }
+            Msg::ExportTemplateAsJson => {
+                let template = unwrap!(
+                    self.character.map(block::character::Template::from_character);
+                    Cmd::none()
+                );
+                template.download_as_json();
+                Cmd::none()
+            }
         }
     }
 }
@@ -254,6 +273,25 @@ impl Tab0 {
                                 theme: slider::Theme::Light,
                             },
                         ),
+                        Text::span("吹き出しの表示時間（秒）"),
+                        Slider::new(
+                            self,
+                            None,
+                            slider::Position::Linear {
+                                min: 0.0,
+                                max: 60.0,
+                                val: character.speech_duration(),
+                                step: 1.0,
+                            },
+                            Sub::map(move |sub| match sub {
+                                slider::On::Input(x) => Msg::Sub(On::SetSpeechDuration(x)),
+                                _ => Msg::NoOp,
+                            }),
+                            slider::Props {
+                                range_is_editable: false,
+                                theme: slider::Theme::Light,
+                            },
+                        ),
                     ],
                 ),
                 Html::div(
@@ -307,6 +345,23 @@ impl Tab0 {
                                     .collect(),
                             ),
                         ),
+                        Text::span("テンプレート"),
+                        Btn::group(
+                            Attributes::new(),
+                            Events::new(),
+                            vec![
+                                Btn::secondary(
+                                    Attributes::new(),
+                                    Events::new().on_click(self, |_| Msg::ExportTemplateAsToml),
+                                    vec![fa::fas_i("fa-file-export"), Html::text("TOML")],
+                                ),
+                                Btn::secondary(
+                                    Attributes::new(),
+                                    Events::new().on_click(self, |_| Msg::ExportTemplateAsJson),
+                                    vec![fa::fas_i("fa-file-export"), Html::text("JSON")],
+                                ),
+                            ],
+                        ),
                     ],
                 ),
             ],
@@ -0,0 +1,125 @@
+use super::ChatUser;
+use crate::arena::block;
+use crate::libs::bcdice::js::GameSystemClass;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref COMMAND: Regex = Regex::new(r"\{\\([^\s\[\]{}\\]*)\z").unwrap();
+    static ref REFERENCE: Regex = Regex::new(r"\{([^\s\[\]{}\\.]*)\z").unwrap();
+    static ref WORD: Regex = Regex::new(r"([0-9A-Za-z]+)\z").unwrap();
+}
+
+const COMMANDS: [&str; 7] = ["div", "grid", "rb", "fas", "capture", "ref", "roll"];
+const MAX_SUGGESTIONS: usize = 8;
+
+pub struct Suggestion {
+    pub label: String,
+    pub from: usize,
+    pub text: String,
+}
+
+impl Suggestion {
+    pub fn apply(&self, input: &str, caret: usize) -> String {
+        format!("{}{}{}", &input[..self.from], self.text, &input[caret..])
+    }
+}
+
+pub fn suggest(
+    input: &str,
+    caret: usize,
+    chat_user: &ChatUser,
+    game_system_class: Option<&GameSystemClass>,
+) -> Vec<Suggestion> {
+    let before = &input[..caret];
+
+    if let Some(partial) = COMMAND.captures(before).and_then(|captures| captures.get(1)) {
+        return COMMANDS
+            .iter()
+            .filter(|name| name.starts_with(partial.as_str()) && **name != partial.as_str())
+            .map(|name| Suggestion {
+                label: format!(r"{{\{}", name),
+                from: partial.start(),
+                text: String::from(*name),
+            })
+            .collect();
+    }
+
+    if let Some(partial) = REFERENCE.captures(before).and_then(|captures| captures.get(1)) {
+        return chat_user
+            .property_names()
+            .into_iter()
+            .filter(|name| name.starts_with(partial.as_str()))
+            .take(MAX_SUGGESTIONS)
+            .map(|name| Suggestion {
+                label: format!("{{{}}}", name),
+                from: partial.start(),
+                text: format!("{}}}", name),
+            })
+            .collect();
+    }
+
+    let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let line = &before[line_start..];
+    if line.trim().is_empty() {
+        return vec![];
+    }
+
+    let mut suggestions: Vec<_> = pallet_items(chat_user)
+        .into_iter()
+        .filter(|item| item.starts_with(line) && item != line)
+        .map(|item| Suggestion {
+            label: item.clone(),
+            from: line_start,
+            text: item,
+        })
+        .collect();
+
+    if let Some((game_system_class, word)) = join_some!(
+        game_system_class,
+        WORD.captures(before).and_then(|captures| captures.get(1))
+    ) {
+        let partial = word.as_str().to_uppercase();
+        for prefix in game_system_class.command_prefixes() {
+            if prefix.to_uppercase().starts_with(&partial) && prefix.len() > partial.len() {
+                suggestions.push(Suggestion {
+                    label: prefix.clone(),
+                    from: word.start(),
+                    text: prefix,
+                });
+            }
+        }
+    }
+
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+fn pallet_items(chat_user: &ChatUser) -> Vec<String> {
+    if let ChatUser::Character(character) = chat_user {
+        character
+            .map(|character| {
+                let chatpallet = character.chatpallet();
+                let mut items = vec![];
+                push_items(&mut items, chatpallet.children(), chatpallet.sub_sections());
+                for section in chatpallet.sections() {
+                    push_items(&mut items, section.children(), section.sub_sections());
+                }
+                items
+            })
+            .unwrap_or_default()
+    } else {
+        vec![]
+    }
+}
+
+fn push_items(
+    items: &mut Vec<String>,
+    children: &Vec<String>,
+    sub_sections: &Vec<block::character::ChatPalletSubSection>,
+) {
+    items.extend(children.iter().cloned());
+    for sub_section in sub_sections {
+        items.extend(sub_section.children().iter().cloned());
+    }
+}
@@ -1,27 +1,64 @@
-use super::super::atom::{attr, btn::Btn, chat_message};
-use crate::arena::{block, BlockMut};
+use super::super::atom::{
+    attr,
+    btn::{self, Btn},
+    chat_message, fa,
+};
+use super::search::{self, Search};
+use crate::arena::{block, BlockMut, BlockRef};
+use crate::dicebot::exchange::{DiceExchange, Verification};
+use crate::libs::random_id::U128Id;
 use isaribi::{
     style,
     styled::{Style, Styled},
 };
 use kagura::prelude::*;
 use nusa::prelude::*;
+use std::rc::Rc;
 
 pub struct Props {
     pub data: BlockMut<block::ChatChannel>,
+    pub client_id: Rc<String>,
+    pub dice_exchange: Rc<DiceExchange>,
 }
 
-pub enum Msg {}
+pub enum Msg {
+    Sub(On),
+    SetEditingMessage(Option<(U128Id, String)>),
+    SetEditingText(String),
+    SaveEditingMessage,
+    ScrollToMessage(U128Id),
+    JumpToMessage(U128Id),
+    SetIsShowingSearch(bool),
+    SetIsShowingAll(bool),
+}
 
-pub enum On {}
+pub enum On {
+    EditMessage {
+        message: BlockMut<block::ChatMessage>,
+        text: String,
+    },
+    DeleteMessage {
+        message: BlockMut<block::ChatMessage>,
+    },
+    ReplyMessage {
+        message: BlockRef<block::ChatMessage>,
+    },
+}
 
 pub struct Channel {
     data: BlockMut<block::ChatChannel>,
+    client_id: Rc<String>,
+    dice_exchange: Rc<DiceExchange>,
+    editing_message: Option<(U128Id, String)>,
+    highlighted_message: Option<U128Id>,
+    is_showing_search: bool,
+    is_showing_all: bool,
     element_id: ElementId,
 }
 
 ElementId! {
-    input_channel_name
+    input_channel_name,
+    channel_log
 }
 
 impl Component for Channel {
@@ -36,6 +73,12 @@ impl Constructor for Channel {
     fn constructor(props: Self::Props) -> Self {
         Self {
             data: props.data,
+            client_id: props.client_id,
+            dice_exchange: props.dice_exchange,
+            editing_message: None,
+            highlighted_message: None,
+            is_showing_search: false,
+            is_showing_all: false,
             element_id: ElementId::new(),
         }
     }
@@ -44,8 +87,81 @@ impl Constructor for Channel {
 impl Update for Channel {
     fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
         self.data = props.data;
+        self.client_id = props.client_id;
+        self.dice_exchange = props.dice_exchange;
         Cmd::none()
     }
+
+    fn update(mut self: Pin<&mut Self>, msg: Self::Msg) -> Cmd<Self> {
+        match msg {
+            Msg::Sub(sub) => Cmd::submit(sub),
+            Msg::SetEditingMessage(editing_message) => {
+                self.editing_message = editing_message;
+                Cmd::none()
+            }
+            Msg::SetEditingText(text) => {
+                if let Some((_, editing_text)) = &mut self.editing_message {
+                    *editing_text = text;
+                }
+                Cmd::none()
+            }
+            Msg::SaveEditingMessage => {
+                let is_valid = self.editing_message.as_ref().map(|(_, text)| {
+                    block::chat_message::Message::from_str(text).is_ok()
+                });
+                if is_valid != Some(true) {
+                    return Cmd::none();
+                }
+                let (message_id, text) = unwrap!(self.editing_message.take(); Cmd::none());
+                let message = self.data.map(|channel| {
+                    channel
+                        .messages()
+                        .iter()
+                        .find(|message| message.id() == message_id)
+                        .map(BlockMut::clone)
+                });
+                let message = unwrap!(message.unwrap_or(None); Cmd::none());
+                Cmd::submit(On::EditMessage { message, text })
+            }
+            Msg::ScrollToMessage(message_id) => {
+                let element = web_sys::window()
+                    .and_then(|window| window.document())
+                    .and_then(|document| {
+                        document.get_element_by_id(&self.message_element_id(&message_id))
+                    });
+                if let Some(element) = element {
+                    element.scroll_into_view();
+                }
+                Cmd::none()
+            }
+            Msg::JumpToMessage(message_id) => {
+                let is_shown = self.data.map(|channel| {
+                    channel
+                        .messages()
+                        .iter()
+                        .rev()
+                        .take(50)
+                        .any(|message| message.id() == message_id)
+                });
+                if !is_shown.unwrap_or(false) {
+                    self.is_showing_all = true;
+                }
+                self.highlighted_message = Some(U128Id::clone(&message_id));
+                Cmd::task(async move { Cmd::chain(Msg::ScrollToMessage(message_id)) })
+            }
+            Msg::SetIsShowingSearch(is_showing_search) => {
+                self.is_showing_search = is_showing_search;
+                if !is_showing_search {
+                    self.highlighted_message = None;
+                }
+                Cmd::none()
+            }
+            Msg::SetIsShowingAll(is_showing_all) => {
+                self.is_showing_all = is_showing_all;
+                Cmd::none()
+            }
+        }
+    }
 }
 
 impl Render<Html> for Channel {
@@ -68,15 +184,30 @@ impl Render<Html> for Channel {
 impl Channel {
     fn render_header(&self, chat_channel: &block::ChatChannel) -> Html {
         Html::div(
-            Attributes::new(),
+            Attributes::new().class(Self::class("channel-header")),
             Events::new(),
-            vec![Html::input(
-                Attributes::new()
-                    .id(&self.element_id.input_channel_name)
-                    .value(chat_channel.name()),
-                Events::new(),
-                vec![],
-            )],
+            vec![
+                Html::input(
+                    Attributes::new()
+                        .id(&self.element_id.input_channel_name)
+                        .value(chat_channel.name()),
+                    Events::new(),
+                    vec![],
+                ),
+                Btn::with_variant(
+                    if self.is_showing_search {
+                        btn::Variant::Primary
+                    } else {
+                        btn::Variant::Secondary
+                    },
+                    Attributes::new().title("検索"),
+                    Events::new().on_click(self, {
+                        let is_showing_search = self.is_showing_search;
+                        move |_| Msg::SetIsShowingSearch(!is_showing_search)
+                    }),
+                    vec![fa::fas_i("fa-search")],
+                ),
+            ],
         )
     }
 
@@ -85,10 +216,26 @@ impl Channel {
             Attributes::new().class(Self::class("channel-main")),
             Events::new(),
             vec![
-                if chat_channel.messages().len() > 25 {
+                if self.is_showing_search {
+                    Search::empty(
+                        self,
+                        None,
+                        search::Props {
+                            data: BlockMut::clone(&self.data),
+                        },
+                        Sub::map(|sub| match sub {
+                            search::On::JumpToMessage(message_id) => {
+                                Msg::JumpToMessage(message_id)
+                            }
+                        }),
+                    )
+                } else {
+                    Html::none()
+                },
+                if chat_channel.messages().len() > 25 && !self.is_showing_all {
                     Btn::secondary(
                         Attributes::new().class(Self::class("banner")),
-                        Events::new(),
+                        Events::new().on_click(self, |_| Msg::SetIsShowingAll(true)),
                         vec![Html::text("全チャットログを表示")],
                     )
                 } else {
@@ -101,18 +248,37 @@ impl Channel {
                         .messages()
                         .iter()
                         .rev()
-                        .take(50)
+                        .take(if self.is_showing_all {
+                            chat_channel.messages().len()
+                        } else {
+                            50
+                        })
                         .rev()
-                        .filter_map(|cm| cm.map(|cm: &block::ChatMessage| self.render_message(cm)))
+                        .filter_map(|cm| {
+                            cm.map(|chat_message: &block::ChatMessage| {
+                                self.render_message(cm, chat_message)
+                            })
+                        })
                         .collect(),
                 ),
             ],
         )
     }
 
-    fn render_message(&self, chat_message: &block::ChatMessage) -> Html {
+    fn render_message(
+        &self,
+        block: &BlockMut<block::ChatMessage>,
+        chat_message: &block::ChatMessage,
+    ) -> Html {
         Html::div(
-            Attributes::new().class(Self::class("channel-message")),
+            Attributes::new()
+                .id(self.message_element_id(&block.id()))
+                .class(Self::class("channel-message"))
+                .class(if self.highlighted_message == Some(block.id()) {
+                    Self::class("channel-message--highlighted")
+                } else {
+                    String::new()
+                }),
             Events::new(),
             vec![
                 chat_message
@@ -171,20 +337,235 @@ impl Channel {
                                 ),
                             ],
                         ),
+                        Html::div(
+                            Attributes::new().class(Self::class("channel-message-heading-row")),
+                            Events::new(),
+                            vec![
+                                Html::span(
+                                    Attributes::new(),
+                                    Events::new(),
+                                    vec![
+                                        self.render_message_proof(block, chat_message),
+                                        if chat_message.is_edited() {
+                                            attr::span(
+                                                Attributes::new()
+                                                    .class(Self::class("channel-message-edited")),
+                                                "（編集済み）",
+                                            )
+                                        } else {
+                                            Html::none()
+                                        },
+                                    ],
+                                ),
+                                attr::span(
+                                    Attributes::new()
+                                        .class(Self::class("channel-message-client")),
+                                    chat_message.sender().client_id().as_ref(),
+                                ),
+                            ],
+                        ),
+                    ],
+                ),
+                chat_message
+                    .reference()
+                    .map(|reference| self.render_message_reference(reference))
+                    .unwrap_or_else(|| Html::none()),
+                match &self.editing_message {
+                    Some((message_id, text)) if *message_id == block.id() => {
+                        self.render_message_editor(text)
+                    }
+                    _ => chat_message::div(
+                        Attributes::new().class(Self::class("channel-message-content")),
+                        Events::new(),
+                        chat_message.message(),
+                    ),
+                },
+                self.render_message_menu(block, chat_message),
+            ],
+        )
+    }
+
+    fn message_element_id(&self, message_id: &U128Id) -> String {
+        format!("{}-{}", self.element_id.channel_log, message_id)
+    }
+
+    fn render_message_proof(
+        &self,
+        block: &BlockMut<block::ChatMessage>,
+        chat_message: &block::ChatMessage,
+    ) -> Html {
+        let proof =
+            unwrap!(chat_message.dice_roll().and_then(|dice_roll| dice_roll.proof()); Html::none());
+        let token = proof.token();
+
+        let (class_name, icon, title) = match self.dice_exchange.verification(&block.id()) {
+            None => (
+                "channel-message-proof--pending",
+                "fa-hourglass-half",
+                format!("出目を検証しています\n{}", token),
+            ),
+            Some(Verification::Verified { abandoned: 0 }) => (
+                "channel-message-proof--verified",
+                "fa-circle-check",
+                format!("出目を検証しました\n{}", token),
+            ),
+            Some(Verification::Verified { abandoned }) => (
+                "channel-message-proof--abandoned",
+                "fa-circle-exclamation",
+                format!(
+                    "出目を検証しました。ただし、この振り手には結果を公開しなかったロールが{}回あります\n{}",
+                    abandoned, token
+                ),
+            ),
+            Some(Verification::Unverifiable(reason)) => (
+                "channel-message-proof--unverifiable",
+                "fa-circle-question",
+                format!("出目を検証できません：{}\n{}", reason, token),
+            ),
+            Some(Verification::Invalid(reason)) => (
+                "channel-message-proof--invalid",
+                "fa-triangle-exclamation",
+                format!("出目が一致しません：{}\n{}", reason, token),
+            ),
+        };
+
+        Html::span(
+            Attributes::new()
+                .class(Self::class("channel-message-proof"))
+                .class(Self::class(class_name))
+                .title(title),
+            Events::new(),
+            vec![fa::fas_i(icon)],
+        )
+    }
+
+    fn render_message_reference(&self, reference: &BlockRef<block::ChatMessage>) -> Html {
+        let message_id = reference.id();
+        reference
+            .map(|original: &block::ChatMessage| {
+                let text = original.message().to_plain_text();
+                let text = text.lines().next().unwrap_or("");
+                let text = if text.chars().count() > 40 {
+                    format!("{}…", text.chars().take(40).collect::<String>())
+                } else {
+                    String::from(text)
+                };
+
+                Html::div(
+                    Attributes::new().class(Self::class("channel-message-reference")),
+                    Events::new().on_click(self, move |_| Msg::JumpToMessage(message_id)),
+                    vec![
+                        fa::fas_i("fa-reply"),
                         attr::span(
-                            Attributes::new().class(Self::class("channel-message-client")),
-                            chat_message.sender().client_id().as_ref(),
+                            Attributes::new().class(Self::class("channel-message-sender")),
+                            original.sender().name(),
                         ),
+                        Html::text(text),
                     ],
+                )
+            })
+            .unwrap_or_else(|| {
+                Html::div(
+                    Attributes::new().class(Self::class("channel-message-reference")),
+                    Events::new(),
+                    vec![fa::fas_i("fa-reply"), Html::text("削除されたメッセージ")],
+                )
+            })
+    }
+
+    fn is_editable(&self, chat_message: &block::ChatMessage) -> bool {
+        *chat_message.sender().kind() == block::chat_message::SenderKind::Normal
+            && *chat_message.sender().client_id() == self.client_id
+    }
+
+    fn render_message_editor(&self, text: &String) -> Html {
+        let parse_error = block::chat_message::Message::from_str(text).err();
+
+        Html::div(
+            Attributes::new().class(Self::class("channel-message-content")),
+            Events::new(),
+            vec![
+                Html::textarea(
+                    Attributes::new()
+                        .class(Self::class("channel-message-editor"))
+                        .value(text),
+                    Events::new().on_input(self, |text| Msg::SetEditingText(text)),
+                    vec![],
                 ),
-                chat_message::div(
-                    Attributes::new().class(Self::class("channel-message-content")),
+                parse_error
+                    .map(|error| {
+                        chat_message::parse_error(Attributes::new(), Events::new(), text, &error)
+                    })
+                    .unwrap_or_else(|| Html::none()),
+                Html::div(
+                    Attributes::new().class(Self::class("channel-message-menu")),
                     Events::new(),
-                    chat_message.message(),
+                    vec![
+                        Btn::secondary(
+                            Attributes::new(),
+                            Events::new().on_click(self, |_| Msg::SetEditingMessage(None)),
+                            vec![Html::text("キャンセル")],
+                        ),
+                        Btn::primary(
+                            Attributes::new(),
+                            Events::new().on_click(self, |_| Msg::SaveEditingMessage),
+                            vec![Html::text("保存")],
+                        ),
+                    ],
                 ),
             ],
         )
     }
+
+    fn render_message_menu(
+        &self,
+        block: &BlockMut<block::ChatMessage>,
+        chat_message: &block::ChatMessage,
+    ) -> Html {
+        Html::div(
+            Attributes::new().class(Self::class("channel-message-menu")),
+            Events::new(),
+            vec![
+                Btn::light(
+                    Attributes::new().title("返信"),
+                    Events::new().on_click(self, {
+                        let message = block.as_ref();
+                        move |_| Msg::Sub(On::ReplyMessage { message })
+                    }),
+                    vec![fa::fas_i("fa-reply")],
+                ),
+            ]
+            .into_iter()
+            .chain(if self.is_editable(chat_message) {
+                vec![
+                    Btn::light(
+                        Attributes::new().title("編集"),
+                        Events::new().on_click(self, {
+                            let message_id = block.id();
+                            let text = if chat_message.source().is_empty() {
+                                chat_message.message().to_source()
+                            } else {
+                                chat_message.source().clone()
+                            };
+                            move |_| Msg::SetEditingMessage(Some((message_id, text)))
+                        }),
+                        vec![fa::fas_i("fa-pen")],
+                    ),
+                    Btn::light(
+                        Attributes::new().title("削除"),
+                        Events::new().on_click(self, {
+                            let message = BlockMut::clone(block);
+                            move |_| Msg::Sub(On::DeleteMessage { message })
+                        }),
+                        vec![fa::fas_i("fa-trash")],
+                    ),
+                ]
+            } else {
+                vec![]
+            })
+            .collect(),
+        )
+    }
 }
 
 impl Styled for Channel {
@@ -197,10 +578,16 @@ impl Styled for Channel {
                 "overflow": "hidden";
             }
 
+            ".channel-header" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content";
+                "column-gap": ".35rem";
+            }
+
             ".channel-main" {
                 "display": "grid";
                 "grid-template-columns": "1fr";
-                "grid-template-rows": "max-content 1fr";
+                "grid-template-rows": "max-content max-content 1fr";
                 "overflow": "hidden";
             }
 
@@ -258,6 +645,65 @@ impl Styled for Channel {
                 "white-space": "pre-wrap";
                 "grid-column": "2";
             }
+
+            ".channel-message--highlighted" {
+                "background-color": format!("{}", crate::libs::color::Pallet::yellow(1));
+            }
+
+            ".channel-message-reference" {
+                "grid-column": "2";
+                "display": "flex";
+                "column-gap": ".35rem";
+                "align-items": "center";
+                "padding-left": ".35rem";
+                "border-left": format!(".2rem solid {}", crate::libs::color::Pallet::gray(5));
+                "font-size": "0.9em";
+                "overflow": "hidden";
+                "white-space": "nowrap";
+                "cursor": "pointer";
+            }
+
+            ".channel-message-edited" {
+                "font-size": "0.9em";
+                "color": format!("{}", crate::libs::color::Pallet::gray(7));
+            }
+
+            ".channel-message-proof" {
+                "margin-right": ".35rem";
+            }
+
+            ".channel-message-proof--pending" {
+                "color": format!("{}", crate::libs::color::Pallet::gray(7));
+            }
+
+            ".channel-message-proof--verified" {
+                "color": format!("{}", crate::libs::color::Pallet::green(7));
+            }
+
+            ".channel-message-proof--abandoned" {
+                "color": format!("{}", crate::libs::color::Pallet::orange(7));
+            }
+
+            ".channel-message-proof--unverifiable" {
+                "color": format!("{}", crate::libs::color::Pallet::gray(5));
+            }
+
+            ".channel-message-proof--invalid" {
+                "color": format!("{}", crate::libs::color::Pallet::red(7));
+            }
+
+            ".channel-message-editor" {
+                "width": "100%";
+                "min-height": "6em";
+                "resize": "vertical";
+            }
+
+            ".channel-message-menu" {
+                "grid-column": "2";
+                "display": "flex";
+                "justify-content": "flex-end";
+                "column-gap": ".35rem";
+            }
         }
     }
 }
@@ -1,5 +1,7 @@
-use super::super::atom::{btn::Btn, text::Text};
-use super::{InputingMessage, SharedState};
+use super::super::atom::{btn::Btn, chat_message, fa, text::Text};
+use super::autocomplete::{self, Suggestion};
+use super::{ChatUser, InputingMessage, SharedState};
+use crate::libs::bcdice::js::GameSystemClass;
 use isaribi::{
     style,
     styled::{Style, Styled},
@@ -12,21 +14,29 @@ use wasm_bindgen::JsCast;
 
 pub struct Props {
     pub shared_state: Rc<RefCell<SharedState>>,
+    pub chat_user: ChatUser,
+    pub game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
 }
 
 pub enum Msg {
     NoOp,
     Sub(On),
-    SetInputingChatMessage(String),
+    SetInputingChatMessage(String, u32),
+    Complete(usize),
 }
 
 pub enum On {
     SendInputingChatMessage,
+    CancelReplying,
 }
 
 pub struct Controller {
     shared_state: Rc<RefCell<SharedState>>,
+    chat_user: ChatUser,
+    game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
     ignore_input: Rc<Cell<bool>>,
+    suggestions: Vec<Suggestion>,
+    caret: usize,
 }
 
 impl Component for Controller {
@@ -41,7 +51,11 @@ impl Constructor for Controller {
     fn constructor(props: Self::Props) -> Self {
         Self {
             shared_state: props.shared_state,
+            chat_user: props.chat_user,
+            game_system_class: props.game_system_class,
             ignore_input: Rc::new(Cell::new(false)),
+            suggestions: vec![],
+            caret: 0,
         }
     }
 }
@@ -49,50 +63,112 @@ impl Constructor for Controller {
 impl Update for Controller {
     fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
         self.shared_state = props.shared_state;
+        if self.chat_user != props.chat_user {
+            self.suggestions.clear();
+        }
+        self.chat_user = props.chat_user;
+        self.game_system_class = props.game_system_class;
         Cmd::none()
     }
 
-    fn update(self: Pin<&mut Self>, msg: Self::Msg) -> Cmd<Self> {
+    fn update(mut self: Pin<&mut Self>, msg: Self::Msg) -> Cmd<Self> {
         match msg {
             Msg::NoOp => Cmd::none(),
-            Msg::Sub(e) => Cmd::submit(e),
-            Msg::SetInputingChatMessage(text) => {
-                self.shared_state.borrow_mut().inputing_message = InputingMessage::Text(text);
+            Msg::Sub(e) => {
+                self.suggestions.clear();
+                Cmd::submit(e)
+            }
+            Msg::SetInputingChatMessage(text, caret) => {
+                let caret = Self::byte_offset(&text, caret);
+                let suggestions = autocomplete::suggest(
+                    &text,
+                    caret,
+                    &self.chat_user,
+                    self.game_system_class.borrow().as_ref(),
+                );
+                self.suggestions = suggestions;
+                self.caret = caret;
+
+                let mut shared_state = self.shared_state.borrow_mut();
+                shared_state.inputing_message = InputingMessage::Text(text);
+                shared_state.parse_error = None;
+                Cmd::none()
+            }
+            Msg::Complete(idx) => {
+                if let Some(suggestion) = self.suggestions.get(idx) {
+                    let mut shared_state = self.shared_state.borrow_mut();
+                    let text = shared_state.inputing_message.to_string();
+                    let caret = self.caret.min(text.len());
+                    shared_state.inputing_message =
+                        InputingMessage::Text(suggestion.apply(&text, caret));
+                }
+                self.suggestions.clear();
                 Cmd::none()
             }
         }
     }
 }
 
+impl Controller {
+    fn byte_offset(text: &str, utf16_offset: u32) -> usize {
+        let mut utf16_len = 0;
+        for (idx, c) in text.char_indices() {
+            if utf16_len >= utf16_offset as usize {
+                return idx;
+            }
+            utf16_len += c.len_utf16();
+        }
+        text.len()
+    }
+}
+
 impl Render<Html> for Controller {
     type Children = ();
     fn render(&self, _: Self::Children) -> Html {
+        let textarea_class = if self.shared_state.borrow().parse_error.is_some() {
+            "textarea-error"
+        } else {
+            "textarea"
+        };
+
         Self::styled(Html::div(
             Attributes::new().class(Self::class("base")),
             Events::new(),
             vec![
+                self.render_replying_message(),
+                self.render_suggestions(),
                 Html::textarea(
-                    Attributes::new().value(self.shared_state.borrow().inputing_message.to_string()),
+                    Attributes::new()
+                        .class(Self::class(textarea_class))
+                        .value(self.shared_state.borrow().inputing_message.to_string()),
                     Events::new()
                         .on("input", self, {
                             let ignore_input = Rc::clone(&self.ignore_input);
+                            let shared_state = Rc::clone(&self.shared_state);
                             move |e| {
                                 let target = unwrap!(e.target(); Msg::NoOp);
                                 let target = unwrap!(target.dyn_into::<web_sys::HtmlTextAreaElement>().ok(); Msg::NoOp);
                                 if ignore_input.get() {
                                     ignore_input.set(false);
-                                    target.set_value("");
-                                    Msg::SetInputingChatMessage(String::from(""))
+                                    target.set_value(&shared_state.borrow().inputing_message.to_string());
+                                    Msg::NoOp
                                 } else {
-                                    Msg::SetInputingChatMessage(target.value())
+                                    let caret = target.selection_start().ok().flatten();
+                                    let text = target.value();
+                                    let caret = caret.unwrap_or(text.encode_utf16().count() as u32);
+                                    Msg::SetInputingChatMessage(text, caret)
                                 }
                             }
                         })
                         .on_keydown(self, {
                             let ignore_input = Rc::clone(&self.ignore_input);
+                            let has_suggestions = !self.suggestions.is_empty();
                             move |e| {
                                 let e = unwrap!(e.dyn_into::<web_sys::KeyboardEvent>().ok(); Msg::NoOp);
-                                if e.key() == "Enter" && !e.shift_key() {
+                                if e.key() == "Tab" && has_suggestions {
+                                    e.prevent_default();
+                                    Msg::Complete(0)
+                                } else if e.key() == "Enter" && !e.shift_key() {
                                     ignore_input.set(true);
                                     Msg::Sub(On::SendInputingChatMessage)
                                 } else {
@@ -102,6 +178,7 @@ impl Render<Html> for Controller {
                         }),
                     vec![],
                 ),
+                self.render_parse_error(),
                 Html::div(
                     Attributes::new().class(Self::class("guide")),
                     Events::new(),
@@ -119,10 +196,91 @@ impl Render<Html> for Controller {
     }
 }
 
+impl Controller {
+    fn render_suggestions(&self) -> Html {
+        if self.suggestions.is_empty() {
+            return Html::none();
+        }
+
+        Html::div(
+            Attributes::new().class(Self::class("suggestions")),
+            Events::new(),
+            self.suggestions
+                .iter()
+                .enumerate()
+                .map(|(idx, suggestion)| {
+                    Btn::menu(
+                        Attributes::new().class(Self::class("suggestion")),
+                        Events::new().on_click(self, move |_| Msg::Complete(idx)),
+                        vec![
+                            Html::text(&suggestion.label),
+                            if idx == 0 {
+                                Text::span("Tab")
+                            } else {
+                                Html::none()
+                            },
+                        ],
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn render_parse_error(&self) -> Html {
+        let shared_state = self.shared_state.borrow();
+        if let Some(error) = &shared_state.parse_error {
+            chat_message::parse_error(
+                Attributes::new(),
+                Events::new(),
+                &shared_state.inputing_message.to_string(),
+                error,
+            )
+        } else {
+            Html::none()
+        }
+    }
+
+    fn render_replying_message(&self) -> Html {
+        let text = self
+            .shared_state
+            .borrow()
+            .replying_message
+            .as_ref()
+            .and_then(|message| {
+                message.map(|message| {
+                    format!(
+                        "{}：{}",
+                        message.sender().name(),
+                        message.message().to_plain_text().lines().next().unwrap_or("")
+                    )
+                })
+            });
+
+        if let Some(text) = text {
+            Html::div(
+                Attributes::new().class(Self::class("replying")),
+                Events::new(),
+                vec![
+                    fa::fas_i("fa-reply"),
+                    Text::span(text),
+                    Btn::light(
+                        Attributes::new().title("返信をやめる"),
+                        Events::new().on_click(self, |_| Msg::Sub(On::CancelReplying)),
+                        vec![fa::fas_i("fa-times")],
+                    ),
+                ],
+            )
+        } else {
+            Html::div(Attributes::new(), Events::new(), vec![])
+        }
+    }
+}
+
 impl Styled for Controller {
     fn style() -> Style {
         style! {
             ".base" {
+                "position": "relative";
                 "grid-column": "2 / 3";
                 "grid-row": "2 / 3";
                 "padding-left": ".65rem";
@@ -130,9 +288,9 @@ impl Styled for Controller {
                 "height": "10rem";
                 "display": "grid";
                 "grid-template-columns": "1fr";
-                "grid-template-rows": "1fr max-content";
+                "grid-template-rows": "max-content 1fr max-content max-content";
                 "column-gap": ".35rem";
-                "row-gap": ".65rem";
+                "row-gap": ".35rem";
             }
 
             ".base textarea" {
@@ -140,6 +298,43 @@ impl Styled for Controller {
                 "resize": "none";
             }
 
+            ".suggestions" {
+                "position": "absolute";
+                "bottom": "100%";
+                "left": ".65rem";
+                "right": ".65rem";
+                "z-index": "1";
+                "display": "grid";
+                "grid-auto-rows": "max-content";
+                "max-height": "15rem";
+                "overflow-y": "auto";
+                "background-color": crate::libs::color::Pallet::gray(0);
+                "box-shadow": "0 0 0.35rem rgba(0, 0, 0, 0.35)";
+            }
+
+            ".suggestion" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content";
+                "column-gap": ".35rem";
+                "white-space": "nowrap";
+                "overflow": "hidden";
+                "text-overflow": "ellipsis";
+            }
+
+            ".textarea-error" {
+                "border-color": format!("{} !important", crate::libs::color::Pallet::red(7));
+                "outline-color": crate::libs::color::Pallet::red(7);
+            }
+
+            ".replying" {
+                "display": "grid";
+                "grid-template-columns": "max-content 1fr max-content";
+                "column-gap": ".35rem";
+                "align-items": "center";
+                "overflow": "hidden";
+                "white-space": "nowrap";
+            }
+
             ".guide" {
                 "display": "grid";
                 "grid-template-columns": "1fr max-content";
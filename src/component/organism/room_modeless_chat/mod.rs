@@ -1,9 +1,15 @@
 use super::atom::common::Common;
 use super::molecule::tab_menu::{self, TabMenu};
 use super::organism::modal_chat_capture::{self, ModalChatCapture};
+use super::organism::modal_chat_macro::{self, ModalChatMacro};
 use super::organism::modal_chatpallet::{self, ModalChatpallet};
 use crate::arena::{block, user, ArenaMut, BlockMut, BlockRef};
-use crate::libs::bcdice::js::GameSystemClass;
+use crate::dicebot::{
+    exchange::{DiceExchange, Seed},
+    proof,
+};
+use crate::libs::bcdice::api;
+use crate::libs::bcdice::js::{CommandResult, GameSystemClass, Rand};
 use crate::libs::random_id::U128Id;
 use isaribi::{
     style,
@@ -15,9 +21,11 @@ use std::cell::RefCell;
 use std::collections::HashSet;
 use std::rc::Rc;
 
+mod autocomplete;
 mod channel;
 mod chat_pallet;
 mod controller;
+mod search;
 mod send;
 
 use channel::Channel;
@@ -40,24 +48,72 @@ impl PartialEq for ChatUser {
     }
 }
 
+impl ChatUser {
+    fn property_names(&self) -> Vec<String> {
+        let mut names = vec![];
+        if let ChatUser::Character(character) = self {
+            character.map(|character| {
+                for property in character.properties() {
+                    push_property_names(&mut names, "", property);
+                }
+            });
+        }
+        names
+    }
+}
+
+fn push_property_names(
+    names: &mut Vec<String>,
+    prefix: &str,
+    property: &BlockMut<block::Property>,
+) {
+    property.map(|property| {
+        let name = if prefix.is_empty() {
+            property.name().clone()
+        } else {
+            format!("{}::{}", prefix, property.name())
+        };
+        for child in property.children() {
+            push_property_names(names, &name, child);
+        }
+        names.push(name);
+    });
+}
+
 pub struct Props {
     pub arena: ArenaMut,
+    pub world: BlockMut<block::World>,
     pub client_id: Rc<String>,
     pub data: BlockMut<block::Chat>,
     pub user: ChatUser,
     pub game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
+    pub api_game_system: Rc<RefCell<Option<Rc<api::GameSystemClass>>>>,
+    pub dice_exchange: Rc<DiceExchange>,
 }
 
 pub struct WaitingChatMessage {
     channel: BlockMut<block::ChatChannel>,
     message: block::chat_message::Message,
+    source: String,
     descriptions: Rc<Vec<(String, String)>>,
     sender: block::chat_message::Sender,
+    reference: Option<BlockRef<block::ChatMessage>>,
+    edit_target: Option<BlockMut<block::ChatMessage>>,
+}
+
+pub struct WaitingMacroChatMessage {
+    channel: BlockMut<block::ChatChannel>,
+    text: String,
+    params: Rc<Vec<block::chat_message::MacroParam>>,
+    properties: Rc<Vec<String>>,
+    sender: block::chat_message::Sender,
+    reference: Option<BlockRef<block::ChatMessage>>,
 }
 
 pub enum ShowingModal {
     None,
     ChatCapture(WaitingChatMessage),
+    ChatMacro(WaitingMacroChatMessage),
     Chatpallet,
 }
 
@@ -65,9 +121,36 @@ pub enum Msg {
     NoOp,
     SendInputingChatMessage,
     SendWaitingChatMessage(Vec<String>),
+    SendMacroChatMessage(Vec<String>),
     SetShowingModal(ShowingModal),
     SetSelectedChannelIdx(usize),
     SetChatPallet(String),
+    EditChatMessage(
+        BlockMut<block::ChatChannel>,
+        BlockMut<block::ChatMessage>,
+        String,
+    ),
+    DeleteChatMessage(BlockMut<block::ChatChannel>, BlockMut<block::ChatMessage>),
+    SetReplyingMessage(Option<BlockRef<block::ChatMessage>>),
+    PostApiRolledChatMessage {
+        sender: block::chat_message::Sender,
+        channel: BlockMut<block::ChatChannel>,
+        message: block::chat_message::Message,
+        source: String,
+        reference: Option<BlockRef<block::ChatMessage>>,
+        edit_target: Option<BlockMut<block::ChatMessage>>,
+        command_results: Vec<(String, CommandResult)>,
+        errors: Vec<String>,
+    },
+    PostExchangedChatMessage {
+        sender: block::chat_message::Sender,
+        channel: BlockMut<block::ChatChannel>,
+        message: block::chat_message::Message,
+        source: String,
+        reference: Option<BlockRef<block::ChatMessage>>,
+        edit_target: Option<BlockMut<block::ChatMessage>>,
+        seed: Seed,
+    },
 }
 
 pub enum On {
@@ -79,10 +162,13 @@ pub enum On {
 
 pub struct RoomModelessChat {
     arena: ArenaMut,
+    world: BlockMut<block::World>,
     chat: BlockMut<block::Chat>,
     chat_user: ChatUser,
     client_id: Rc<String>,
     game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
+    api_game_system: Rc<RefCell<Option<Rc<api::GameSystemClass>>>>,
+    dice_exchange: Rc<DiceExchange>,
 
     selected_channel_idx: usize,
     showing_modal: ShowingModal,
@@ -92,12 +178,16 @@ pub struct RoomModelessChat {
 
 pub struct SharedState {
     inputing_message: InputingMessage,
+    replying_message: Option<BlockRef<block::ChatMessage>>,
+    parse_error: Option<block::chat_message::ParseError>,
 }
 
 impl SharedState {
     fn new() -> Self {
         Self {
             inputing_message: InputingMessage::Text(String::new()),
+            replying_message: None,
+            parse_error: None,
         }
     }
 }
@@ -152,10 +242,13 @@ impl Constructor for RoomModelessChat {
     fn constructor(props: Props) -> Self {
         Self {
             arena: props.arena,
+            world: props.world,
             chat: props.data,
             chat_user: props.user,
             client_id: props.client_id,
             game_system_class: props.game_system_class,
+            api_game_system: props.api_game_system,
+            dice_exchange: props.dice_exchange,
 
             selected_channel_idx: 0,
             showing_modal: ShowingModal::None,
@@ -168,10 +261,13 @@ impl Constructor for RoomModelessChat {
 impl Update for RoomModelessChat {
     fn on_load(mut self: Pin<&mut Self>, props: Props) -> Cmd<Self> {
         self.arena = props.arena;
+        self.world = props.world;
         self.chat = props.data;
         self.chat_user = props.user;
         self.client_id = props.client_id;
         self.game_system_class = props.game_system_class;
+        self.api_game_system = props.api_game_system;
+        self.dice_exchange = props.dice_exchange;
 
         Cmd::none()
     }
@@ -181,6 +277,8 @@ impl Update for RoomModelessChat {
             Msg::NoOp => Cmd::none(),
             Msg::SendInputingChatMessage => {
                 let message = self.shared_state.borrow_mut().inputing_message.take();
+                let reference = self.shared_state.borrow_mut().replying_message.take();
+                self.shared_state.borrow_mut().parse_error = None;
 
                 let sender = match &self.chat_user {
                     ChatUser::Player(player) => player.map(|player| {
@@ -213,12 +311,93 @@ impl Update for RoomModelessChat {
                     .unwrap_or(None);
 
                 if let Some((sender, channel)) = join_some!(sender, channel) {
-                    self.send_chat_message(sender, channel, &message)
+                    let params = block::chat_message::MacroParam::parse_all(&message);
+                    if !params.is_empty() {
+                        self.showing_modal = ShowingModal::ChatMacro(WaitingMacroChatMessage {
+                            channel: channel,
+                            text: message,
+                            params: Rc::new(params),
+                            properties: Rc::new(self.chat_user.property_names()),
+                            sender: sender,
+                            reference: reference,
+                        });
+                        return Cmd::none();
+                    }
+                    self.send_text(sender, channel, &message, reference)
                 } else {
                     Cmd::none()
                 }
             }
             Msg::SendWaitingChatMessage(captured) => self.send_waitng_chat_message(&captured),
+            Msg::SendMacroChatMessage(values) => self.send_macro_chat_message(&values),
+            Msg::EditChatMessage(channel, message, text) => {
+                self.edit_chat_message(channel, message, &text)
+            }
+            Msg::DeleteChatMessage(channel, message) => {
+                self.delete_chat_message(channel, message)
+            }
+            Msg::SetReplyingMessage(message) => {
+                self.shared_state.borrow_mut().replying_message = message;
+                Cmd::none()
+            }
+            Msg::PostApiRolledChatMessage {
+                sender,
+                channel,
+                message,
+                source,
+                reference,
+                edit_target,
+                mut command_results,
+                errors,
+            } => {
+                let (message, command_results) = block::chat_message::roll_with(
+                    |command| {
+                        let idx = command_results.iter().position(|(x, _)| x == command)?;
+                        Some(command_results.remove(idx).1)
+                    },
+                    message,
+                );
+                let command_results = command_results
+                    .into_iter()
+                    .map(|command_result| {
+                        let message = block::chat_message::Message::from(&command_result);
+                        (command_result, message, None)
+                    })
+                    .chain(
+                        self.roll_dice_table(&message.to_plain_text(), |sides| {
+                            (js_sys::Math::random() * sides as f64).floor() as u32 + 1
+                        })
+                        .map(|(command_result, message)| (command_result, message, None)),
+                    )
+                    .collect();
+                self.post_rolled_chat_message(
+                    sender,
+                    channel,
+                    message,
+                    source,
+                    command_results,
+                    errors,
+                    reference,
+                    edit_target,
+                )
+            }
+            Msg::PostExchangedChatMessage {
+                sender,
+                channel,
+                message,
+                source,
+                reference,
+                edit_target,
+                seed,
+            } => self.post_exchanged_chat_message(
+                sender,
+                channel,
+                message,
+                source,
+                reference,
+                edit_target,
+                seed,
+            ),
             Msg::SetSelectedChannelIdx(idx) => {
                 self.selected_channel_idx = idx;
                 Cmd::none()
@@ -300,8 +479,31 @@ impl Render<Html> for RoomModelessChat {
                                                 None,
                                                 channel::Props {
                                                     data: BlockMut::clone(&channel),
+                                                    client_id: Rc::clone(&self.client_id),
+                                                    dice_exchange: Rc::clone(&self.dice_exchange),
                                                 },
-                                                Sub::none(),
+                                                Sub::map({
+                                                    let channel = BlockMut::clone(&channel);
+                                                    move |sub| match sub {
+                                                        channel::On::EditMessage {
+                                                            message,
+                                                            text,
+                                                        } => Msg::EditChatMessage(
+                                                            BlockMut::clone(&channel),
+                                                            message,
+                                                            text,
+                                                        ),
+                                                        channel::On::DeleteMessage { message } => {
+                                                            Msg::DeleteChatMessage(
+                                                                BlockMut::clone(&channel),
+                                                                message,
+                                                            )
+                                                        }
+                                                        channel::On::ReplyMessage { message } => {
+                                                            Msg::SetReplyingMessage(Some(message))
+                                                        }
+                                                    }
+                                                }),
                                             ),
                                         )
                                     })
@@ -315,9 +517,12 @@ impl Render<Html> for RoomModelessChat {
                     None,
                     controller::Props {
                         shared_state: Rc::clone(&self.shared_state),
+                        chat_user: ChatUser::clone(&self.chat_user),
+                        game_system_class: Rc::clone(&self.game_system_class),
                     },
                     Sub::map(|sub| match sub {
                         controller::On::SendInputingChatMessage => Msg::SendInputingChatMessage,
+                        controller::On::CancelReplying => Msg::SetReplyingMessage(None),
                     }),
                 ),
                 match &self.showing_modal {
@@ -335,6 +540,20 @@ impl Render<Html> for RoomModelessChat {
                             modal_chat_capture::On::Send(x) => Msg::SendWaitingChatMessage(x),
                         }),
                     ),
+                    ShowingModal::ChatMacro(waiting_chat_message) => ModalChatMacro::empty(
+                        self,
+                        None,
+                        modal_chat_macro::Props {
+                            params: Rc::clone(&waiting_chat_message.params),
+                            properties: Rc::clone(&waiting_chat_message.properties),
+                        },
+                        Sub::map(|sub| match sub {
+                            modal_chat_macro::On::Cancel => {
+                                Msg::SetShowingModal(ShowingModal::None)
+                            }
+                            modal_chat_macro::On::Send(x) => Msg::SendMacroChatMessage(x),
+                        }),
+                    ),
                     ShowingModal::Chatpallet => {
                         if let ChatUser::Character(character) = &self.chat_user {
                             character
@@ -0,0 +1,373 @@
+use super::super::atom::{
+    attr,
+    btn::{self, Btn},
+    fa,
+    text::Text,
+};
+use crate::arena::{block, BlockMut};
+use crate::libs::random_id::U128Id;
+use isaribi::{
+    style,
+    styled::{Style, Styled},
+};
+use kagura::prelude::*;
+use nusa::prelude::*;
+
+pub struct Props {
+    pub data: BlockMut<block::ChatChannel>,
+}
+
+pub enum Msg {
+    Sub(On),
+    SetText(String),
+    SetSenderName(String),
+    SetSince(String),
+    SetUntil(String),
+    SetIsDiceOnly(bool),
+    ShowMoreResults,
+}
+
+pub enum On {
+    JumpToMessage(U128Id),
+}
+
+pub struct Search {
+    data: BlockMut<block::ChatChannel>,
+    filter: ChatFilter,
+    result_limit: usize,
+}
+
+const RESULT_PAGE_SIZE: usize = 100;
+
+pub struct ChatFilter {
+    text: String,
+    sender_name: String,
+    since: Option<chrono::NaiveDate>,
+    until: Option<chrono::NaiveDate>,
+    is_dice_only: bool,
+}
+
+impl ChatFilter {
+    fn new() -> Self {
+        Self {
+            text: String::new(),
+            sender_name: String::new(),
+            since: None,
+            until: None,
+            is_dice_only: false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_empty()
+            && self.sender_name.is_empty()
+            && self.since.is_none()
+            && self.until.is_none()
+            && !self.is_dice_only
+    }
+
+    fn is_match(&self, chat_message: &block::ChatMessage) -> bool {
+        if self.is_dice_only && chat_message.dice_roll().is_none() {
+            return false;
+        }
+
+        if !self.sender_name.is_empty()
+            && !chat_message.sender().name().contains(&self.sender_name)
+        {
+            return false;
+        }
+
+        let date = chat_message
+            .timestamp()
+            .with_timezone(&chrono::Local)
+            .naive_local()
+            .date();
+
+        if self.since.map(|since| date < since).unwrap_or(false) {
+            return false;
+        }
+
+        if self.until.map(|until| date > until).unwrap_or(false) {
+            return false;
+        }
+
+        self.text.is_empty() || chat_message.message().to_string().contains(&self.text)
+    }
+}
+
+fn parse_date(date: &String) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+impl Component for Search {
+    type Props = Props;
+    type Msg = Msg;
+    type Event = On;
+}
+
+impl HtmlComponent for Search {}
+
+impl Constructor for Search {
+    fn constructor(props: Self::Props) -> Self {
+        Self {
+            data: props.data,
+            filter: ChatFilter::new(),
+            result_limit: RESULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl Update for Search {
+    fn on_load(mut self: Pin<&mut Self>, props: Self::Props) -> Cmd<Self> {
+        self.data = props.data;
+        Cmd::none()
+    }
+
+    fn update(mut self: Pin<&mut Self>, msg: Self::Msg) -> Cmd<Self> {
+        if !matches!(msg, Msg::Sub(..) | Msg::ShowMoreResults) {
+            self.result_limit = RESULT_PAGE_SIZE;
+        }
+
+        match msg {
+            Msg::Sub(sub) => Cmd::submit(sub),
+            Msg::SetText(text) => {
+                self.filter.text = text;
+                Cmd::none()
+            }
+            Msg::SetSenderName(sender_name) => {
+                self.filter.sender_name = sender_name;
+                Cmd::none()
+            }
+            Msg::SetSince(since) => {
+                self.filter.since = parse_date(&since);
+                Cmd::none()
+            }
+            Msg::SetUntil(until) => {
+                self.filter.until = parse_date(&until);
+                Cmd::none()
+            }
+            Msg::SetIsDiceOnly(is_dice_only) => {
+                self.filter.is_dice_only = is_dice_only;
+                Cmd::none()
+            }
+            Msg::ShowMoreResults => {
+                self.result_limit += RESULT_PAGE_SIZE;
+                Cmd::none()
+            }
+        }
+    }
+}
+
+impl Render<Html> for Search {
+    type Children = ();
+    fn render(&self, _: Self::Children) -> Html {
+        Self::styled(Html::div(
+            Attributes::new().class(Self::class("base")),
+            Events::new(),
+            vec![self.render_form(), self.render_result()],
+        ))
+    }
+}
+
+impl Search {
+    fn render_form(&self) -> Html {
+        Html::div(
+            Attributes::new().class(Self::class("form")),
+            Events::new(),
+            vec![
+                Html::div(
+                    Attributes::new().class(Self::class("form-text")),
+                    Events::new(),
+                    vec![
+                        Html::input(
+                            Attributes::new()
+                                .value(&self.filter.text)
+                                .string("placeholder", "本文"),
+                            Events::new().on_input(self, |text| Msg::SetText(text)),
+                            vec![],
+                        ),
+                        Html::input(
+                            Attributes::new()
+                                .value(&self.filter.sender_name)
+                                .string("placeholder", "発言者"),
+                            Events::new()
+                                .on_input(self, |sender_name| Msg::SetSenderName(sender_name)),
+                            vec![],
+                        ),
+                        Btn::with_variant(
+                            if self.filter.is_dice_only {
+                                btn::Variant::Primary
+                            } else {
+                                btn::Variant::Secondary
+                            },
+                            Attributes::new().title("ダイスの結果のみ"),
+                            Events::new().on_click(self, {
+                                let is_dice_only = self.filter.is_dice_only;
+                                move |_| Msg::SetIsDiceOnly(!is_dice_only)
+                            }),
+                            vec![fa::fas_i("fa-dice")],
+                        ),
+                    ],
+                ),
+                Html::div(
+                    Attributes::new().class(Self::class("form-date")),
+                    Events::new(),
+                    vec![
+                        Html::input(
+                            Attributes::new().type_("date"),
+                            Events::new().on_input(self, |since| Msg::SetSince(since)),
+                            vec![],
+                        ),
+                        Text::span("〜"),
+                        Html::input(
+                            Attributes::new().type_("date"),
+                            Events::new().on_input(self, |until| Msg::SetUntil(until)),
+                            vec![],
+                        ),
+                    ],
+                ),
+            ],
+        )
+    }
+
+    fn render_result(&self) -> Html {
+        if self.filter.is_empty() {
+            return Html::none();
+        }
+
+        let mut items: Vec<Html> = self
+            .data
+            .map(|channel| {
+                channel
+                    .messages()
+                    .iter()
+                    .rev()
+                    .filter_map(|chat_message| {
+                        let block_id = chat_message.id();
+                        chat_message
+                            .map(|chat_message: &block::ChatMessage| {
+                                if self.filter.is_match(chat_message) {
+                                    Some(self.render_result_item(block_id, chat_message))
+                                } else {
+                                    None
+                                }
+                            })
+                            .unwrap_or(None)
+                    })
+                    .take(self.result_limit + 1)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if items.len() > self.result_limit {
+            items.truncate(self.result_limit);
+            items.push(Btn::secondary(
+                Attributes::new().class(Self::class("result-more")),
+                Events::new().on_click(self, |_| Msg::ShowMoreResults),
+                vec![Html::text("さらに表示")],
+            ));
+        }
+
+        Html::div(
+            Attributes::new().class(Self::class("result")),
+            Events::new(),
+            items,
+        )
+    }
+
+    fn render_result_item(&self, block_id: U128Id, chat_message: &block::ChatMessage) -> Html {
+        Html::div(
+            Attributes::new().class(Self::class("result-item")),
+            Events::new().on_click(self, move |_| Msg::Sub(On::JumpToMessage(block_id))),
+            vec![
+                attr::span(
+                    Attributes::new().class(Self::class("result-sender")),
+                    chat_message.sender().name(),
+                ),
+                attr::span(
+                    Attributes::new().class(Self::class("result-text")),
+                    chat_message
+                        .message()
+                        .to_string()
+                        .lines()
+                        .next()
+                        .unwrap_or(""),
+                ),
+                attr::span(
+                    Attributes::new().class(Self::class("result-timestamp")),
+                    chat_message
+                        .timestamp()
+                        .with_timezone(&chrono::Local)
+                        .format("%Y/%m/%d %H:%M")
+                        .to_string(),
+                ),
+            ],
+        )
+    }
+}
+
+impl Styled for Search {
+    fn style() -> Style {
+        style! {
+            ".base" {
+                "display": "grid";
+                "grid-template-columns": "1fr";
+                "row-gap": ".35rem";
+                "padding-bottom": ".35rem";
+            }
+
+            ".form" {
+                "display": "grid";
+                "grid-template-columns": "1fr";
+                "row-gap": ".35rem";
+            }
+
+            ".form-text" {
+                "display": "grid";
+                "grid-template-columns": "1fr 1fr max-content";
+                "column-gap": ".35rem";
+                "align-items": "center";
+            }
+
+            ".form-date" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content 1fr";
+                "column-gap": ".35rem";
+                "align-items": "center";
+            }
+
+            ".result" {
+                "max-height": "12rem";
+                "overflow-y": "scroll";
+                "border-bottom": format!(".1rem solid {}", crate::libs::color::Pallet::gray(6));
+            }
+
+            ".result-item" {
+                "display": "grid";
+                "grid-template-columns": "max-content 1fr max-content";
+                "column-gap": ".65rem";
+                "padding": ".15rem .35rem";
+                "cursor": "pointer";
+            }
+
+            ".result-item:hover" {
+                "background-color": format!("{}", crate::libs::color::Pallet::gray(2));
+            }
+
+            ".result-text" {
+                "overflow": "hidden";
+                "white-space": "nowrap";
+                "text-overflow": "ellipsis";
+            }
+
+            ".result-timestamp" {
+                "font-size": "0.9em";
+                "color": format!("{}", crate::libs::color::Pallet::gray(7));
+            }
+
+            ".result-more" {
+                "width": "100%";
+            }
+        }
+    }
+}
@@ -1,13 +1,105 @@
 use super::*;
 
 impl RoomModelessChat {
+    pub fn send_text(
+        mut self: Pin<&mut Self>,
+        mut sender: block::chat_message::Sender,
+        channel: BlockMut<block::ChatChannel>,
+        text: &String,
+        reference: Option<BlockRef<block::ChatMessage>>,
+    ) -> Cmd<Self> {
+        let expression = if let ChatUser::Character(character) = &self.chat_user {
+            character
+                .map(|character| character.find_expression(text))
+                .unwrap_or(None)
+        } else {
+            None
+        };
+
+        let source = text;
+        let mut text = text.clone();
+
+        if let Some(expressionless_text) = expression.as_ref().map(|(_, text)| text) {
+            if let Err(err) = block::chat_message::Message::from_str(expressionless_text) {
+                let mut shared_state = self.shared_state.borrow_mut();
+                shared_state.inputing_message = InputingMessage::Text(source.clone());
+                shared_state.replying_message = reference;
+                shared_state.parse_error = Some(err);
+                return Cmd::none();
+            }
+        }
+
+        if let ChatUser::Character(character) = &mut self.chat_user {
+            if let Some((tex_idx, expressionless_text)) = expression {
+                character.update(|character| {
+                    character.set_selected_texture_idx(tex_idx);
+                });
+                sender.set_icon(
+                    character
+                        .map(|character| {
+                            character
+                                .selected_texture()
+                                .and_then(|texture| texture.image().map(BlockRef::clone))
+                        })
+                        .unwrap_or(None),
+                );
+                text = expressionless_text;
+
+                if text.is_empty() {
+                    return Cmd::submit(On::UpdateBlocks {
+                        insert: set! {},
+                        update: set! { character.id() },
+                    });
+                }
+            }
+        }
+
+        if let ChatUser::Character(character) = &self.chat_user {
+            if let Some(cmds) = block::chat_message::PropertyCommand::parse_all(&text) {
+                let character = BlockMut::clone(character);
+                return self.send_property_commands(character, channel, cmds);
+            }
+        }
+        self.send_chat_message(sender, channel, &text, reference)
+    }
+
+    pub fn send_macro_chat_message(mut self: Pin<&mut Self>, values: &Vec<String>) -> Cmd<Self> {
+        let mut showing_modal = ShowingModal::None;
+        std::mem::swap(&mut self.showing_modal, &mut showing_modal);
+        if let ShowingModal::ChatMacro(WaitingMacroChatMessage {
+            channel,
+            text,
+            params,
+            sender,
+            reference,
+            ..
+        }) = showing_modal
+        {
+            let text = block::chat_message::MacroParam::substitute(&text, &params, values);
+            self.send_text(sender, channel, &text, reference)
+        } else {
+            Cmd::none()
+        }
+    }
+
     pub fn send_chat_message(
         mut self: Pin<&mut Self>,
         sender: block::chat_message::Sender,
-        mut channel: BlockMut<block::ChatChannel>,
+        channel: BlockMut<block::ChatChannel>,
         message: &String,
+        reference: Option<BlockRef<block::ChatMessage>>,
     ) -> Cmd<Self> {
-        let message = block::chat_message::Message::from_str(message);
+        let source = message.clone();
+        let message = match block::chat_message::Message::from_str(message) {
+            Ok(message) => message,
+            Err(err) => {
+                let mut shared_state = self.shared_state.borrow_mut();
+                shared_state.inputing_message = InputingMessage::Text(message.clone());
+                shared_state.replying_message = reference;
+                shared_state.parse_error = Some(err);
+                return Cmd::none();
+            }
+        };
         let (message, descriptions) = if let ChatUser::Character(character) = &self.chat_user {
             if let Some(res) = character.map(|character| {
                 block::chat_message::map(character.properties(), character.chat_ref(), message)
@@ -24,39 +116,217 @@ impl RoomModelessChat {
             self.showing_modal = ShowingModal::ChatCapture(WaitingChatMessage {
                 channel: channel,
                 message: message,
+                source: source,
                 descriptions: Rc::new(descriptions),
                 sender: sender,
+                reference: reference,
+                edit_target: None,
             });
             return Cmd::none();
         }
 
-        let command_results =
-            if let Some(game_system_class) = self.game_system_class.borrow().as_ref() {
-                block::chat_message::roll(game_system_class, &message)
-            } else {
-                crate::debug::log_1("no dicebot");
-                vec![]
-            };
+        self.post_chat_message(sender, channel, message, source, reference, None)
+    }
+
+    fn post_chat_message(
+        &mut self,
+        sender: block::chat_message::Sender,
+        channel: BlockMut<block::ChatChannel>,
+        message: block::chat_message::Message,
+        source: String,
+        reference: Option<BlockRef<block::ChatMessage>>,
+        edit_target: Option<BlockMut<block::ChatMessage>>,
+    ) -> Cmd<Self> {
+        let api_game_system = self.api_game_system.borrow().as_ref().map(Rc::clone);
+        if let Some(api_game_system) = api_game_system {
+            let mut commands = vec![];
+            block::chat_message::roll_with(
+                |command| {
+                    if api_game_system.is_command(command) {
+                        commands.push(String::from(command));
+                    }
+                    None
+                },
+                message.clone(),
+            );
 
+            if !commands.is_empty() {
+                return Cmd::task(async move {
+                    let mut command_results = vec![];
+                    let mut errors = vec![];
+                    for command in commands {
+                        match api_game_system.eval(&command).await {
+                            Ok(command_result) => command_results.push((command, command_result)),
+                            Err(error) => errors.push(error),
+                        }
+                    }
+                    Cmd::chain(Msg::PostApiRolledChatMessage {
+                        sender,
+                        channel,
+                        message,
+                        source,
+                        reference,
+                        edit_target,
+                        command_results,
+                        errors,
+                    })
+                });
+            }
+        }
+
+        // 振るコマンドがあるときだけ、出目の種を他のピアと交換してから振る
+        let (rolled_message, command_results) =
+            block::chat_message::roll(self.game_system_class.borrow().as_ref(), message.clone());
+        let has_dice_table = self
+            .find_dice_table(&rolled_message.to_plain_text())
+            .is_some();
+
+        if command_results.is_empty() && !has_dice_table {
+            return self.post_rolled_chat_message(
+                sender,
+                channel,
+                rolled_message,
+                source,
+                vec![],
+                vec![],
+                reference,
+                edit_target,
+            );
+        }
+
+        let dice_exchange = Rc::clone(&self.dice_exchange);
+        Cmd::task(async move {
+            let seed = dice_exchange.exchange().await;
+            Cmd::chain(Msg::PostExchangedChatMessage {
+                sender,
+                channel,
+                message,
+                source,
+                reference,
+                edit_target,
+                seed,
+            })
+        })
+    }
+
+    pub fn post_exchanged_chat_message(
+        &mut self,
+        sender: block::chat_message::Sender,
+        channel: BlockMut<block::ChatChannel>,
+        message: block::chat_message::Message,
+        source: String,
+        reference: Option<BlockRef<block::ChatMessage>>,
+        edit_target: Option<BlockMut<block::ChatMessage>>,
+        seed: Seed,
+    ) -> Cmd<Self> {
+        let new_proof = |index: usize, system: String, command: String| {
+            block::chat_message::DiceProof::new(
+                U128Id::clone(&seed.roll),
+                index as u32,
+                seed.seed.clone(),
+                seed.entropies.clone(),
+                system,
+                command,
+            )
+        };
+
+        let mut proofs = vec![];
+        let (message, command_results) = {
+            let game_system_class = self.game_system_class.borrow();
+            let system = block::chat_message::system_id(game_system_class.as_ref());
+            block::chat_message::roll_with(
+                |command| {
+                    let stream = seed.stream(proofs.len() as u32)?;
+                    let command_result = proof::with_stream(stream, || {
+                        block::chat_message::eval(game_system_class.as_ref(), command)
+                    })?;
+                    proofs.push(new_proof(
+                        proofs.len(),
+                        system.clone(),
+                        String::from(command),
+                    ));
+                    Some(command_result)
+                },
+                message,
+            )
+        };
+
+        let text = message.to_plain_text();
+        let dice_table_result = self.find_dice_table(&text).and_then(|name| {
+            let stream = seed.stream(proofs.len() as u32)?;
+            let (command_result, message) = proof::with_stream(stream, || {
+                self.roll_dice_table(&text, |sides| {
+                    (proof::random() * sides as f64).floor() as u32 + 1
+                })
+            })?;
+            let proof = new_proof(proofs.len(), String::from("table"), name);
+            Some((command_result, message, Some(proof)))
+        });
+
+        let command_results = command_results
+            .into_iter()
+            .zip(proofs.into_iter())
+            .map(|(command_result, proof)| {
+                let message = block::chat_message::Message::from(&command_result);
+                (command_result, message, Some(proof))
+            })
+            .chain(dice_table_result)
+            .collect();
+
+        self.post_rolled_chat_message(
+            sender,
+            channel,
+            message,
+            source,
+            command_results,
+            vec![],
+            reference,
+            edit_target,
+        )
+    }
+
+    pub fn post_rolled_chat_message(
+        &mut self,
+        sender: block::chat_message::Sender,
+        mut channel: BlockMut<block::ChatChannel>,
+        message: block::chat_message::Message,
+        source: String,
+        command_results: Vec<(
+            CommandResult,
+            block::chat_message::Message,
+            Option<block::chat_message::DiceProof>,
+        )>,
+        errors: Vec<String>,
+        reference: Option<BlockRef<block::ChatMessage>>,
+        edit_target: Option<BlockMut<block::ChatMessage>>,
+    ) -> Cmd<Self> {
         let mut message_ids = set! {};
+        let mut update = set! {};
         let now = chrono::Utc::now();
+        let roller = sender.clone();
 
-        let chat_message = block::ChatMessage::new(sender, now.clone(), message);
-        let chat_message = self.arena.insert(chat_message);
-        message_ids.insert(chat_message.id());
-        channel.update(|channel: &mut block::ChatChannel| {
-            channel.messages_push(chat_message);
-        });
+        if let Some(mut edit_target) = edit_target {
+            // 編集ではメッセージを差し替え、振り直したダイスの結果だけを新たに投稿する
+            edit_target.update(|chat_message: &mut block::ChatMessage| {
+                chat_message.set_message(message);
+                chat_message.set_source(source);
+            });
+            update.insert(edit_target.id());
+        } else {
+            if let ChatUser::Character(character) = &mut self.chat_user {
+                let speech = message.to_plain_text();
+                character.update(|character| {
+                    character.set_speech(&speech);
+                });
+                update.insert(character.id());
+            }
 
-        for command_result in command_results {
-            let message = block::chat_message::Message::from(&command_result);
-            let sender = block::chat_message::Sender::new(
-                Rc::clone(&self.client_id),
-                None,
-                String::from("Dicebot"),
-                block::chat_message::SenderKind::System,
-            );
-            let chat_message = block::ChatMessage::new(sender, now.clone(), message);
+            let mut chat_message = block::ChatMessage::new(sender, now.clone(), message);
+            chat_message.set_reference(reference);
+            chat_message.set_source(source);
+            if let ChatUser::Character(character) = &self.chat_user {
+                chat_message.set_character(Some(BlockMut::clone(character)));
+            }
             let chat_message = self.arena.insert(chat_message);
             message_ids.insert(chat_message.id());
             channel.update(|channel: &mut block::ChatChannel| {
@@ -64,10 +334,188 @@ impl RoomModelessChat {
             });
         }
 
-        let channel_id = channel.id();
+        let dicebot = block::chat_message::Sender::new(
+            Rc::clone(&self.client_id),
+            None,
+            String::from("Dicebot"),
+            block::chat_message::SenderKind::System,
+        );
+
+        for (command_result, message, proof) in command_results {
+            let mut chat_message = block::ChatMessage::new(dicebot.clone(), now.clone(), message);
+            let mut dice_roll =
+                block::chat_message::DiceRoll::from_command_result(roller.clone(), &command_result);
+            dice_roll.set_proof(proof);
+            chat_message.set_dice_roll(Some(dice_roll));
+            let chat_message = self.arena.insert(chat_message);
+            message_ids.insert(chat_message.id());
+            channel.update(|channel: &mut block::ChatChannel| {
+                channel.messages_push(chat_message);
+            });
+        }
+
+        for error in errors {
+            let message = block::chat_message::Message::from(vec![
+                block::chat_message::MessageToken::Command(block::chat_message::Command {
+                    name: block::chat_message::Message::from(vec![
+                        block::chat_message::MessageToken::Text(String::from("error")),
+                    ]),
+                    args: vec![],
+                    text: block::chat_message::Message::from(vec![
+                        block::chat_message::MessageToken::Text(error),
+                    ]),
+                }),
+            ]);
+            let chat_message = block::ChatMessage::new(dicebot.clone(), now.clone(), message);
+            let chat_message = self.arena.insert(chat_message);
+            message_ids.insert(chat_message.id());
+            channel.update(|channel: &mut block::ChatChannel| {
+                channel.messages_push(chat_message);
+            });
+        }
+
+        update.insert(channel.id());
         Cmd::submit(On::UpdateBlocks {
             insert: message_ids,
-            update: set! { channel_id },
+            update,
+        })
+    }
+
+    fn find_dice_table(&self, text: &str) -> Option<String> {
+        let name = text.split_whitespace().next()?;
+        self.world
+            .map(|world| {
+                world.dice_tables().iter().any(|dice_table| {
+                    dice_table
+                        .map(|dice_table| dice_table.name() == name)
+                        .unwrap_or(false)
+                })
+            })
+            .unwrap_or(false)
+            .then(|| String::from(name))
+    }
+
+    pub fn roll_dice_table(
+        &self,
+        text: &str,
+        mut rand: impl FnMut(u32) -> u32,
+    ) -> Option<(CommandResult, block::chat_message::Message)> {
+        let name = text.split_whitespace().next()?;
+        let (header, result) = self
+            .world
+            .map(|world| {
+                world.dice_tables().iter().find_map(|dice_table| {
+                    dice_table
+                        .map(|dice_table| {
+                            if dice_table.name() != name {
+                                return None;
+                            }
+                            let result = dice_table.roll(&mut rand)?;
+                            Some((dice_table.header(&result), result))
+                        })
+                        .unwrap_or(None)
+                })
+            })
+            .unwrap_or(None)?;
+
+        let text = result
+            .text
+            .map(|text| block::chat_message::Message::from_str_or_text(&text))
+            .unwrap_or_else(|| {
+                block::chat_message::Message::from(vec![block::chat_message::MessageToken::Text(
+                    String::from("該当なし"),
+                )])
+            });
+        let text = if let ChatUser::Character(character) = &self.chat_user {
+            character
+                .map(|character| {
+                    block::chat_message::map(
+                        character.properties(),
+                        character.chat_ref(),
+                        text.clone(),
+                    )
+                    .0
+                })
+                .unwrap_or(text)
+        } else {
+            text
+        };
+
+        let command_result = CommandResult {
+            text: format!("{}{}", header, text.to_plain_text()),
+            detailed_rands: result
+                .rands
+                .into_iter()
+                .map(|(sides, value)| Rand {
+                    kind: String::from("normal"),
+                    sides: sides as i32,
+                    value: value as i32,
+                })
+                .collect(),
+            secret: false,
+            success: false,
+            failure: false,
+            critical: false,
+            fumble: false,
+        };
+
+        let mut message = vec![block::chat_message::MessageToken::Text(header)];
+        message.extend(Into::<Vec<_>>::into(text));
+
+        Some((command_result, block::chat_message::Message::from(message)))
+    }
+
+    pub fn send_property_commands(
+        &mut self,
+        character: BlockMut<block::Character>,
+        mut channel: BlockMut<block::ChatChannel>,
+        cmds: Vec<block::chat_message::PropertyCommand>,
+    ) -> Cmd<Self> {
+        let (name, results) = unwrap!(character.map(|character| {
+            (
+                character.name().clone(),
+                cmds.iter()
+                    .map(|cmd| cmd.apply(character.properties()))
+                    .collect::<Vec<_>>(),
+            )
+        }); Cmd::none());
+
+        let mut property_ids = set! {};
+        let text = results
+            .into_iter()
+            .map(|result| match result {
+                Ok(result) => {
+                    property_ids.insert(result.property.id());
+                    format!("{}：{} → {}", result.name, result.before, result.after)
+                }
+                Err(err) => err,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let sender = block::chat_message::Sender::new(
+            Rc::clone(&self.client_id),
+            None,
+            name,
+            block::chat_message::SenderKind::System,
+        );
+        let message = block::chat_message::Message::from(vec![
+            block::chat_message::MessageToken::Text(text),
+        ]);
+        let chat_message = block::ChatMessage::new(sender, chrono::Utc::now(), message);
+        let chat_message = self.arena.insert(chat_message);
+        let chat_message_id = chat_message.id();
+        channel.update(|channel: &mut block::ChatChannel| {
+            channel.messages_push(chat_message);
+        });
+
+        let mut update = property_ids;
+        update.insert(character.id());
+        update.insert(channel.id());
+
+        Cmd::submit(On::UpdateBlocks {
+            insert: set! { chat_message_id },
+            update,
         })
     }
 
@@ -75,29 +523,145 @@ impl RoomModelessChat {
         let mut showing_modal = ShowingModal::None;
         std::mem::swap(&mut self.showing_modal, &mut showing_modal);
         if let ShowingModal::ChatCapture(WaitingChatMessage {
-            mut channel,
+            channel,
             message,
+            source,
             sender,
+            reference,
+            edit_target,
             ..
         }) = showing_modal
         {
             let message = Self::capture_message(&captured, message);
-            let chat_message = block::ChatMessage::new(sender, chrono::Utc::now(), message);
-            let chat_message = self.arena.insert(chat_message);
-            let chat_message_id = chat_message.id();
-            channel.update(|channel: &mut block::ChatChannel| {
-                channel.messages_push(chat_message);
-            });
-            let channel_id = channel.id();
-            Cmd::submit(On::UpdateBlocks {
-                insert: set! { chat_message_id },
-                update: set! { channel_id },
-            })
+            self.post_chat_message(sender, channel, message, source, reference, edit_target)
         } else {
             Cmd::none()
         }
     }
 
+    pub fn edit_chat_message(
+        &mut self,
+        channel: BlockMut<block::ChatChannel>,
+        chat_message: BlockMut<block::ChatMessage>,
+        text: &String,
+    ) -> Cmd<Self> {
+        let (sender, character) = unwrap!(chat_message.map(|chat_message| {
+            (
+                chat_message.sender().clone(),
+                chat_message.character().map(BlockMut::clone),
+            )
+        }); Cmd::none());
+
+        if *sender.client_id() != self.client_id {
+            return Cmd::none();
+        }
+
+        let message = match block::chat_message::Message::from_str(text) {
+            Ok(message) => message,
+            Err(err) => {
+                let mut shared_state = self.shared_state.borrow_mut();
+                shared_state.inputing_message = InputingMessage::Text(text.clone());
+                shared_state.parse_error = Some(err);
+                return Cmd::none();
+            }
+        };
+        let (message, descriptions) = if let Some(character) = &character {
+            if let Some(res) = character.map(|character| {
+                block::chat_message::map(character.properties(), character.chat_ref(), message)
+            }) {
+                res
+            } else {
+                return Cmd::none();
+            }
+        } else {
+            block::chat_message::map(&vec![], Self::ref_none(), message)
+        };
+
+        if descriptions.len() > 0 {
+            self.showing_modal = ShowingModal::ChatCapture(WaitingChatMessage {
+                channel: channel,
+                message: message,
+                source: text.clone(),
+                descriptions: Rc::new(descriptions),
+                sender: sender,
+                reference: None,
+                edit_target: Some(chat_message),
+            });
+            return Cmd::none();
+        }
+
+        self.post_chat_message(
+            sender,
+            channel,
+            message,
+            text.clone(),
+            None,
+            Some(chat_message),
+        )
+    }
+
+    pub fn delete_chat_message(
+        &mut self,
+        mut channel: BlockMut<block::ChatChannel>,
+        chat_message: BlockMut<block::ChatMessage>,
+    ) -> Cmd<Self> {
+        let is_own_message = chat_message
+            .map(|chat_message| *chat_message.sender().client_id() == self.client_id)
+            .unwrap_or(false);
+
+        if !is_own_message {
+            return Cmd::none();
+        }
+
+        let chat_message_id = chat_message.id();
+        channel.update(|channel: &mut block::ChatChannel| {
+            channel.remove_message(&chat_message_id);
+        });
+
+        // 削除したメッセージへの返信は参照を外す
+        let mut replies = self
+            .chat
+            .map(|chat| {
+                chat.channels()
+                    .iter()
+                    .filter_map(|channel| {
+                        channel.map(|channel| {
+                            channel
+                                .messages()
+                                .iter()
+                                .filter(|message| {
+                                    message
+                                        .map(|message| {
+                                            message
+                                                .reference()
+                                                .map(|reference| reference.id() == chat_message_id)
+                                                .unwrap_or(false)
+                                        })
+                                        .unwrap_or(false)
+                                })
+                                .map(BlockMut::clone)
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .flatten()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let mut update = set! { channel.id() };
+        for reply in &mut replies {
+            reply.update(|reply: &mut block::ChatMessage| {
+                reply.set_reference(None);
+            });
+            update.insert(reply.id());
+        }
+
+        Cmd::submit(On::UpdateBlocks {
+            insert: set! {},
+            update,
+        })
+    }
+
     fn ref_none<'a>() -> impl FnMut(&String) -> block::chat_message::Message + 'a {
         |_ref_name: &String| block::chat_message::Message::from(vec![])
     }
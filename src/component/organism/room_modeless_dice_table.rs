@@ -0,0 +1,281 @@
+use super::atom::{btn::Btn, common::Common, fa, text::Text};
+use super::organism::room_modeless::RoomModeless;
+use crate::arena::{block, ArenaMut, BlockMut};
+use crate::libs::random_id::U128Id;
+use isaribi::{
+    style,
+    styled::{Style, Styled},
+};
+use kagura::prelude::*;
+use nusa::prelude::*;
+use std::collections::HashSet;
+
+pub struct Props {
+    pub arena: ArenaMut,
+    pub world: BlockMut<block::World>,
+    pub data: BlockMut<block::DiceTable>,
+}
+
+pub enum Msg {
+    Sub(On),
+    SetName(String),
+    SetDice(String),
+    PushItem,
+    RemoveItem(usize),
+    SetItemMin(usize, String),
+    SetItemMax(usize, String),
+    SetItemText(usize, String),
+}
+
+pub enum On {
+    UpdateBlocks {
+        insert: HashSet<U128Id>,
+        update: HashSet<U128Id>,
+    },
+}
+
+pub struct RoomModelessDiceTable {
+    arena: ArenaMut,
+    world: BlockMut<block::World>,
+    dice_table: BlockMut<block::DiceTable>,
+    element_id: ElementId,
+}
+
+ElementId! {
+    input_dice_table_name
+}
+
+impl Component for RoomModelessDiceTable {
+    type Props = Props;
+    type Msg = Msg;
+    type Event = On;
+}
+
+impl HtmlComponent for RoomModelessDiceTable {}
+
+impl Constructor for RoomModelessDiceTable {
+    fn constructor(props: Props) -> Self {
+        Self {
+            arena: props.arena,
+            world: props.world,
+            dice_table: props.data,
+            element_id: ElementId::new(),
+        }
+    }
+}
+
+impl Update for RoomModelessDiceTable {
+    fn on_load(mut self: Pin<&mut Self>, props: Props) -> Cmd<Self> {
+        self.arena = props.arena;
+        self.world = props.world;
+        self.dice_table = props.data;
+        Cmd::none()
+    }
+
+    fn update(mut self: Pin<&mut Self>, msg: Msg) -> Cmd<Self> {
+        match msg {
+            Msg::Sub(sub) => Cmd::submit(sub),
+            Msg::SetName(name) => {
+                self.dice_table.update(|dice_table| {
+                    dice_table.set_name(name);
+                });
+                self.updated()
+            }
+            Msg::SetDice(dice) => {
+                self.dice_table.update(|dice_table| {
+                    dice_table.set_dice(dice);
+                });
+                self.updated()
+            }
+            Msg::PushItem => {
+                self.dice_table.update(|dice_table| {
+                    dice_table.push_item();
+                });
+                self.updated()
+            }
+            Msg::RemoveItem(idx) => {
+                self.dice_table.update(|dice_table| {
+                    dice_table.remove_item(idx);
+                });
+                self.updated()
+            }
+            Msg::SetItemMin(idx, min) => {
+                let min = unwrap!(min.parse().ok(); Cmd::none());
+                self.dice_table.update(|dice_table| {
+                    let max = dice_table.items().get(idx).map(|item| item.max());
+                    dice_table.set_item_range(idx, min, max.unwrap_or(min));
+                });
+                self.updated()
+            }
+            Msg::SetItemMax(idx, max) => {
+                let max = unwrap!(max.parse().ok(); Cmd::none());
+                self.dice_table.update(|dice_table| {
+                    let min = dice_table.items().get(idx).map(|item| item.min());
+                    dice_table.set_item_range(idx, min.unwrap_or(max), max);
+                });
+                self.updated()
+            }
+            Msg::SetItemText(idx, text) => {
+                self.dice_table.update(|dice_table| {
+                    dice_table.set_item_text(idx, text);
+                });
+                self.updated()
+            }
+        }
+    }
+}
+
+impl RoomModelessDiceTable {
+    fn updated(&self) -> Cmd<Self> {
+        Cmd::submit(On::UpdateBlocks {
+            insert: set! {},
+            update: set! { self.dice_table.id() },
+        })
+    }
+}
+
+impl Render<Html> for RoomModelessDiceTable {
+    type Children = ();
+    fn render(&self, _: Self::Children) -> Html {
+        Self::styled(Html::div(
+            Attributes::new()
+                .class(RoomModeless::class("common-base"))
+                .class("pure-form"),
+            Events::new(),
+            vec![
+                self.dice_table
+                    .map(|data| self.render_header(data))
+                    .unwrap_or(Common::none()),
+                self.dice_table
+                    .map(|data| self.render_main(data))
+                    .unwrap_or(Common::none()),
+            ],
+        ))
+    }
+}
+
+impl RoomModelessDiceTable {
+    fn render_header(&self, dice_table: &block::DiceTable) -> Html {
+        let dice_attrs = Attributes::new().value(dice_table.dice());
+        let dice_attrs = if block::DiceTable::is_valid_dice(dice_table.dice()) {
+            dice_attrs
+        } else {
+            dice_attrs.class(Self::class("dice-error"))
+        };
+
+        Html::div(
+            Attributes::new().class(RoomModeless::class("common-header")),
+            Events::new(),
+            vec![
+                Html::label(
+                    Attributes::new()
+                        .class(RoomModeless::class("common-label"))
+                        .string("for", &self.element_id.input_dice_table_name),
+                    Events::new(),
+                    vec![fa::fas_i("fa-table-list")],
+                ),
+                Html::input(
+                    Attributes::new()
+                        .id(&self.element_id.input_dice_table_name)
+                        .value(dice_table.name()),
+                    Events::new().on_input(self, |name| Msg::SetName(name)),
+                    vec![],
+                ),
+                Text::span("ダイス"),
+                Html::input(
+                    dice_attrs,
+                    Events::new().on_input(self, |dice| Msg::SetDice(dice)),
+                    vec![],
+                ),
+            ],
+        )
+    }
+
+    fn render_main(&self, dice_table: &block::DiceTable) -> Html {
+        Html::div(
+            Attributes::new().class(Self::class("main")),
+            Events::new(),
+            vec![
+                Html::div(
+                    Attributes::new().class(Self::class("items")),
+                    Events::new(),
+                    dice_table
+                        .items()
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, item)| self.render_item(idx, item))
+                        .flatten()
+                        .collect(),
+                ),
+                Btn::secondary(
+                    Attributes::new(),
+                    Events::new().on_click(self, |_| Msg::PushItem),
+                    vec![fa::fas_i("fa-plus"), Html::text(" 行を追加")],
+                ),
+                Text::span(format!(
+                    "チャットで「{}」と送信すると、この表を振ります。",
+                    dice_table.name()
+                )),
+            ],
+        )
+    }
+
+    fn render_item(&self, idx: usize, item: &block::dice_table::DiceTableItem) -> Vec<Html> {
+        vec![
+            Html::input(
+                Attributes::new()
+                    .type_("number")
+                    .value(item.min().to_string()),
+                Events::new().on_input(self, move |min| Msg::SetItemMin(idx, min)),
+                vec![],
+            ),
+            Text::span("～"),
+            Html::input(
+                Attributes::new()
+                    .type_("number")
+                    .value(item.max().to_string()),
+                Events::new().on_input(self, move |max| Msg::SetItemMax(idx, max)),
+                vec![],
+            ),
+            Html::input(
+                Attributes::new().value(item.text()),
+                Events::new().on_input(self, move |text| Msg::SetItemText(idx, text)),
+                vec![],
+            ),
+            Btn::danger(
+                Attributes::new(),
+                Events::new().on_click(self, move |_| Msg::RemoveItem(idx)),
+                vec![fa::fas_i("fa-xmark")],
+            ),
+        ]
+    }
+}
+
+impl Styled for RoomModelessDiceTable {
+    fn style() -> Style {
+        style! {
+            ".main" {
+                "display": "grid";
+                "grid-template-columns": "1fr";
+                "grid-auto-rows": "max-content";
+                "row-gap": ".65rem";
+                "padding-left": ".65rem";
+                "padding-right": ".65rem";
+                "overflow-y": "scroll";
+            }
+
+            ".items" {
+                "display": "grid";
+                "grid-template-columns": "5em max-content 5em 1fr max-content";
+                "align-items": "center";
+                "column-gap": ".35rem";
+                "row-gap": ".35rem";
+            }
+
+            ".dice-error" {
+                "border-color": crate::libs::color::Pallet::red(7);
+                "background-color": crate::libs::color::Pallet::red(0);
+            }
+        }
+    }
+}
@@ -0,0 +1,787 @@
+use super::atom::{
+    btn::{self, Btn},
+    common::Common,
+    dropdown::{self, Dropdown},
+    fa,
+    text::Text,
+};
+use super::organism::room_modeless::RoomModeless;
+use crate::arena::{block, ArenaMut, BlockMut, BlockRef};
+use crate::dicebot::{
+    exchange::{DiceExchange, Seed},
+    proof,
+};
+use crate::libs::bcdice::api;
+use crate::libs::bcdice::js::{CommandResult, GameSystemClass};
+use crate::libs::random_id::U128Id;
+use block::condition::DurationUnit;
+use isaribi::{
+    style,
+    styled::{Style, Styled},
+};
+use kagura::prelude::*;
+use nusa::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+pub struct Props {
+    pub arena: ArenaMut,
+    pub world: BlockMut<block::World>,
+    pub chat: BlockMut<block::Chat>,
+    pub client_id: Rc<String>,
+    pub data: BlockMut<block::Initiative>,
+    pub game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
+    pub api_game_system: Rc<RefCell<Option<Rc<api::GameSystemClass>>>>,
+    pub dice_exchange: Rc<DiceExchange>,
+}
+
+pub enum Msg {
+    Sub(On),
+    SetSortKey(String),
+    SetDice(String),
+    SetValue(usize, String),
+    RollValue(usize),
+    RollAll,
+    RollExchangedValues(Vec<U128Id>, Seed),
+    PostRolledValues(
+        Vec<(
+            U128Id,
+            Result<CommandResult, String>,
+            Option<block::chat_message::DiceProof>,
+        )>,
+    ),
+    PushCharacter(BlockMut<block::Character>),
+    PushAllCharacters,
+    RemoveEntry(usize),
+    Next,
+    Prev,
+    Reset,
+}
+
+pub enum On {
+    UpdateBlocks {
+        insert: HashSet<U128Id>,
+        update: HashSet<U128Id>,
+    },
+}
+
+pub struct RoomModelessInitiative {
+    arena: ArenaMut,
+    world: BlockMut<block::World>,
+    chat: BlockMut<block::Chat>,
+    client_id: Rc<String>,
+    initiative: BlockMut<block::Initiative>,
+    game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
+    api_game_system: Rc<RefCell<Option<Rc<api::GameSystemClass>>>>,
+    dice_exchange: Rc<DiceExchange>,
+    element_id: ElementId,
+}
+
+ElementId! {
+    input_sort_key
+}
+
+impl Component for RoomModelessInitiative {
+    type Props = Props;
+    type Msg = Msg;
+    type Event = On;
+}
+
+impl HtmlComponent for RoomModelessInitiative {}
+
+impl Constructor for RoomModelessInitiative {
+    fn constructor(props: Props) -> Self {
+        Self {
+            arena: props.arena,
+            world: props.world,
+            chat: props.chat,
+            client_id: props.client_id,
+            initiative: props.data,
+            game_system_class: props.game_system_class,
+            api_game_system: props.api_game_system,
+            dice_exchange: props.dice_exchange,
+            element_id: ElementId::new(),
+        }
+    }
+}
+
+impl Update for RoomModelessInitiative {
+    fn on_load(mut self: Pin<&mut Self>, props: Props) -> Cmd<Self> {
+        self.arena = props.arena;
+        self.world = props.world;
+        self.chat = props.chat;
+        self.client_id = props.client_id;
+        self.initiative = props.data;
+        self.game_system_class = props.game_system_class;
+        self.api_game_system = props.api_game_system;
+        self.dice_exchange = props.dice_exchange;
+        Cmd::none()
+    }
+
+    fn update(mut self: Pin<&mut Self>, msg: Msg) -> Cmd<Self> {
+        match msg {
+            Msg::Sub(sub) => Cmd::submit(sub),
+            Msg::SetSortKey(sort_key) => {
+                let sort_key = Some(sort_key).filter(|sort_key| !sort_key.is_empty());
+                self.initiative.update(|initiative| {
+                    initiative.set_sort_key(sort_key);
+                });
+                self.updated(set! {}, set! {})
+            }
+            Msg::SetDice(dice) => {
+                self.initiative.update(|initiative| {
+                    initiative.set_dice(dice);
+                });
+                self.updated(set! {}, set! {})
+            }
+            Msg::SetValue(idx, value) => {
+                let value = unwrap!(value.parse().ok(); Cmd::none());
+                self.initiative.update(|initiative| {
+                    initiative.set_value(idx, value);
+                });
+                self.updated(set! {}, set! {})
+            }
+            Msg::RollValue(idx) => self.roll(vec![idx]),
+            Msg::RollAll => {
+                let idxs = self
+                    .initiative
+                    .map(|initiative| (0..initiative.entries().len()).collect())
+                    .unwrap_or_default();
+                self.roll(idxs)
+            }
+            Msg::RollExchangedValues(character_ids, seed) => {
+                self.roll_exchanged_values(character_ids, seed)
+            }
+            Msg::PostRolledValues(results) => self.post_rolled_values(results),
+            Msg::PushCharacter(character) => {
+                self.initiative.update(|initiative| {
+                    initiative.push_character(character);
+                });
+                self.updated(set! {}, set! {})
+            }
+            Msg::PushAllCharacters => {
+                let characters = self
+                    .world
+                    .map(|world| world.characters().clone())
+                    .unwrap_or_default();
+                self.initiative.update(|initiative| {
+                    for character in characters {
+                        initiative.push_character(character);
+                    }
+                });
+                self.updated(set! {}, set! {})
+            }
+            Msg::RemoveEntry(idx) => {
+                self.initiative.update(|initiative| {
+                    initiative.remove_entry(idx);
+                });
+                self.updated(set! {}, set! {})
+            }
+            Msg::Next => {
+                let (ended, round) = unwrap!(self.initiative.map(|initiative| {
+                    (
+                        initiative.current().map(|entry| entry.character().id()),
+                        initiative.round(),
+                    )
+                }); Cmd::none());
+                let mut is_reached = false;
+                self.initiative.update(|initiative| {
+                    is_reached = initiative.next();
+                });
+                let is_new_round = self
+                    .initiative
+                    .map(|initiative| initiative.round() > round)
+                    .unwrap_or(false);
+                // 戻った手番をもう一度進めるときは状態の経過を数えない
+                let (mut texts, update) = if is_reached {
+                    self.tick_conditions(ended, is_new_round)
+                } else {
+                    (vec![], set! {})
+                };
+                texts.extend(self.turn_message());
+                self.post_message(texts, update)
+            }
+            Msg::Prev => {
+                self.initiative.update(|initiative| {
+                    initiative.prev();
+                });
+                let text = self.turn_message();
+                self.post_message(text.into_iter().collect(), set! {})
+            }
+            Msg::Reset => {
+                self.initiative.update(|initiative| {
+                    initiative.reset();
+                });
+                let text = self.turn_message();
+                self.post_message(text.into_iter().collect(), set! {})
+            }
+        }
+    }
+}
+
+impl RoomModelessInitiative {
+    fn updated(&self, insert: HashSet<U128Id>, mut update: HashSet<U128Id>) -> Cmd<Self> {
+        update.insert(self.initiative.id());
+        Cmd::submit(On::UpdateBlocks { insert, update })
+    }
+
+    // チャットと同じダイスボットで振り、結果をダイスロールとして投稿する
+    fn roll(&self, idxs: Vec<usize>) -> Cmd<Self> {
+        let dice =
+            unwrap!(self.initiative.map(|initiative| initiative.dice().clone()); Cmd::none());
+        let character_ids = self
+            .initiative
+            .map(|initiative| {
+                idxs.into_iter()
+                    .filter_map(|idx| initiative.entries().get(idx))
+                    .map(|entry| entry.character().id())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        if character_ids.is_empty() {
+            return Cmd::none();
+        }
+
+        let api_game_system = self.api_game_system.borrow().as_ref().map(Rc::clone);
+        if let Some(api_game_system) = api_game_system.filter(|api| api.is_command(&dice)) {
+            return Cmd::task(async move {
+                let mut results = vec![];
+                for character_id in character_ids {
+                    results.push((character_id, api_game_system.eval(&dice).await, None));
+                }
+                Cmd::chain(Msg::PostRolledValues(results))
+            });
+        }
+
+        let dice_exchange = Rc::clone(&self.dice_exchange);
+        Cmd::task(async move {
+            let seed = dice_exchange.exchange().await;
+            Cmd::chain(Msg::RollExchangedValues(character_ids, seed))
+        })
+    }
+
+    // キャラクターごとに交換した種から別々の乱数列を作って振る
+    fn roll_exchanged_values(&self, character_ids: Vec<U128Id>, seed: Seed) -> Cmd<Self> {
+        let dice =
+            unwrap!(self.initiative.map(|initiative| initiative.dice().clone()); Cmd::none());
+        let game_system_class = self.game_system_class.borrow();
+        let system = block::chat_message::system_id(game_system_class.as_ref());
+
+        let results = character_ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, character_id)| {
+                let result = seed
+                    .stream(index as u32)
+                    .and_then(|stream| {
+                        proof::with_stream(stream, || {
+                            block::chat_message::eval(game_system_class.as_ref(), &dice)
+                        })
+                    })
+                    .ok_or_else(|| format!("{}：ダイスを振れません", dice));
+                let proof = result.as_ref().ok().map(|_| {
+                    block::chat_message::DiceProof::new(
+                        U128Id::clone(&seed.roll),
+                        index as u32,
+                        seed.seed.clone(),
+                        seed.entropies.clone(),
+                        system.clone(),
+                        dice.clone(),
+                    )
+                });
+                (character_id, result, proof)
+            })
+            .collect();
+
+        Cmd::chain(Msg::PostRolledValues(results))
+    }
+
+    fn post_rolled_values(
+        &mut self,
+        results: Vec<(
+            U128Id,
+            Result<CommandResult, String>,
+            Option<block::chat_message::DiceProof>,
+        )>,
+    ) -> Cmd<Self> {
+        let mut channel = unwrap!(
+            self.chat
+                .map(|chat| chat.channels().first().map(BlockMut::clone))
+                .unwrap_or(None);
+            Cmd::none()
+        );
+
+        let dice =
+            unwrap!(self.initiative.map(|initiative| initiative.dice().clone()); Cmd::none());
+        let dicebot = block::chat_message::Sender::new(
+            Rc::clone(&self.client_id),
+            None,
+            String::from("Dicebot"),
+            block::chat_message::SenderKind::System,
+        );
+        let now = chrono::Utc::now();
+        let mut insert = set! {};
+        let mut messages = vec![];
+
+        for (character_id, result, proof) in results {
+            let character = self
+                .initiative
+                .map(|initiative| {
+                    initiative
+                        .entries()
+                        .iter()
+                        .find(|entry| entry.character().id() == character_id)
+                        .map(|entry| BlockMut::clone(entry.character()))
+                })
+                .unwrap_or(None);
+            let roller = character.as_ref().and_then(|character| {
+                character.map(|character| {
+                    block::chat_message::Sender::new(
+                        Rc::clone(&self.client_id),
+                        character.selected_texture().and_then(|texture| {
+                            texture.image().map(|image| BlockRef::clone(&image))
+                        }),
+                        character.name().clone(),
+                        block::chat_message::SenderKind::Normal,
+                    )
+                })
+            });
+            let (character, roller) = if let (Some(character), Some(roller)) = (character, roller) {
+                (character, roller)
+            } else {
+                continue;
+            };
+
+            let mut chat_message = block::ChatMessage::new(
+                roller.clone(),
+                now.clone(),
+                block::chat_message::Message::from(vec![block::chat_message::MessageToken::Text(
+                    format!("{} イニシアチブ", dice),
+                )]),
+            );
+            chat_message.set_character(Some(BlockMut::clone(&character)));
+            messages.push(chat_message);
+
+            match result {
+                Ok(command_result) => {
+                    let value = command_result
+                        .text
+                        .rsplit(" ＞ ")
+                        .next()
+                        .and_then(|value| value.trim().parse().ok());
+                    if let Some(value) = value {
+                        self.initiative.update(|initiative| {
+                            let idx = initiative
+                                .entries()
+                                .iter()
+                                .position(|entry| entry.character().id() == character_id);
+                            if let Some(idx) = idx {
+                                initiative.set_value(idx, value);
+                            }
+                        });
+                    }
+
+                    let mut chat_message = block::ChatMessage::new(
+                        dicebot.clone(),
+                        now.clone(),
+                        block::chat_message::Message::from(&command_result),
+                    );
+                    let mut dice_roll =
+                        block::chat_message::DiceRoll::from_command_result(roller, &command_result);
+                    dice_roll.set_proof(proof);
+                    chat_message.set_dice_roll(Some(dice_roll));
+                    messages.push(chat_message);
+                }
+                Err(error) => {
+                    let message = block::chat_message::Message::from(vec![
+                        block::chat_message::MessageToken::Command(block::chat_message::Command {
+                            name: block::chat_message::Message::from(vec![
+                                block::chat_message::MessageToken::Text(String::from("error")),
+                            ]),
+                            args: vec![],
+                            text: block::chat_message::Message::from(vec![
+                                block::chat_message::MessageToken::Text(error),
+                            ]),
+                        }),
+                    ]);
+                    messages.push(block::ChatMessage::new(
+                        dicebot.clone(),
+                        now.clone(),
+                        message,
+                    ));
+                }
+            }
+        }
+
+        for chat_message in messages {
+            let chat_message = self.arena.insert(chat_message);
+            insert.insert(chat_message.id());
+            channel.update(|channel: &mut block::ChatChannel| {
+                channel.messages_push(chat_message);
+            });
+        }
+
+        self.updated(insert, set! { channel.id() })
+    }
+
+    fn turn_message(&self) -> Option<String> {
+        self.initiative
+            .map(|initiative| {
+                initiative
+                    .current()
+                    .and_then(|entry| entry.character().map(|c| c.name().clone()))
+                    .map(|name| format!("ラウンド{}：{}の手番です。", initiative.round(), name))
+            })
+            .unwrap_or(None)
+    }
+
+    fn tick_conditions(
+        &mut self,
+        ended: Option<U128Id>,
+        is_new_round: bool,
+    ) -> (Vec<String>, HashSet<U128Id>) {
+        let conditions = self
+            .world
+            .map(|world| world.conditions().clone())
+            .unwrap_or_default();
+        let mut texts = vec![];
+        let mut update = set! {};
+        let mut expired = vec![];
+
+        for mut condition in conditions {
+            let condition_id = condition.id();
+            condition.update(|condition| {
+                let is_target = condition.duration().is_some()
+                    && match condition.duration_unit() {
+                        DurationUnit::Round => is_new_round,
+                        DurationUnit::Turn => ended.as_ref() == Some(&condition.character().id()),
+                    };
+                if !is_target {
+                    return;
+                }
+                if condition.tick() {
+                    let name = condition
+                        .character()
+                        .map(|character| character.name().clone())
+                        .unwrap_or_default();
+                    texts.push(format!(
+                        "{}の「{}」が終了しました。",
+                        name,
+                        condition.name()
+                    ));
+                    expired.push(U128Id::clone(&condition_id));
+                }
+                update.insert(U128Id::clone(&condition_id));
+            });
+        }
+
+        if !expired.is_empty() {
+            self.world.update(|world| {
+                for condition_id in &expired {
+                    world.remove_condition(condition_id);
+                }
+            });
+            update.insert(self.world.id());
+        }
+
+        (texts, update)
+    }
+
+    fn post_message(&mut self, texts: Vec<String>, update: HashSet<U128Id>) -> Cmd<Self> {
+        let mut channel = unwrap!(
+            self.chat
+                .map(|chat| chat.channels().first().map(BlockMut::clone))
+                .unwrap_or(None);
+            self.updated(set! {}, update)
+        );
+
+        if texts.is_empty() {
+            return self.updated(set! {}, update);
+        }
+
+        let sender = block::chat_message::Sender::new(
+            Rc::clone(&self.client_id),
+            None,
+            String::from("イニシアチブ"),
+            block::chat_message::SenderKind::System,
+        );
+        let message =
+            block::chat_message::Message::from(vec![block::chat_message::MessageToken::Text(
+                texts.join("\n"),
+            )]);
+        let chat_message = block::ChatMessage::new(sender, chrono::Utc::now(), message);
+        let chat_message = self.arena.insert(chat_message);
+        let chat_message_id = chat_message.id();
+        channel.update(|channel: &mut block::ChatChannel| {
+            channel.messages_push(chat_message);
+        });
+
+        let mut update = update;
+        update.insert(channel.id());
+        self.updated(set! { chat_message_id }, update)
+    }
+}
+
+impl Render<Html> for RoomModelessInitiative {
+    type Children = ();
+    fn render(&self, _: Self::Children) -> Html {
+        Self::styled(Html::div(
+            Attributes::new()
+                .class(RoomModeless::class("common-base"))
+                .class("pure-form"),
+            Events::new(),
+            vec![
+                self.initiative
+                    .map(|data| self.render_header(data))
+                    .unwrap_or(Common::none()),
+                self.initiative
+                    .map(|data| self.render_main(data))
+                    .unwrap_or(Common::none()),
+            ],
+        ))
+    }
+}
+
+impl RoomModelessInitiative {
+    fn render_header(&self, initiative: &block::Initiative) -> Html {
+        Html::div(
+            Attributes::new().class(RoomModeless::class("common-header")),
+            Events::new(),
+            vec![
+                Html::label(
+                    Attributes::new()
+                        .class(RoomModeless::class("common-label"))
+                        .string("for", &self.element_id.input_sort_key),
+                    Events::new(),
+                    vec![Html::text("並び順")],
+                ),
+                Html::input(
+                    Attributes::new()
+                        .id(&self.element_id.input_sort_key)
+                        .string("placeholder", "パラメータ名（空欄のときは値を直接入力）")
+                        .value(initiative.sort_key().map(String::clone).unwrap_or_default()),
+                    Events::new().on_input(self, |sort_key| Msg::SetSortKey(sort_key)),
+                    vec![],
+                ),
+                Text::span("ダイス"),
+                Html::div(
+                    Attributes::new().class(Self::class("dice")),
+                    Events::new(),
+                    vec![
+                        Html::input(
+                            Attributes::new().value(initiative.dice()),
+                            Events::new().on_input(self, |dice| Msg::SetDice(dice)),
+                            vec![],
+                        ),
+                        Btn::secondary(
+                            Attributes::new(),
+                            Events::new().on_click(self, |_| Msg::RollAll),
+                            vec![fa::fas_i("fa-dice"), Html::text(" 全員振る")],
+                        ),
+                    ],
+                ),
+            ],
+        )
+    }
+
+    fn render_main(&self, initiative: &block::Initiative) -> Html {
+        Html::div(
+            Attributes::new().class(Self::class("main")),
+            Events::new(),
+            vec![
+                Html::div(
+                    Attributes::new().class(Self::class("controller")),
+                    Events::new(),
+                    vec![
+                        Btn::secondary(
+                            Attributes::new(),
+                            Events::new().on_click(self, |_| Msg::Prev),
+                            vec![fa::fas_i("fa-backward-step")],
+                        ),
+                        Html::span(
+                            Attributes::new().class(Self::class("round")),
+                            Events::new(),
+                            vec![Html::text(format!("ラウンド {}", initiative.round()))],
+                        ),
+                        Btn::primary(
+                            Attributes::new(),
+                            Events::new().on_click(self, |_| Msg::Next),
+                            vec![fa::fas_i("fa-forward-step")],
+                        ),
+                        Btn::secondary(
+                            Attributes::new(),
+                            Events::new().on_click(self, |_| Msg::Reset),
+                            vec![fa::fas_i("fa-rotate-left")],
+                        ),
+                    ],
+                ),
+                Html::div(
+                    Attributes::new().class(Self::class("entries")),
+                    Events::new(),
+                    initiative
+                        .entries()
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, entry)| {
+                            self.render_entry(
+                                idx,
+                                entry,
+                                idx == initiative.turn(),
+                                initiative.sort_key().is_some(),
+                            )
+                        })
+                        .collect(),
+                ),
+                self.render_add_character(initiative),
+            ],
+        )
+    }
+
+    fn render_entry(
+        &self,
+        idx: usize,
+        entry: &block::initiative::InitiativeEntry,
+        is_current: bool,
+        is_sorted_by_property: bool,
+    ) -> Html {
+        let name = unwrap!(entry.character().map(|c| c.name().clone()); Html::none());
+        let attrs = if is_current {
+            Attributes::new()
+                .class(Self::class("entry"))
+                .class(Self::class("entry--current"))
+        } else {
+            Attributes::new().class(Self::class("entry"))
+        };
+
+        Html::div(
+            attrs,
+            Events::new(),
+            vec![
+                if is_current {
+                    fa::fas_i("fa-caret-right")
+                } else {
+                    Html::span(Attributes::new(), Events::new(), vec![])
+                },
+                Text::span(name),
+                Html::input(
+                    Attributes::new()
+                        .type_("number")
+                        .flag("readonly", is_sorted_by_property)
+                        .value(entry.value().to_string()),
+                    Events::new().on_input(self, move |value| Msg::SetValue(idx, value)),
+                    vec![],
+                ),
+                Btn::secondary(
+                    Attributes::new().flag("disabled", is_sorted_by_property),
+                    Events::new().on_click(self, move |_| Msg::RollValue(idx)),
+                    vec![fa::fas_i("fa-dice")],
+                ),
+                Btn::danger(
+                    Attributes::new(),
+                    Events::new().on_click(self, move |_| Msg::RemoveEntry(idx)),
+                    vec![fa::fas_i("fa-xmark")],
+                ),
+            ],
+        )
+    }
+
+    fn render_add_character(&self, initiative: &block::Initiative) -> Html {
+        let characters = self
+            .world
+            .map(|world| {
+                world
+                    .characters()
+                    .iter()
+                    .filter(|character| !initiative.contains(&character.id()))
+                    .map(BlockMut::clone)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        Dropdown::new(
+            self,
+            None,
+            dropdown::Props {
+                direction: dropdown::Direction::Bottom,
+                toggle_type: dropdown::ToggleType::Click,
+                variant: btn::Variant::Secondary,
+            },
+            Sub::none(),
+            (
+                vec![fa::fas_i("fa-plus"), Html::text(" キャラクターを追加")],
+                characters
+                    .into_iter()
+                    .filter_map(|character| {
+                        let name = character.map(|c| c.name().clone())?;
+                        Some(Btn::menu(
+                            Attributes::new(),
+                            Events::new().on_click(self, move |_| Msg::PushCharacter(character)),
+                            vec![Html::text(name)],
+                        ))
+                    })
+                    .chain(vec![Btn::menu(
+                        Attributes::new(),
+                        Events::new().on_click(self, |_| Msg::PushAllCharacters),
+                        vec![Html::text("全員を追加")],
+                    )])
+                    .collect(),
+            ),
+        )
+    }
+}
+
+impl Styled for RoomModelessInitiative {
+    fn style() -> Style {
+        style! {
+            ".main" {
+                "display": "grid";
+                "grid-template-columns": "1fr";
+                "grid-auto-rows": "max-content";
+                "row-gap": ".65rem";
+                "padding-left": ".65rem";
+                "padding-right": ".65rem";
+                "overflow-y": "scroll";
+            }
+
+            ".dice" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content";
+                "column-gap": ".35rem";
+            }
+
+            ".controller" {
+                "display": "grid";
+                "grid-template-columns": "max-content 1fr max-content max-content";
+                "align-items": "center";
+                "column-gap": ".35rem";
+            }
+
+            ".round" {
+                "text-align": "center";
+                "font-weight": "bold";
+            }
+
+            ".entries" {
+                "display": "grid";
+                "grid-template-columns": "1fr";
+                "row-gap": ".35rem";
+            }
+
+            ".entry" {
+                "display": "grid";
+                "grid-template-columns": "1em 1fr 6em max-content max-content";
+                "align-items": "center";
+                "column-gap": ".35rem";
+                "padding": ".15rem .35rem";
+                "border-radius": "2px";
+            }
+
+            ".entry--current" {
+                "background-color": crate::libs::color::Pallet::yellow(1);
+                "font-weight": "bold";
+            }
+        }
+    }
+}
@@ -22,6 +22,8 @@ use kagura::prelude::*;
 use nusa::prelude::*;
 use std::collections::HashSet;
 use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
 
 pub struct Props {
     pub arena: ArenaMut,
@@ -34,6 +36,11 @@ pub enum Msg {
     SetSetectedToolIdx(usize),
     SetTool(usize, TableTool),
     SetShowingModal(ShowingModal),
+    LoadTemplate(usize, web_sys::File),
+    AddTemplate(usize, block::character::Template),
+    SetTemplateError(Option<String>),
+    ExportTemplateAsToml(Rc<block::character::Template>),
+    ExportTemplateAsJson(Rc<block::character::Template>),
 }
 
 pub enum On {
@@ -46,6 +53,8 @@ pub enum On {
 
 pub struct TableMenu {
     tools: SelectList<TableTool>,
+    templates: Vec<Rc<block::character::Template>>,
+    template_error: Option<String>,
     showing_modal: ShowingModal,
     arena: ArenaMut,
     world: BlockMut<block::World>,
@@ -82,6 +91,7 @@ impl Constructor for TableMenu {
                         tex_size: 1.5,
                         color: crate::libs::color::Pallet::gray(5),
                         texture: None,
+                        template: None,
                     })),
                     TableTool::Boxblock(Rc::new(table_tool::Boxblock {
                         color: crate::libs::color::Pallet::blue(5),
@@ -105,6 +115,11 @@ impl Constructor for TableMenu {
                 ],
                 0,
             ),
+            templates: block::character::template::presets()
+                .into_iter()
+                .map(Rc::new)
+                .collect(),
+            template_error: None,
             showing_modal: ShowingModal::None,
             arena: props.arena,
             world: props.world,
@@ -145,6 +160,51 @@ impl Update for TableMenu {
                 self.showing_modal = showing_modal;
                 Cmd::none()
             }
+            Msg::LoadTemplate(tool_idx, file) => Cmd::task(async move {
+                let text = JsFuture::from(file.text())
+                    .await
+                    .ok()
+                    .and_then(|text| text.as_string());
+                let text = unwrap!(text; Cmd::chain(Msg::SetTemplateError(Some(format!(
+                    "{}を読み込めません",
+                    file.name()
+                )))));
+                match block::character::Template::parse(&text) {
+                    Ok(template) => Cmd::chain(Msg::AddTemplate(tool_idx, template)),
+                    Err(err) => Cmd::chain(Msg::SetTemplateError(Some(format!(
+                        "{}はテンプレートとして不正です：{}",
+                        file.name(),
+                        err
+                    )))),
+                }
+            }),
+            Msg::SetTemplateError(template_error) => {
+                self.template_error = template_error;
+                Cmd::none()
+            }
+            Msg::AddTemplate(tool_idx, template) => {
+                self.template_error = None;
+                let template = Rc::new(template);
+                self.templates.push(Rc::clone(&template));
+                if let Some(TableTool::Character(character)) = self.tools.get(tool_idx) {
+                    let mut character = character.as_ref().clone();
+                    character.template = Some(template);
+                    Cmd::chain(Msg::SetTool(
+                        tool_idx,
+                        TableTool::Character(Rc::new(character)),
+                    ))
+                } else {
+                    Cmd::none()
+                }
+            }
+            Msg::ExportTemplateAsToml(template) => {
+                template.download_as_toml();
+                Cmd::none()
+            }
+            Msg::ExportTemplateAsJson(template) => {
+                template.download_as_json();
+                Cmd::none()
+            }
         }
     }
 }
@@ -605,11 +665,145 @@ impl TableMenu {
                             )
                         }
                     },
+                    Text::span("テンプレート"),
+                    self.render_tool_option_character_template(tool_idx, character),
                 ],
             )],
         )
     }
 
+    fn render_tool_option_character_template(
+        &self,
+        tool_idx: usize,
+        character: &Rc<table_tool::Character>,
+    ) -> Html {
+        Html::div(
+            Attributes::new().class(Self::class("template")),
+            Events::new(),
+            vec![
+                Dropdown::new(
+                    self,
+                    None,
+                    dropdown::Props {
+                        direction: dropdown::Direction::Bottom,
+                        toggle_type: dropdown::ToggleType::Click,
+                        variant: btn::Variant::DarkLikeMenu,
+                    },
+                    Sub::none(),
+                    (
+                        vec![Html::text(
+                            character
+                                .template
+                                .as_ref()
+                                .map(|template| template.name.as_str())
+                                .unwrap_or("なし"),
+                        )],
+                        vec![None]
+                            .into_iter()
+                            .chain(self.templates.iter().map(Some))
+                            .map(|template| {
+                                self.render_tool_option_character_template_item(
+                                    tool_idx, character, template,
+                                )
+                            })
+                            .collect(),
+                    ),
+                ),
+                Html::label(
+                    Attributes::new().class(Self::class("template-import")),
+                    Events::new(),
+                    vec![
+                        fa::fas_i("fa-file-import"),
+                        Html::text("読込"),
+                        Html::input(
+                            Attributes::new()
+                                .type_("file")
+                                .string("accept", ".toml,.json"),
+                            Events::new().on("change", self, move |e| {
+                                let target = unwrap!(e.target(); Msg::NoOp);
+                                let input = unwrap!(
+                                    target.dyn_into::<web_sys::HtmlInputElement>().ok();
+                                    Msg::NoOp
+                                );
+                                let files = unwrap!(input.files(); Msg::NoOp);
+                                let file = unwrap!(files.get(0); Msg::NoOp);
+                                input.set_value("");
+                                Msg::LoadTemplate(tool_idx, file)
+                            }),
+                            vec![],
+                        ),
+                    ],
+                ),
+                if let Some(template) = character.template.as_ref() {
+                    Btn::group(
+                        Attributes::new(),
+                        Events::new(),
+                        vec![
+                            Btn::secondary(
+                                Attributes::new(),
+                                Events::new().on_click(self, {
+                                    let template = Rc::clone(template);
+                                    move |_| Msg::ExportTemplateAsToml(template)
+                                }),
+                                vec![Html::text("TOML")],
+                            ),
+                            Btn::secondary(
+                                Attributes::new(),
+                                Events::new().on_click(self, {
+                                    let template = Rc::clone(template);
+                                    move |_| Msg::ExportTemplateAsJson(template)
+                                }),
+                                vec![Html::text("JSON")],
+                            ),
+                        ],
+                    )
+                } else {
+                    Common::none()
+                },
+                self.template_error
+                    .as_ref()
+                    .map(|template_error| {
+                        Html::span(
+                            Attributes::new().class(Self::class("template-error")),
+                            Events::new(),
+                            vec![Html::text(template_error)],
+                        )
+                    })
+                    .unwrap_or(Common::none()),
+            ],
+        )
+    }
+
+    fn render_tool_option_character_template_item(
+        &self,
+        tool_idx: usize,
+        character: &Rc<table_tool::Character>,
+        template: Option<&Rc<block::character::Template>>,
+    ) -> Html {
+        let text = template
+            .map(|template| {
+                if template.system.is_empty() {
+                    template.name.clone()
+                } else {
+                    format!("{}（{}）", template.name, template.system)
+                }
+            })
+            .unwrap_or_else(|| String::from("なし"));
+        Btn::menu(
+            Attributes::new(),
+            Events::new().on_click(self, {
+                let character = Rc::clone(&character);
+                let template = template.map(Rc::clone);
+                move |_| {
+                    let mut character = character.as_ref().clone();
+                    character.template = template;
+                    Msg::SetTool(tool_idx, TableTool::Character(Rc::new(character)))
+                }
+            }),
+            vec![Html::text(text)],
+        )
+    }
+
     fn render_tool_option_boxblock(
         &self,
         tool_idx: usize,
@@ -1053,6 +1247,31 @@ impl Styled for TableMenu {
                 "object-fit": "contain";
             }
 
+            ".template" {
+                "display": "grid";
+                "grid-template-columns": "1fr max-content max-content";
+                "column-gap": ".35rem";
+                "align-items": "center";
+            }
+
+            ".template-import" {
+                "cursor": "pointer";
+                "padding": ".5em 1em";
+                "border-radius": "2px";
+                "background-color": crate::libs::color::Pallet::gray(7);
+                "color": crate::libs::color::Pallet::gray(0);
+            }
+
+            ".template-import > input" {
+                "display": "none";
+            }
+
+            ".template-error" {
+                "grid-column": "1 / -1";
+                "color": crate::libs::color::Pallet::red(7);
+                "font-size": "0.9em";
+            }
+
             "sub-option" {
                 "display": "grid";
                 "grid-template-columns": "1fr 1fr";
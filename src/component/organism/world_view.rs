@@ -2,6 +2,7 @@ use super::atom::{btn::Btn, fa, text::Text};
 use super::molecule::tab_menu::{self, TabMenu};
 use super::organism::{
     component_list::{self, ComponentList},
+    dice_table_list::{self, DiceTableList},
     scene_list::{self, SceneList},
 };
 use crate::arena::{block, ArenaMut, BlockMut};
@@ -26,6 +27,7 @@ pub enum Msg {
 }
 
 pub enum On {
+    OpenDiceTableModeless(U128Id),
     UpdateBlocks {
         insert: HashSet<U128Id>,
         update: HashSet<U128Id>,
@@ -166,6 +168,34 @@ impl Render<Html> for WorldView {
                                         )],
                                     ),
                                 ),
+                                (
+                                    Html::text("ダイス表"),
+                                    Html::div(
+                                        Attributes::new()
+                                            .class(Self::class("padding"))
+                                            .class(Self::class("scroll")),
+                                        Events::new(),
+                                        vec![DiceTableList::empty(
+                                            self,
+                                            None,
+                                            dice_table_list::Props {
+                                                arena: ArenaMut::clone(&self.arena),
+                                                world: BlockMut::clone(&self.world),
+                                            },
+                                            Sub::map(|sub| match sub {
+                                                dice_table_list::On::OpenDiceTable(
+                                                    dice_table_id,
+                                                ) => Msg::Sub(On::OpenDiceTableModeless(
+                                                    dice_table_id,
+                                                )),
+                                                dice_table_list::On::UpdateBlocks {
+                                                    insert,
+                                                    update,
+                                                } => Msg::Sub(On::UpdateBlocks { insert, update }),
+                                            }),
+                                        )],
+                                    ),
+                                ),
                             ],
                         ),
                     )],
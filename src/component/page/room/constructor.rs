@@ -59,6 +59,7 @@ impl Constructor for Room {
 
         let chat_users = vec![ChatUser::Player(BlockMut::clone(&me))];
         let game_system_class = Rc::new(RefCell::new(None));
+        let api_game_system = Rc::new(RefCell::new(None));
 
         let modeless_container = Rc::new(RefCell::new(TabModelessList::new()));
         super::open_modeless(
@@ -70,9 +71,13 @@ impl Constructor for Room {
                 data: BlockMut::clone(&chat),
                 user: ChatUser::Player(BlockMut::clone(&me)),
                 game_system_class: Rc::clone(&game_system_class),
+                api_game_system: Rc::clone(&api_game_system),
+                dice_exchange: Rc::clone(&props.dice_exchange),
             },
         );
 
+        let table = Rc::new(RefCell::new(Table::new(Rc::clone(&props.client_id))));
+
         Self {
             arena: arena,
             local_arena: Arena::new(),
@@ -81,17 +86,22 @@ impl Constructor for Room {
 
             bcdice_loader: props.bcdice_loader,
             game_system_class: game_system_class,
+            bcdice_api_root: props.bcdice_api_root,
+            api_game_system: api_game_system,
+            dice_exchange: props.dice_exchange,
+            sheet_mappings: Rc::new(vec![]),
 
             chat: chat,
             world: world,
             me: me,
 
-            table: Rc::new(RefCell::new(Table::new())),
+            table: table,
             modeless_container: modeless_container,
 
             table_tool: TableMenu::initial_selected(),
             ok_to_catch_file: true,
             is_2d_mode: false,
+            is_dice_animation_enabled: true,
             is_debug_mode: false,
 
             chat_users: chat_users,
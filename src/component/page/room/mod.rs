@@ -2,6 +2,8 @@ use super::organism::room_modeless;
 use super::organism::room_modeless_chat::ChatUser;
 use super::organism::tab_modeless_container::TabModelessList;
 use crate::arena::{block, resource, user, Arena, ArenaMut, BlockMut, Untyped};
+use crate::dicebot::exchange::{DiceExchange, Verification};
+use crate::libs::bcdice::api;
 use crate::libs::bcdice::js::{DynamicLoader, GameSystemClass};
 use crate::libs::random_id::U128Id;
 use crate::table::{table_tool::TableTool, Table};
@@ -22,8 +24,11 @@ pub struct Props {
     pub annot_room_id: Rc<String>,
 
     pub bcdice_loader: Rc<DynamicLoader>,
+    pub bcdice_api_root: Option<Rc<String>>,
+    pub dice_exchange: Rc<DiceExchange>,
 
     pub update_blocks: HashSet<U128Id>,
+    pub posted_blocks: HashSet<U128Id>,
 
     pub chat: Option<BlockMut<block::Chat>>,
     pub world: Option<BlockMut<block::World>>,
@@ -31,6 +36,7 @@ pub struct Props {
 
 pub enum Msg {
     NoOp,
+    SetDiceVerification(U128Id, Verification),
     UpdateBlocks {
         insert: HashSet<U128Id>,
         update: HashSet<U128Id>,
@@ -40,6 +46,8 @@ pub enum Msg {
     OpenCharacterModeless(U128Id),
     OpenCraftboardModeless(U128Id),
     OpenTextboardModeless(U128Id),
+    OpenDiceTableModeless(U128Id),
+    OpenInitiativeModeless,
     SetOkToCatchFile(bool),
     SetSelectedTableTool(TableTool),
     SetShowingContextmenu(Option<ShowingContextmenu>),
@@ -52,10 +60,15 @@ pub enum Msg {
     OnTableMousemove(VEvent<web_sys::MouseEvent>),
     OnTableContextmenu(VEvent<web_sys::MouseEvent>),
     AddResourceImageData(resource::ImageData),
+    AddCharacterFromSheet(block::character::sheet::Sheet, [f64; 2]),
+    AddSheetMapping(block::character::sheet::Mapping),
     SetIs2dMode(bool),
+    SetIsDiceAnimationEnabled(bool),
     SetBlockIsFixedPosition(BlockMut<Untyped>, bool),
     SetBlockIsBindToGrid(BlockMut<Untyped>, bool),
     SetGameSystemClass(GameSystemClass),
+    SetBuiltinSystem(String),
+    SetApiGameSystem(api::GameSystemClass),
     RemoveCharacter(U128Id),
     RemoveBoxblock(U128Id),
     RemoveCraftboard(U128Id),
@@ -78,6 +91,10 @@ pub struct Room {
 
     bcdice_loader: Rc<DynamicLoader>,
     game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
+    bcdice_api_root: Option<Rc<String>>,
+    api_game_system: Rc<RefCell<Option<Rc<api::GameSystemClass>>>>,
+    dice_exchange: Rc<DiceExchange>,
+    sheet_mappings: Rc<Vec<block::character::sheet::Mapping>>,
 
     chat: BlockMut<block::Chat>,
     world: BlockMut<block::World>,
@@ -90,6 +107,7 @@ pub struct Room {
     table_tool: TableTool,
     ok_to_catch_file: bool,
     is_2d_mode: bool,
+    is_dice_animation_enabled: bool,
     is_debug_mode: bool,
     chat_users: Vec<ChatUser>,
 
@@ -113,6 +131,8 @@ enum ShowingContextmenuData {
 pub enum ShowingModal {
     None,
     ChatUser,
+    ChatExport,
+    DiceStats,
     Dicebot,
     Resource,
 }
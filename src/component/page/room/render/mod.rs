@@ -2,6 +2,7 @@ use super::super::atom::{
     btn::{self, Btn},
     common::Common,
     dropdown::{self, Dropdown},
+    fa,
     file_catcher::{self, FileCatcher},
     header::{self, Header},
     marker::Marker,
@@ -9,7 +10,9 @@ use super::super::atom::{
     text::Text,
 };
 use super::super::organism::{
+    modal_chat_export::{self, ModalChatExport},
     modal_chat_user::{self, ModalChatUser},
+    modal_dice_stats::{self, ModalDiceStats},
     modal_dicebot::{self, ModalDicebot},
     modal_resource::{self, ModalResource},
     room_modeless::{self, RoomModeless},
@@ -53,9 +56,16 @@ impl Render<Html> for Room {
                     None,
                     file_catcher::Props {
                         ok_to_catch_file: self.ok_to_catch_file,
+                        sheet_mappings: Rc::clone(&self.sheet_mappings),
                     },
                     Sub::map(|sub| match sub {
                         file_catcher::On::LoadImageData(data) => Msg::AddResourceImageData(data),
+                        file_catcher::On::LoadCharacterSheet(sheet, page_coord) => {
+                            Msg::AddCharacterFromSheet(sheet, page_coord)
+                        }
+                        file_catcher::On::LoadSheetMapping(mapping) => {
+                            Msg::AddSheetMapping(mapping)
+                        }
                     }),
                     (
                         Attributes::new().class(Common::layered()),
@@ -153,7 +163,8 @@ impl Render<Html> for Room {
                                             world: BlockMut::clone(&self.world),
                                         },
                                         Sub::map(|sub| match sub {
-                                            world_view::On::UpdateBlocks {insert, update} => Msg::UpdateBlocks{insert, update}
+                                            world_view::On::UpdateBlocks {insert, update} => Msg::UpdateBlocks{insert, update},
+                                            world_view::On::OpenDiceTableModeless(dice_table_id) => Msg::OpenDiceTableModeless(dice_table_id),
                                         }),
                                     ),
                                 ],
@@ -195,22 +206,54 @@ impl Room {
                     modal_chat_user::On::Select(selected) => Msg::CloseModalChatUser(selected),
                 }),
             ),
+            ShowingModal::ChatExport => ModalChatExport::empty(
+                self,
+                None,
+                modal_chat_export::Props {
+                    chat: BlockMut::clone(&self.chat),
+                },
+                Sub::map(|sub| match sub {
+                    modal_chat_export::On::Close => Msg::SetShowingModal(ShowingModal::None),
+                }),
+            ),
+            ShowingModal::DiceStats => ModalDiceStats::empty(
+                self,
+                None,
+                modal_dice_stats::Props {
+                    chat: BlockMut::clone(&self.chat),
+                },
+                Sub::map(|sub| match sub {
+                    modal_dice_stats::On::Close => Msg::SetShowingModal(ShowingModal::None),
+                }),
+            ),
             ShowingModal::Dicebot => ModalDicebot::empty(
                 self,
                 None,
                 modal_dicebot::Props {
                     bcdice_loader: Rc::clone(&self.bcdice_loader),
+                    bcdice_api_root: self.bcdice_api_root.as_ref().map(Rc::clone),
                     selected_game_system: self
                         .game_system_class
                         .borrow()
                         .as_ref()
-                        .map(|game_system_class| game_system_class.id().clone()),
+                        .map(|game_system_class| game_system_class.id().clone())
+                        .or_else(|| {
+                            self.api_game_system
+                                .borrow()
+                                .as_ref()
+                                .map(|api_game_system| api_game_system.id().clone())
+                        })
+                        .or_else(|| crate::dicebot::selected()),
                 },
                 Sub::map(|sub| match sub {
                     modal_dicebot::On::Close => Msg::SetShowingModal(ShowingModal::None),
                     modal_dicebot::On::SelectGameSystem { game_system_class } => {
                         Msg::SetGameSystemClass(game_system_class)
                     }
+                    modal_dicebot::On::SelectBuiltinSystem { id } => Msg::SetBuiltinSystem(id),
+                    modal_dicebot::On::SelectApiGameSystem { api_game_system } => {
+                        Msg::SetApiGameSystem(api_game_system)
+                    }
                 }),
             ),
             ShowingModal::Resource => ModalResource::empty(
@@ -322,6 +365,20 @@ impl Room {
                                 }),
                                 vec![Html::text("ダイスボット設定")],
                             ),
+                            Btn::menu(
+                                Attributes::new(),
+                                Events::new().on_click(self, |_| {
+                                    Msg::SetShowingModal(ShowingModal::DiceStats)
+                                }),
+                                vec![Html::text("ダイス統計")],
+                            ),
+                            Btn::menu(
+                                Attributes::new(),
+                                Events::new().on_click(self, |_| {
+                                    Msg::SetShowingModal(ShowingModal::ChatExport)
+                                }),
+                                vec![Html::text("チャットログを保存")],
+                            ),
                         ],
                     ),
                 ),
@@ -330,6 +387,11 @@ impl Room {
                     Events::new().on_click(self, |_| Msg::SetShowingModal(ShowingModal::Resource)),
                     vec![Html::text("リソース")],
                 ),
+                Btn::dark(
+                    Attributes::new(),
+                    Events::new().on_click(self, |_| Msg::OpenInitiativeModeless),
+                    vec![Html::text("イニシアチブ")],
+                ),
             ],
         )
     }
@@ -386,6 +448,21 @@ impl Room {
                             Events::new().on_click(self, |_| Msg::SetIs2dMode(false)),
                             vec![Html::text("3Dモード（透視法）")],
                         ),
+                        Btn::menu(
+                            Attributes::new(),
+                            Events::new().on_click(self, {
+                                let is_dice_animation_enabled = self.is_dice_animation_enabled;
+                                move |_| Msg::SetIsDiceAnimationEnabled(!is_dice_animation_enabled)
+                            }),
+                            vec![
+                                fa::fas_i(if self.is_dice_animation_enabled {
+                                    "fa-square-check"
+                                } else {
+                                    "fa-square"
+                                }),
+                                Html::text(" ダイスアニメーション"),
+                            ],
+                        ),
                     ],
                 ),
             )],
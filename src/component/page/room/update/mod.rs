@@ -40,19 +40,42 @@ impl Update for Room {
         }
 
         self.reserve_rendering(props.update_blocks.iter());
+        self.throw_dice(props.posted_blocks.iter());
+        let verification = self.verify_dice_rolls(props.update_blocks.iter());
 
-        Cmd::none()
+        if let Some(initiative_id) = self.sort_initiative(props.update_blocks.iter()) {
+            return Cmd::list(vec![
+                verification,
+                Cmd::submit(On::UpdateBlocks {
+                    insert: set! {},
+                    update: set! { initiative_id },
+                }),
+            ]);
+        }
+
+        verification
     }
 
     fn update(mut self: Pin<&mut Self>, msg: Msg) -> Cmd<Self> {
         match msg {
             Msg::NoOp => Cmd::none(),
-            Msg::UpdateBlocks { insert, update } => {
+            Msg::SetDiceVerification(message_id, verification) => {
+                self.dice_exchange
+                    .set_verification(message_id, verification);
+                Cmd::none()
+            }
+            Msg::UpdateBlocks { insert, mut update } => {
                 self.reserve_rendering(insert.iter().chain(update.iter()));
+                self.throw_dice(insert.iter());
+                let verification = self.verify_dice_rolls(insert.iter().chain(update.iter()));
+                update.extend(self.sort_initiative(insert.iter().chain(update.iter())));
 
                 crate::debug::log_1("UpdateBlocks");
 
-                Cmd::submit(On::UpdateBlocks { insert, update })
+                Cmd::list(vec![
+                    verification,
+                    Cmd::submit(On::UpdateBlocks { insert, update }),
+                ])
             }
             Msg::OpenBoxblockModeless(boxblock_id) => {
                 if let Some(boxblock) = self.arena.get_mut(&boxblock_id) {
@@ -112,6 +135,59 @@ impl Update for Room {
 
                 Cmd::none()
             }
+            Msg::OpenDiceTableModeless(dice_table_id) => {
+                if let Some(dice_table) = self.arena.get_mut(&dice_table_id) {
+                    super::open_modeless(
+                        &self.client_id,
+                        &self.arena,
+                        &self.world,
+                        &self.modeless_container,
+                        room_modeless::ContentData::DiceTable(dice_table),
+                    );
+                }
+
+                Cmd::none()
+            }
+            Msg::OpenInitiativeModeless => {
+                let mut insert = set! {};
+                let mut update = set! {};
+
+                let initiative = unwrap!(
+                    self.world.map(|world| BlockMut::clone(world.initiative()));
+                    Cmd::none()
+                );
+                let initiative = if initiative.map(|_| ()).is_some() {
+                    initiative
+                } else {
+                    let initiative = self.arena.insert(block::Initiative::new());
+                    insert.insert(initiative.id());
+                    update.insert(self.world.id());
+                    self.world.update(|world| {
+                        world.set_initiative(BlockMut::clone(&initiative));
+                    });
+                    initiative
+                };
+
+                super::open_modeless(
+                    &self.client_id,
+                    &self.arena,
+                    &self.world,
+                    &self.modeless_container,
+                    room_modeless::ContentData::Initiative {
+                        data: initiative,
+                        chat: BlockMut::clone(&self.chat),
+                        game_system_class: Rc::clone(&self.game_system_class),
+                        api_game_system: Rc::clone(&self.api_game_system),
+                        dice_exchange: Rc::clone(&self.dice_exchange),
+                    },
+                );
+
+                if insert.is_empty() {
+                    Cmd::none()
+                } else {
+                    Cmd::submit(On::UpdateBlocks { insert, update })
+                }
+            }
             Msg::OpenChatModeless(chat_user) => {
                 if !self.chat_users.iter().any(|user| *user == chat_user) {
                     self.chat_users.push(ChatUser::clone(&chat_user));
@@ -126,6 +202,8 @@ impl Update for Room {
                         data: BlockMut::clone(&self.chat),
                         user: chat_user,
                         game_system_class: Rc::clone(&self.game_system_class),
+                        api_game_system: Rc::clone(&self.api_game_system),
+                        dice_exchange: Rc::clone(&self.dice_exchange),
                     },
                 );
 
@@ -248,12 +326,39 @@ impl Update for Room {
                 });
                 Cmd::none()
             }
+            Msg::AddCharacterFromSheet(sheet, [page_x, page_y]) => {
+                let mouse_coord = self.table.borrow().mouse_coord(page_x, page_y);
+                let character_id = self.table.borrow_mut().create_character_from_sheet(
+                    ArenaMut::clone(&self.arena),
+                    BlockMut::clone(&self.world),
+                    &mouse_coord,
+                    &sheet,
+                );
+                let character_id = unwrap!(character_id; Cmd::none());
+                let blocks = self.table.borrow_mut().take_updated();
+
+                Cmd::list(vec![
+                    Cmd::chain(Msg::UpdateBlocks {
+                        insert: blocks.insert,
+                        update: blocks.update,
+                    }),
+                    Cmd::chain(Msg::OpenCharacterModeless(character_id)),
+                ])
+            }
+            Msg::AddSheetMapping(mapping) => {
+                Rc::make_mut(&mut self.sheet_mappings).push(mapping);
+                Cmd::none()
+            }
             Msg::SetIs2dMode(is_2d_mode) => {
                 self.is_2d_mode = is_2d_mode;
                 self.table.borrow_mut().set_camera_mode(is_2d_mode);
                 self.table.borrow_mut().reserve_rendering();
                 Cmd::none()
             }
+            Msg::SetIsDiceAnimationEnabled(is_dice_animation_enabled) => {
+                self.is_dice_animation_enabled = is_dice_animation_enabled;
+                Cmd::none()
+            }
             Msg::SetBlockIsFixedPosition(block, is_fixed_position) => {
                 trys! {
                     block.type_as::<block::Boxblock>().update(|boxblock| {
@@ -283,7 +388,21 @@ impl Update for Room {
                 Cmd::none()
             }
             Msg::SetGameSystemClass(game_system_class) => {
+                crate::dicebot::select(None);
                 *self.game_system_class.borrow_mut() = Some(game_system_class);
+                *self.api_game_system.borrow_mut() = None;
+                Cmd::none()
+            }
+            Msg::SetBuiltinSystem(id) => {
+                crate::dicebot::select(Some(&id));
+                *self.game_system_class.borrow_mut() = None;
+                *self.api_game_system.borrow_mut() = None;
+                Cmd::none()
+            }
+            Msg::SetApiGameSystem(api_game_system) => {
+                crate::dicebot::select(None);
+                *self.game_system_class.borrow_mut() = None;
+                *self.api_game_system.borrow_mut() = Some(Rc::new(api_game_system));
                 Cmd::none()
             }
 
@@ -402,11 +521,14 @@ impl Room {
             BlockKind::Boxblock
             | BlockKind::CanvasTexture
             | BlockKind::Character
+            | BlockKind::Condition
             | BlockKind::Craftboard
             | BlockKind::Textboard
+            | BlockKind::Property
             | BlockKind::LayerGroup
             | BlockKind::Scene
-            | BlockKind::Table => true,
+            | BlockKind::Table
+            | BlockKind::World => true,
             _ => false,
         });
 
@@ -414,4 +536,113 @@ impl Room {
             self.table.borrow_mut().reserve_rendering();
         }
     }
+
+    // 並び順のパラメータが変わったときにイニシアチブを並べ直す
+    fn sort_initiative<'a>(&self, mut updates: impl Iterator<Item = &'a U128Id>) -> Option<U128Id> {
+        let need_sorting = updates.any(|b_id| match self.arena.kind_of(b_id) {
+            BlockKind::Character | BlockKind::Property => true,
+            _ => false,
+        });
+
+        if !need_sorting {
+            return None;
+        }
+
+        let mut initiative = self
+            .world
+            .map(|world| BlockMut::clone(world.initiative()))?;
+        let mut is_sorted = false;
+        initiative.update(|initiative| {
+            if initiative.sort_key().is_some() {
+                is_sorted = initiative.sort();
+            }
+        });
+
+        if is_sorted {
+            Some(initiative.id())
+        } else {
+            None
+        }
+    }
+
+    fn throw_dice<'a>(&self, block_ids: impl Iterator<Item = &'a U128Id>) {
+        if !self.is_dice_animation_enabled {
+            return;
+        }
+
+        for block_id in block_ids {
+            if self.arena.kind_of(block_id) != BlockKind::ChatMessage {
+                continue;
+            }
+            let rands = self
+                .arena
+                .get::<block::ChatMessage>(block_id)
+                .and_then(|chat_message| {
+                    chat_message.map(|chat_message| {
+                        chat_message
+                            .dice_roll()
+                            .map(|dice_roll| {
+                                dice_roll
+                                    .rands()
+                                    .iter()
+                                    .map(|rand| (rand.sides(), rand.value()))
+                                    .collect()
+                            })
+                            .unwrap_or_default()
+                    })
+                })
+                .unwrap_or_default();
+
+            if !rands.is_empty() {
+                self.table
+                    .borrow_mut()
+                    .throw_dice(U128Id::clone(block_id), &rands);
+            }
+        }
+    }
+
+    // 出目の証明が付いたメッセージを受け取ったら、同じ種で振り直して照合する
+    fn verify_dice_rolls<'a>(&self, block_ids: impl Iterator<Item = &'a U128Id>) -> Cmd<Self> {
+        let mut cmds = vec![];
+        for block_id in block_ids {
+            if self.arena.kind_of(block_id) != BlockKind::ChatMessage {
+                continue;
+            }
+            let chat_message = self.arena.get::<block::ChatMessage>(block_id);
+            let dice_proof = chat_message.as_ref().and_then(|chat_message| {
+                chat_message
+                    .map(|chat_message| {
+                        chat_message
+                            .dice_roll()
+                            .and_then(|dice_roll| dice_roll.proof())
+                            .map(block::chat_message::DiceProof::clone)
+                    })
+                    .unwrap_or(None)
+            });
+            let (chat_message, dice_proof) = if let Some(x) = join_some!(chat_message, dice_proof) {
+                x
+            } else {
+                continue;
+            };
+
+            let dice_exchange = Rc::clone(&self.dice_exchange);
+            let bcdice_loader = Rc::clone(&self.bcdice_loader);
+            let game_system_class = Rc::clone(&self.game_system_class);
+            let world = self.world.as_ref();
+            let message_id = U128Id::clone(block_id);
+            cmds.push(Cmd::task(async move {
+                let verification = task::verify_dice_roll(
+                    dice_exchange,
+                    bcdice_loader,
+                    game_system_class,
+                    world,
+                    chat_message,
+                    dice_proof,
+                )
+                .await;
+                Cmd::chain(Msg::SetDiceVerification(message_id, verification))
+            }));
+        }
+        Cmd::list(cmds)
+    }
 }
@@ -1,5 +1,12 @@
+use crate::arena::{block, BlockRef};
+use crate::dicebot::{
+    exchange::{DiceExchange, Verification},
+    proof,
+};
+use crate::libs::bcdice::js::{CommandResult, DynamicLoader, GameSystemClass, Rand};
 use crate::libs::skyway::{MeshRoom, Peer};
 use js_sys::Promise;
+use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -39,3 +46,126 @@ pub async fn try_to_open_room_connection(
     .ok()
     .map(move |_| room)
 }
+
+// 受け取ったダイスロールを同じ種で振り直し、投稿された結果と照合する
+pub async fn verify_dice_roll(
+    dice_exchange: Rc<DiceExchange>,
+    bcdice_loader: Rc<DynamicLoader>,
+    game_system_class: Rc<RefCell<Option<GameSystemClass>>>,
+    world: BlockRef<block::World>,
+    chat_message: BlockRef<block::ChatMessage>,
+    dice_proof: block::chat_message::DiceProof,
+) -> Verification {
+    let abandoned = match dice_exchange
+        .check(dice_proof.roll(), dice_proof.seed(), dice_proof.entropies())
+        .await
+    {
+        Ok(abandoned) => abandoned,
+        Err(verification) => return verification,
+    };
+
+    let stream = unwrap!(
+        proof::stream_seed(dice_proof.seed(), dice_proof.entropies(), dice_proof.index());
+        Verification::Invalid(String::from("種の形式が正しくありません"))
+    );
+    let system = dice_proof.system();
+    let command = dice_proof.command();
+
+    // ダイス表は本文がキャラクターの値で展開されるため、見出しと出目だけを照合する
+    let (text, is_prefix, command_result) = if system == "table" {
+        let rerolled = world
+            .map(|world| {
+                world.dice_tables().iter().find_map(|dice_table| {
+                    dice_table
+                        .map(|dice_table| {
+                            if dice_table.name() != command {
+                                return None;
+                            }
+                            let result = proof::with_stream(stream.clone(), || {
+                                dice_table.roll(|sides| {
+                                    (proof::random() * sides as f64).floor() as u32 + 1
+                                })
+                            })?;
+                            Some((dice_table.header(&result), result))
+                        })
+                        .unwrap_or(None)
+                })
+            })
+            .unwrap_or(None);
+        let (header, result) = unwrap!(
+            rerolled;
+            Verification::Unverifiable(format!("ダイス表「{}」が見つかりません", command))
+        );
+
+        let command_result = CommandResult {
+            text: header.clone(),
+            detailed_rands: result
+                .rands
+                .into_iter()
+                .map(|(sides, value)| Rand {
+                    kind: String::from("normal"),
+                    sides: sides as i32,
+                    value: value as i32,
+                })
+                .collect(),
+            secret: false,
+            success: false,
+            failure: false,
+            critical: false,
+            fumble: false,
+        };
+        (header, true, command_result)
+    } else {
+        let command_result = if crate::dicebot::is_system(system) {
+            proof::with_stream(stream, || crate::dicebot::eval_as(system, command))
+        } else if game_system_class
+            .borrow()
+            .as_ref()
+            .map(|game_system_class| game_system_class.id() == system)
+            .unwrap_or(false)
+        {
+            proof::with_stream(stream, || {
+                game_system_class
+                    .borrow()
+                    .as_ref()
+                    .and_then(|game_system_class| game_system_class.eval(command))
+            })
+        } else if let Some(game_system_class) = bcdice_loader.dynamic_load(system).await {
+            proof::with_stream(stream, || game_system_class.eval(command))
+        } else {
+            return Verification::Unverifiable(format!("{}を読み込めません", system));
+        };
+        let command_result = unwrap!(
+            command_result;
+            Verification::Invalid(format!("{}を振り直せません", command))
+        );
+        (command_result.text.clone(), false, command_result)
+    };
+
+    let is_same = chat_message
+        .map(|chat_message| {
+            let posted = chat_message.message().to_string();
+            let is_same_text = if is_prefix {
+                posted.starts_with(&text)
+            } else {
+                posted == text
+            };
+            chat_message
+                .dice_roll()
+                .map(|dice_roll| {
+                    let rerolled = block::chat_message::DiceRoll::from_command_result(
+                        dice_roll.roller().clone(),
+                        &command_result,
+                    );
+                    is_same_text && dice_roll.is_same_roll(&rerolled)
+                })
+                .unwrap_or(false)
+        })
+        .unwrap_or(false);
+
+    if is_same {
+        Verification::Verified { abandoned }
+    } else {
+        Verification::Invalid(String::from("振り直した結果が投稿と一致しません"))
+    }
+}
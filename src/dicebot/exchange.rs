@@ -0,0 +1,264 @@
+use super::proof;
+use crate::libs::random_id::U128Id;
+use crate::libs::skyway::{self, MeshRoom};
+use js_sys::Promise;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+// ピアの乱数を待つ上限
+const ENTROPY_TIMEOUT: i32 = 3000;
+// 振った側より遅れて届く乱数を待ってから検証する
+const VERIFICATION_DELAY: i32 = 1000;
+
+pub struct DiceExchange {
+    room: Rc<MeshRoom>,
+    peer_id: Rc<String>,
+    state: RefCell<State>,
+}
+
+struct State {
+    members: HashSet<String>,
+    rolls: HashMap<U128Id, Roll>,
+    verifications: HashMap<U128Id, Verification>,
+}
+
+struct Roll {
+    roller: String,
+    commitment: String,
+    committed_at: f64,
+    entropies: Vec<(String, String)>,
+    contribution: Option<String>,
+    is_revealed: bool,
+    resolve: Option<js_sys::Function>,
+}
+
+pub struct Seed {
+    pub roll: U128Id,
+    pub seed: String,
+    pub entropies: Vec<(String, String)>,
+}
+
+#[derive(Clone)]
+pub enum Verification {
+    Verified { abandoned: usize },
+    Unverifiable(String),
+    Invalid(String),
+}
+
+impl Roll {
+    fn new(roller: String, commitment: String) -> Self {
+        Self {
+            roller,
+            commitment,
+            committed_at: js_sys::Date::now(),
+            entropies: vec![],
+            contribution: None,
+            is_revealed: false,
+            resolve: None,
+        }
+    }
+}
+
+impl Seed {
+    pub fn stream(&self, index: u32) -> Option<Vec<u8>> {
+        proof::stream_seed(&self.seed, &self.entropies, index)
+    }
+}
+
+fn timeout(ms: i32) -> JsFuture {
+    JsFuture::from(Promise::new(&mut |resolve, _| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms);
+        }
+    }))
+}
+
+impl DiceExchange {
+    pub fn new(room: Rc<MeshRoom>, peer_id: Rc<String>) -> Self {
+        Self {
+            room,
+            peer_id,
+            state: RefCell::new(State {
+                members: HashSet::new(),
+                rolls: HashMap::new(),
+                verifications: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn add_member(&self, peer_id: String) {
+        self.state.borrow_mut().members.insert(peer_id);
+    }
+
+    pub fn remove_member(&self, peer_id: &str) {
+        self.state.borrow_mut().members.remove(peer_id);
+    }
+
+    // 種のハッシュを先に公開し、それを見たピアから乱数を集める
+    pub async fn exchange(&self) -> Seed {
+        let roll = U128Id::new();
+        let seed = proof::new_seed();
+        let commitment = proof::commitment(&seed).unwrap_or_default();
+
+        self.state.borrow_mut().rolls.insert(
+            U128Id::clone(&roll),
+            Roll::new(String::clone(&self.peer_id), String::clone(&commitment)),
+        );
+        self.room.send_msg(skyway::Msg::DiceCommit {
+            roll: U128Id::clone(&roll),
+            commitment,
+        });
+
+        if !self.state.borrow().members.is_empty() {
+            let _ = JsFuture::from(Promise::new(&mut |resolve, _| {
+                if let Some(window) = web_sys::window() {
+                    let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                        &resolve,
+                        ENTROPY_TIMEOUT,
+                    );
+                }
+                if let Some(roll) = self.state.borrow_mut().rolls.get_mut(&roll) {
+                    roll.resolve = Some(resolve);
+                }
+            }))
+            .await;
+        }
+
+        let entropies = self
+            .state
+            .borrow_mut()
+            .rolls
+            .get_mut(&roll)
+            .map(|roll| {
+                roll.resolve = None;
+                roll.is_revealed = true;
+                roll.entropies.clone()
+            })
+            .unwrap_or_default();
+
+        Seed {
+            roll,
+            seed,
+            entropies,
+        }
+    }
+
+    // 最初に届いた約束だけを記録し、以後は上書きしない
+    pub fn receive_commit(&self, peer_id: String, roll: U128Id, commitment: String) {
+        let entropy = proof::new_seed();
+
+        {
+            let mut state = self.state.borrow_mut();
+            if state.rolls.contains_key(&roll) {
+                return;
+            }
+            let mut commit = Roll::new(peer_id, commitment);
+            commit
+                .entropies
+                .push((String::clone(&self.peer_id), entropy.clone()));
+            commit.contribution = Some(entropy.clone());
+            state.rolls.insert(U128Id::clone(&roll), commit);
+        }
+
+        self.room
+            .send_msg(skyway::Msg::DiceEntropy { roll, entropy });
+    }
+
+    pub fn receive_entropy(&self, peer_id: String, roll: U128Id, entropy: String) {
+        let resolve = {
+            let mut state = self.state.borrow_mut();
+            let State { members, rolls, .. } = &mut *state;
+            let roll = unwrap!(rolls.get_mut(&roll));
+
+            if peer_id == roll.roller
+                || roll.is_revealed
+                || !proof::is_seed(&entropy)
+                || roll.entropies.iter().any(|(x, _)| *x == peer_id)
+            {
+                return;
+            }
+            roll.entropies.push((peer_id, entropy));
+
+            let is_complete = members
+                .iter()
+                .all(|member| roll.entropies.iter().any(|(x, _)| x == member));
+            if is_complete {
+                roll.resolve.take()
+            } else {
+                None
+            }
+        };
+
+        if let Some(resolve) = resolve {
+            let _ = resolve.call0(&JsValue::null());
+        }
+    }
+
+    // 明かされた種と乱数が、このクライアントの受け取った約束と乱数に一致するかを調べる
+    // 一致すれば、同じ振り手が明かさずに放置した過去のロールの数を返す
+    pub async fn check(
+        &self,
+        roll: &U128Id,
+        seed: &str,
+        entropies: &[(String, String)],
+    ) -> Result<usize, Verification> {
+        let _ = timeout(VERIFICATION_DELAY).await;
+
+        let mut state = self.state.borrow_mut();
+        let (roller, committed_at) = {
+            let commit = state.rolls.get_mut(roll).ok_or_else(|| {
+                Verification::Unverifiable(String::from("ロールの宣言を受け取っていません"))
+            })?;
+
+            if proof::commitment(seed).as_ref() != Some(&commit.commitment) {
+                return Err(Verification::Invalid(String::from(
+                    "明かされた種が宣言と一致しません",
+                )));
+            }
+            if let Some(contribution) = &commit.contribution {
+                let own = (String::clone(&self.peer_id), String::clone(contribution));
+                if !entropies.contains(&own) {
+                    return Err(Verification::Invalid(String::from(
+                        "このクライアントの乱数が使われていません",
+                    )));
+                }
+            }
+            // 他のピアの乱数が間に合わずに省かれることはあるが、見ていない乱数が混ざることはない
+            if entropies
+                .iter()
+                .any(|entropy| !commit.entropies.contains(entropy))
+            {
+                return Err(Verification::Invalid(String::from(
+                    "送られていない乱数が含まれています",
+                )));
+            }
+
+            commit.is_revealed = true;
+            (commit.roller.clone(), commit.committed_at)
+        };
+
+        let abandoned = state
+            .rolls
+            .values()
+            .filter(|commit| {
+                commit.roller == roller && !commit.is_revealed && commit.committed_at < committed_at
+            })
+            .count();
+
+        Ok(abandoned)
+    }
+
+    pub fn set_verification(&self, message_id: U128Id, verification: Verification) {
+        self.state
+            .borrow_mut()
+            .verifications
+            .insert(message_id, verification);
+    }
+
+    pub fn verification(&self, message_id: &U128Id) -> Option<Verification> {
+        self.state.borrow().verifications.get(message_id).cloned()
+    }
+}
@@ -1,17 +1,41 @@
+use crate::libs::bcdice::js::{CommandResult, Rand};
+use lazy_static::lazy_static;
 use regex::Regex;
 use sainome;
 use serde::Deserialize;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
-use wasm_bindgen::prelude::*;
 
-thread_local! { static RUN_TIME: Rc<sainome::RunTime<'static>> = Rc::new(sainome::RunTime::new(rand)); }
+pub mod exchange;
+pub mod proof;
+
+lazy_static! {
+    static ref COMMAND: Regex = Regex::new(r"\A[0-9dD+\-*/()<>=!]+\z").unwrap();
+    static ref DICE: Regex = Regex::new(r"(\d+)[dD](\d+)").unwrap();
+    static ref OPERATOR: Regex = Regex::new(r"<>|<=|>=|==|!=|=|<|>").unwrap();
+}
+
+const SYSTEMS: [(&str, &str, &str); 2] = [
+    ("sainome:DiceBot", "汎用ダイス（内蔵）", ""),
+    (
+        "sainome:SwordWorld2",
+        "ソード・ワールド2.x（内蔵）",
+        include_str!("sword_world.toml"),
+    ),
+];
+
+thread_local! {
+    static SYSTEM: RefCell<System> = RefCell::new(System::new(None));
+    static RANDS: RefCell<Vec<Rand>> = RefCell::new(vec![]);
+}
 
 pub type RunTime = sainome::RunTime<'static>;
 
 #[derive(Deserialize)]
 pub struct Config {
+    #[serde(default)]
     def: Vec<HashMap<String, String>>,
+    #[serde(default)]
     pattern: Vec<Pattern>,
 }
 
@@ -21,12 +45,32 @@ pub struct Pattern {
     replace: String,
 }
 
-pub fn new_run_time() -> RunTime {
-    RunTime::new(rand)
+struct System {
+    id: Option<String>,
+    run_time: RunTime,
+    config: Option<Config>,
 }
 
-pub fn config() -> Config {
-    toml::from_str(include_str!("sword_world.toml")).unwrap()
+impl System {
+    fn new(id: Option<&str>) -> Self {
+        let mut run_time = new_run_time();
+        let system = id.and_then(|id| SYSTEMS.iter().find(|(system_id, ..)| *system_id == id));
+        let config = system.and_then(|(_, _, config)| toml::from_str::<Config>(config).ok());
+
+        if let Some(config) = &config {
+            set_env(config, &mut run_time);
+        }
+
+        Self {
+            id: config.as_ref().and(id).map(String::from),
+            run_time,
+            config,
+        }
+    }
+}
+
+pub fn new_run_time() -> RunTime {
+    RunTime::new(rand)
 }
 
 pub fn set_env(config: &Config, run_time: &mut RunTime) {
@@ -40,121 +84,167 @@ pub fn set_env(config: &Config, run_time: &mut RunTime) {
     }
 }
 
-pub fn cmd_with_config(cmd: String, config: &Config) -> String {
+pub fn cmd_with_config(cmd: &str, config: &Config) -> Option<String> {
     for pattern in &config.pattern {
         if let Ok(capture) = Regex::new(&pattern.capture) {
-            if capture.is_match(cmd.as_str()) {
-                return capture
-                    .replace_all(cmd.as_str(), pattern.replace.as_str())
-                    .to_string();
+            if capture.is_match(cmd) {
+                return Some(
+                    capture
+                        .replace_all(cmd, pattern.replace.as_str())
+                        .to_string(),
+                );
             }
         }
     }
-    cmd
+    None
 }
 
-fn rand(n: u32) -> u32 {
-    (js_sys::Math::random() * n as f64).floor() as u32
+pub fn systems() -> Vec<(String, String)> {
+    SYSTEMS
+        .iter()
+        .map(|(id, name, _)| (String::from(*id), String::from(*name)))
+        .collect()
 }
 
-pub mod bcdice {
-    use serde::Deserialize;
-    use std::ops::Deref;
-
-    #[derive(Deserialize, Debug)]
-    pub struct ImplNames {
-        name: String,
-        system: String,
-        sort_key: String,
-    }
-
-    #[derive(Deserialize, Debug)]
-    pub struct Names {
-        names: Vec<ImplNames>,
-    }
-
-    #[derive(Deserialize, Debug)]
-    #[serde(rename_all = "camelCase")]
-    pub struct ImplSystemInfo {
-        name: String,
-        game_type: String,
-        sort_key: String,
-        prefixs: Vec<String>,
-        info: String,
-    }
-
-    #[derive(Deserialize, Debug)]
-    pub struct SystemInfo {
-        systeminfo: ImplSystemInfo,
-    }
-
-    #[derive(Deserialize, Debug)]
-    pub struct DiceRoll {
-        result: String,
-    }
+pub fn is_system(id: &str) -> bool {
+    SYSTEMS.iter().any(|(system_id, ..)| *system_id == id)
+}
 
-    impl ImplNames {
-        pub fn name(&self) -> &String {
-            &self.name
+pub fn select(id: Option<&str>) {
+    SYSTEM.with(|system| {
+        if system.borrow().id.as_ref().map(String::as_str) != id {
+            *system.borrow_mut() = System::new(id);
         }
+    });
+}
 
-        pub fn system(&self) -> &String {
-            &self.system
-        }
+pub fn selected() -> Option<String> {
+    SYSTEM.with(|system| system.borrow().id.clone())
+}
 
-        pub fn sort_key(&self) -> &String {
-            &self.sort_key
-        }
-    }
+pub fn eval(text: &str) -> Option<CommandResult> {
+    SYSTEM.with(|system| eval_with(&system.borrow(), text))
+}
 
-    impl Names {
-        pub fn sorted(mut self) -> Self {
-            self.names.sort_by(|a, b| a.name.cmp(&b.name));
-            self
-        }
+// 選択中のシステムを切り替えずに、指定したシステムで評価する
+pub fn eval_as(id: &str, text: &str) -> Option<CommandResult> {
+    if selected().as_deref() == Some(id) {
+        eval(text)
+    } else {
+        eval_with(&System::new(Some(id)), text)
     }
+}
 
-    impl Deref for Names {
-        type Target = Vec<ImplNames>;
-        fn deref(&self) -> &Self::Target {
-            &self.names
-        }
-    }
+fn eval_with(system: &System, text: &str) -> Option<CommandResult> {
+    let command = text.split_whitespace().next()?;
+
+    let code = if COMMAND.is_match(command) && DICE.is_match(command) {
+        let code = DICE.replace_all(command, "((${1})b${2}>>log>>sum)");
+        OPERATOR
+            .replace_all(&code, |captures: &regex::Captures| match &captures[0] {
+                "<>" => String::from("!="),
+                "=" => String::from("=="),
+                operator => String::from(operator),
+            })
+            .to_string()
+    } else {
+        let config = system.config.as_ref()?;
+        cmd_with_config(command, config)?
+    };
+
+    RANDS.with(|rands| rands.borrow_mut().clear());
+    let (result, log) = sainome::exec(&code, &system.run_time);
+    let result = result?.to_string();
+    let detailed_rands = RANDS.with(|rands| rands.borrow_mut().drain(..).collect());
+
+    let success = result == "true";
+    let failure = result == "false";
+    let critical = log.iter().any(|line| line.ends_with("回転"));
+    let fumble = result == "自動失敗" || log.iter().any(|line| line == "自動失敗");
+    let result = if success {
+        String::from("成功")
+    } else if failure {
+        String::from("失敗")
+    } else {
+        result
+    };
+
+    let text = vec![format!("({})", command.to_uppercase())]
+        .into_iter()
+        .chain(log.into_iter())
+        .chain(vec![result].into_iter())
+        .collect::<Vec<_>>()
+        .join(" ＞ ");
+
+    Some(CommandResult {
+        text,
+        detailed_rands,
+        secret: false,
+        success,
+        failure,
+        critical,
+        fumble,
+    })
+}
 
-    impl AsRef<Vec<ImplNames>> for Names {
-        fn as_ref(&self) -> &Vec<ImplNames> {
-            &self.names
-        }
-    }
+// sainomeは0からn-1の値を受け取り、1を足して出目にする
+fn rand(n: u32) -> u32 {
+    let value = (random() * n as f64).floor() as u32;
+    RANDS.with(|rands| {
+        rands.borrow_mut().push(Rand {
+            kind: String::from("normal"),
+            sides: n as i32,
+            value: value as i32 + 1,
+        })
+    });
+    value
+}
 
-    impl ImplSystemInfo {
-        pub fn name(&self) -> &String {
-            &self.name
-        }
+#[cfg(not(test))]
+fn random() -> f64 {
+    proof::random()
+}
 
-        pub fn game_type(&self) -> &String {
-            &self.game_type
-        }
+#[cfg(test)]
+fn random() -> f64 {
+    tests::random()
+}
 
-        pub fn prefixs(&self) -> &Vec<String> {
-            &self.prefixs
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
 
-        pub fn info(&self) -> &String {
-            &self.info
-        }
+    thread_local! {
+        static SEED: Cell<u32> = Cell::new(0);
     }
 
-    impl Deref for SystemInfo {
-        type Target = ImplSystemInfo;
-        fn deref(&self) -> &Self::Target {
-            &self.systeminfo
-        }
+    pub fn random() -> f64 {
+        SEED.with(|seed| {
+            let value = seed.get().wrapping_mul(1103515245).wrapping_add(12345);
+            seed.set(value);
+            (value >> 16) as f64 / 65536.0
+        })
     }
 
-    impl DiceRoll {
-        pub fn result(&self) -> &String {
-            &self.result
+    #[test]
+    fn test_recorded_rands_match_result() {
+        select(Some("sainome:DiceBot"));
+        assert_eq!(selected().as_deref(), Some("sainome:DiceBot"));
+
+        for _ in 0..20 {
+            let result = eval("3d6").unwrap();
+            let faces = result
+                .detailed_rands
+                .iter()
+                .map(|rand| rand.value)
+                .collect::<Vec<_>>();
+
+            assert_eq!(faces.len(), 3);
+            assert!(faces.iter().all(|face| (1..=6).contains(face)));
+            assert!(result
+                .text
+                .ends_with(&format!(" ＞ {}", faces.iter().sum::<i32>())));
         }
     }
 }
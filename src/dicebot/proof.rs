@@ -0,0 +1,170 @@
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use wasm_bindgen::{prelude::*, JsCast};
+
+const SEED_LENGTH: usize = 32;
+
+thread_local! {
+    static STREAM: RefCell<Option<Stream>> = RefCell::new(None);
+}
+
+struct Stream {
+    seed: Vec<u8>,
+    draws: u32,
+}
+
+fn hash(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
+}
+
+fn decode(text: &str) -> Option<Vec<u8>> {
+    hex::decode(text)
+        .ok()
+        .filter(|bytes| bytes.len() == SEED_LENGTH)
+}
+
+fn draw(seed: &[u8], n: u32) -> f64 {
+    let mut data = seed.to_vec();
+    data.extend(&n.to_be_bytes());
+    let hash = hash(&data);
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&hash[0..8]);
+    (u64::from_be_bytes(buf) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub fn new_seed() -> String {
+    hex::encode(crate::libs::random_id::u8vec(SEED_LENGTH))
+}
+
+pub fn is_seed(text: &str) -> bool {
+    decode(text).is_some()
+}
+
+pub fn commitment(seed: &str) -> Option<String> {
+    decode(seed).map(|seed| hex::encode(hash(&seed)))
+}
+
+// 振った側の種と各ピアの乱数から、index番目のコマンドが使う乱数列の種を作る
+pub fn stream_seed(seed: &str, entropies: &[(String, String)], index: u32) -> Option<Vec<u8>> {
+    let mut data = decode(seed)?;
+
+    let mut entropies = entropies.iter().collect::<Vec<_>>();
+    entropies.sort();
+    for (peer_id, entropy) in entropies {
+        data.extend(hash(peer_id.as_bytes()));
+        data.extend(decode(entropy)?);
+    }
+
+    data.extend(&index.to_be_bytes());
+    Some(hash(&data))
+}
+
+pub fn random() -> f64 {
+    STREAM.with(|stream| {
+        if let Some(stream) = stream.borrow_mut().as_mut() {
+            let value = draw(&stream.seed, stream.draws);
+            stream.draws += 1;
+            value
+        } else {
+            js_sys::Math::random()
+        }
+    })
+}
+
+// BCDiceはMath.randomで出目を決めるため、fを評価する間だけ乱数列に差し替える
+pub fn with_stream<T>(seed: Vec<u8>, f: impl FnOnce() -> T) -> T {
+    let math = js_sys::Reflect::get(&js_sys::global(), &JsValue::from("Math")).ok();
+    let math_random = math
+        .as_ref()
+        .and_then(|math| js_sys::Reflect::get(math, &JsValue::from("random")).ok());
+    let seeded_random = Closure::wrap(Box::new(random) as Box<dyn FnMut() -> f64>);
+
+    STREAM.with(|stream| *stream.borrow_mut() = Some(Stream { seed, draws: 0 }));
+    if let Some(math) = &math {
+        let _ = js_sys::Reflect::set(
+            math,
+            &JsValue::from("random"),
+            seeded_random.as_ref().unchecked_ref(),
+        );
+    }
+
+    let res = f();
+
+    if let Some((math, math_random)) = join_some!(math, math_random) {
+        let _ = js_sys::Reflect::set(&math, &JsValue::from("random"), &math_random);
+    }
+    STREAM.with(|stream| *stream.borrow_mut() = None);
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed(n: u8) -> String {
+        hex::encode([n; SEED_LENGTH])
+    }
+
+    #[test]
+    fn test_commitment() {
+        assert_eq!(commitment(&seed(1)), commitment(&seed(1)));
+        assert_ne!(commitment(&seed(1)), commitment(&seed(2)));
+        assert_eq!(commitment("00"), None);
+        assert_eq!(commitment("not hex"), None);
+        assert!(is_seed(&seed(1)));
+        assert!(!is_seed("00"));
+    }
+
+    #[test]
+    fn test_stream_seed_depends_on_every_entropy() {
+        let entropies = vec![
+            (String::from("peer-a"), seed(2)),
+            (String::from("peer-b"), seed(3)),
+        ];
+        let stream = stream_seed(&seed(1), &entropies, 0);
+
+        assert!(stream.is_some());
+        assert_eq!(stream, stream_seed(&seed(1), &entropies, 0));
+        assert_ne!(stream, stream_seed(&seed(1), &entropies, 1));
+        assert_ne!(stream, stream_seed(&seed(4), &entropies, 0));
+        assert_ne!(stream, stream_seed(&seed(1), &entropies[0..1], 0));
+
+        let swapped = vec![
+            (String::from("peer-a"), seed(3)),
+            (String::from("peer-b"), seed(2)),
+        ];
+        assert_ne!(stream, stream_seed(&seed(1), &swapped, 0));
+    }
+
+    #[test]
+    fn test_stream_seed_ignores_entropy_order() {
+        let entropies = vec![
+            (String::from("peer-a"), seed(2)),
+            (String::from("peer-b"), seed(3)),
+        ];
+        let reversed = entropies.iter().rev().cloned().collect::<Vec<_>>();
+
+        assert_eq!(
+            stream_seed(&seed(1), &entropies, 0),
+            stream_seed(&seed(1), &reversed, 0)
+        );
+    }
+
+    #[test]
+    fn test_stream_seed_rejects_malformed_entropy() {
+        let entropies = vec![(String::from("peer-a"), String::from("00"))];
+        assert_eq!(stream_seed(&seed(1), &entropies, 0), None);
+    }
+
+    #[test]
+    fn test_draw() {
+        let stream = stream_seed(&seed(1), &[], 0).unwrap();
+        for n in 0..100 {
+            let value = draw(&stream, n);
+            assert!((0.0..1.0).contains(&value));
+            assert_eq!(value, draw(&stream, n));
+        }
+        assert_ne!(draw(&stream, 0), draw(&stream, 1));
+    }
+}
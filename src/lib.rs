@@ -21,6 +21,7 @@ extern crate sainome;
 extern crate serde;
 extern crate serde_json;
 extern crate serde_yaml;
+extern crate sha2;
 extern crate toml;
 extern crate wasm_bindgen_futures;
 extern crate web_sys;
@@ -35,6 +36,7 @@ mod libs;
 mod arena;
 mod component;
 mod debug;
+mod dicebot;
 mod model;
 mod table;
 
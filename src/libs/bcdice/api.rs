@@ -1,3 +1,4 @@
+use super::js::{CommandResult, Rand};
 use crate::libs::js_object::Object;
 use regex::Regex;
 use wasm_bindgen::JsCast;
@@ -17,6 +18,11 @@ pub struct GameSystemInfo {
     pub help_message: String,
 }
 
+pub struct GameSystemClass {
+    root: String,
+    info: GameSystemInfo,
+}
+
 async fn get_json(url: &str) -> Option<Object> {
     let mut opts = web_sys::RequestInit::new();
     opts.method("GET");
@@ -77,3 +83,83 @@ pub async fn game_system_info(root: &str, id: &str) -> Option<GameSystemInfo> {
         help_message,
     })
 }
+
+pub async fn roll(root: &str, id: &str, command: &str) -> Result<CommandResult, String> {
+    let encoded = js_sys::encode_uri_component(command).as_string();
+    let encoded = unwrap!(encoded; Err(format!("{}：コマンドを送信できません", command)));
+    let response = get_json(&format!(
+        "{}/v2/game_system/{}/roll?command={}",
+        root, id, encoded
+    ))
+    .await;
+    let response = unwrap!(response; Err(format!("{}：BCDice-APIに接続できません", command)));
+
+    let ok = response
+        .get("ok")
+        .and_then(|x| x.as_bool())
+        .unwrap_or(false);
+    if !ok {
+        let reason = response
+            .get("reason")
+            .and_then(|x| x.as_string())
+            .unwrap_or_else(|| String::from("コマンドを実行できません"));
+        return Err(format!("{}：{}", command, reason));
+    }
+
+    let invalid = || format!("{}：BCDice-APIの応答が不正です", command);
+    let text = unwrap!(response.get("text").and_then(|x| x.as_string()); Err(invalid()));
+    let secret = unwrap!(response.get("secret").and_then(|x| x.as_bool()); Err(invalid()));
+    let success = unwrap!(response.get("success").and_then(|x| x.as_bool()); Err(invalid()));
+    let failure = unwrap!(response.get("failure").and_then(|x| x.as_bool()); Err(invalid()));
+    let critical = unwrap!(response.get("critical").and_then(|x| x.as_bool()); Err(invalid()));
+    let fumble = unwrap!(response.get("fumble").and_then(|x| x.as_bool()); Err(invalid()));
+
+    let detailed_rands = response
+        .get("rands")
+        .map(|x| js_sys::Array::from(&x).to_vec())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|x| x.dyn_into::<Object>().ok())
+        .filter_map(|x| x.try_as::<Rand>())
+        .collect();
+
+    Ok(CommandResult {
+        text,
+        detailed_rands,
+        secret,
+        success,
+        failure,
+        critical,
+        fumble,
+    })
+}
+
+impl GameSystemClass {
+    pub async fn load(root: &str, id: &str) -> Option<Self> {
+        let info = unwrap!(game_system_info(root, id).await; None);
+        Some(Self {
+            root: String::from(root),
+            info,
+        })
+    }
+
+    pub fn id(&self) -> &String {
+        &self.info.id
+    }
+
+    pub fn name(&self) -> &String {
+        &self.info.name
+    }
+
+    pub fn help_message(&self) -> &String {
+        &self.info.help_message
+    }
+
+    pub fn is_command(&self, command: &str) -> bool {
+        self.info.command_pattern.is_match(command)
+    }
+
+    pub async fn eval(&self, command: &str) -> Result<CommandResult, String> {
+        roll(&self.root, &self.info.id, command).await
+    }
+}
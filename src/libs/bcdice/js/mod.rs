@@ -128,6 +128,24 @@ impl GameSystemClass {
         &self.command_pattern
     }
 
+    pub fn command_prefixes(&self) -> Vec<String> {
+        let source = String::from(self.command_pattern.source());
+        let mut prefixes: Vec<String> = vec![];
+
+        for alternative in source.split(|c| c == '|' || c == '(' || c == ')') {
+            let alternative = alternative.trim_start_matches("?:").trim_start_matches('^');
+            if alternative.len() >= 2
+                && alternative.chars().all(|c| c.is_ascii_alphanumeric())
+                && alternative.chars().any(|c| c.is_ascii_alphabetic())
+                && !prefixes.iter().any(|prefix| prefix == alternative)
+            {
+                prefixes.push(String::from(alternative));
+            }
+        }
+
+        prefixes
+    }
+
     pub fn eval(&self, command: &str) -> Option<CommandResult> {
         crate::debug::log_2("command", command);
         let result = unwrap!(self.eval.call1(&self.this, &JsValue::from(command)).ok(); None);
@@ -198,7 +216,7 @@ impl TryFrom<&Object> for Rand {
 
     fn try_from(data: &Object) -> Result<Self, Self::Error> {
         let kind = unwrap!(data.get("kind").and_then(|x| x.as_string()); Err(()));
-        let sides = unwrap!(data.get("kind").and_then(|x| x.as_f64()).map(|x| x as i32); Err(()));
+        let sides = unwrap!(data.get("sides").and_then(|x| x.as_f64()).map(|x| x as i32); Err(()));
         let value = unwrap!(data.get("value").and_then(|x| x.as_f64()).map(|x| x as i32); Err(()));
 
         Ok(Self { kind, sides, value })
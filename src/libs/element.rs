@@ -1,4 +1,4 @@
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{prelude::*, JsCast};
 
 pub fn html_image_element() -> web_sys::HtmlImageElement {
     web_sys::window()
@@ -40,3 +40,30 @@ pub fn canvas_from_image(img: &web_sys::HtmlImageElement) -> web_sys::HtmlCanvas
 
     canvas
 }
+
+pub fn download(file_name: &str, type_: &str, data: &str) {
+    let blob = unwrap!(web_sys::Blob::new_with_str_sequence_and_options(
+        array![data].as_ref(),
+        web_sys::BlobPropertyBag::new().type_(type_)
+    )
+    .ok());
+    let url = unwrap!(web_sys::Url::create_object_url_with_blob(&blob).ok());
+    let document = unwrap!(web_sys::window().and_then(|window| window.document()));
+    let a = unwrap!(document.create_element("a").ok());
+    let _ = a.set_attribute("href", &url);
+    let _ = a.set_attribute("download", file_name);
+    if let Ok(a) = a.dyn_into::<web_sys::HtmlElement>() {
+        a.click();
+    }
+
+    let revoke = Closure::once(Box::new(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    }) as Box<dyn FnOnce()>);
+    if let Some(window) = web_sys::window() {
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            revoke.as_ref().unchecked_ref(),
+            1000,
+        );
+    }
+    revoke.forget();
+}
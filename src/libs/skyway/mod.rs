@@ -47,6 +47,9 @@ extern "C" {
 
     #[wasm_bindgen(method, getter)]
     pub fn id(this: &DataConnection) -> String;
+
+    #[wasm_bindgen(method, getter, js_name = "remoteId")]
+    pub fn remote_id(this: &DataConnection) -> String;
 }
 
 #[wasm_bindgen(module = "skyway-js")]
@@ -76,6 +79,14 @@ pub enum Msg {
     PostBlock(JsValue),
     GetBlock(U128Id),
     GetBlockResponse(JsValue),
+    DiceCommit {
+        roll: U128Id,
+        commitment: String,
+    },
+    DiceEntropy {
+        roll: U128Id,
+        entropy: String,
+    },
 }
 
 impl DataConnection {
@@ -100,6 +111,8 @@ impl Msg {
             Self::PostBlock { .. } => "PostBlock",
             Self::GetBlock { .. } => "GetBlock",
             Self::GetBlockResponse { .. } => "GetBlockResponse",
+            Self::DiceCommit { .. } => "DiceCommit",
+            Self::DiceEntropy { .. } => "DiceEntropy",
         }
     }
 }
@@ -132,6 +145,16 @@ impl Into<Object> for Msg {
             Self::PostBlock(block_data) => block_data,
             Self::GetBlock(block_id) => block_id.to_jsvalue(),
             Self::GetBlockResponse(block_data) => block_data,
+            Self::DiceCommit { roll, commitment } => (object! {
+                "roll": roll.to_jsvalue(),
+                "commitment": commitment
+            })
+            .into(),
+            Self::DiceEntropy { roll, entropy } => (object! {
+                "roll": roll.to_jsvalue(),
+                "entropy": entropy
+            })
+            .into(),
         };
         object! {
             "type": type_name,
@@ -163,6 +186,8 @@ impl From<&Object> for Msg {
                     .map(|block_id| Msg::GetBlock(block_id))
                     .unwrap_or(Msg::None),
                 "GetBlockResponse" => Self::GetBlockResponse(payload.into()),
+                "DiceCommit" => parse_dice_commit(&payload),
+                "DiceEntropy" => parse_dice_entropy(&payload),
                 _ => Self::None,
             }
         } else {
@@ -195,3 +220,21 @@ fn parse_post_arena(payload: &Object) -> Msg {
         blocks,
     }
 }
+
+fn parse_dice_commit(payload: &Object) -> Msg {
+    let roll = unwrap!(payload.get("roll"); Msg::None);
+    let roll = unwrap!(U128Id::from_jsvalue(&roll); Msg::None);
+
+    let commitment = unwrap!(payload.get("commitment").and_then(|x| x.as_string()); Msg::None);
+
+    Msg::DiceCommit { roll, commitment }
+}
+
+fn parse_dice_entropy(payload: &Object) -> Msg {
+    let roll = unwrap!(payload.get("roll"); Msg::None);
+    let roll = unwrap!(U128Id::from_jsvalue(&roll); Msg::None);
+
+    let entropy = unwrap!(payload.get("entropy").and_then(|x| x.as_string()); Msg::None);
+
+    Msg::DiceEntropy { roll, entropy }
+}
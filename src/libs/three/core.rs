@@ -1,6 +1,6 @@
 use wasm_bindgen::prelude::*;
 
-use super::{Camera, Euler, Ray, Vector2, Vector3};
+use super::{Camera, Euler, Quaternion, Ray, Vector2, Vector3};
 
 #[wasm_bindgen(module = "three")]
 extern "C" {
@@ -53,6 +53,9 @@ extern "C" {
 
     #[wasm_bindgen(method, js_name = "setFromPoints")]
     pub fn set_from_points(this: &BufferGeometry, points: &js_sys::Array) -> BufferGeometry;
+
+    #[wasm_bindgen(method, js_name = "computeVertexNormals")]
+    pub fn compute_vertex_normals(this: &BufferGeometry);
 }
 
 #[wasm_bindgen(module = "three")]
@@ -92,6 +95,12 @@ extern "C" {
     #[wasm_bindgen(method, getter)]
     pub fn rotation(this: &Object3D) -> Euler;
 
+    #[wasm_bindgen(method, getter)]
+    pub fn quaternion(this: &Object3D) -> Quaternion;
+
+    #[wasm_bindgen(method, js_name = "lookAt")]
+    pub fn look_at(this: &Object3D, x: f64, y: f64, z: f64);
+
     #[wasm_bindgen(method, getter, js_name = "userData")]
     pub fn user_data(this: &Object3D) -> JsValue;
 
@@ -28,6 +28,14 @@ extern "C" {
     pub fn set_z(this: &Euler, z: f64);
 }
 
+#[wasm_bindgen(module = "three")]
+extern "C" {
+    pub type Quaternion;
+
+    #[wasm_bindgen(method, js_name = "setFromUnitVectors")]
+    pub fn set_from_unit_vectors(this: &Quaternion, from: &Vector3, to: &Vector3) -> Quaternion;
+}
+
 #[wasm_bindgen(module = "three")]
 extern "C" {
     pub type Ray;
@@ -5,6 +5,7 @@ pub struct Config {
     pub skyway: SkywayConfig,
     pub client: ClientConfig,
     pub drive: DriveConfig,
+    pub bcdice: Option<BcdiceConfig>,
 }
 
 #[derive(Deserialize)]
@@ -22,3 +23,8 @@ pub struct DriveConfig {
     pub api_key: String,
     pub client_id: String,
 }
+
+#[derive(Deserialize)]
+pub struct BcdiceConfig {
+    pub api_root: String,
+}
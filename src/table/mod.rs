@@ -1,7 +1,8 @@
+use crate::arena::block::character::sheet::Sheet;
 use crate::arena::{block, component, ArenaMut, ArenaRef, BlockKind, BlockMut, BlockRef};
 use crate::libs::random_id::U128Id;
 use nusa::v_node::v_element::VEvent;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashSet;
 use std::rc::Rc;
 use wasm_bindgen::{prelude::*, JsCast};
@@ -29,6 +30,7 @@ pub struct Table {
     last_cursor_position: [f64; 2],
 
     is_reserve_rendering: bool,
+    rendering_generation: Rc<Cell<usize>>,
 
     updated_blocks: UpdatedBlocks,
 }
@@ -39,9 +41,9 @@ struct CameraState {
 }
 
 impl Table {
-    pub fn new() -> Self {
+    pub fn new(client_id: Rc<String>) -> Self {
         Self {
-            three: Rc::new(RefCell::new(Three::new())),
+            three: Rc::new(RefCell::new(Three::new(client_id))),
 
             is_2d_mode: false,
 
@@ -52,6 +54,7 @@ impl Table {
             tool_state: TableToolState::None,
             last_cursor_position: [0.0, 0.0],
             is_reserve_rendering: true,
+            rendering_generation: Rc::new(Cell::new(0)),
 
             updated_blocks: UpdatedBlocks {
                 insert: HashSet::new(),
@@ -83,24 +86,61 @@ impl Table {
         self.is_reserve_rendering = true;
     }
 
+    pub fn throw_dice(&mut self, message_id: U128Id, rands: &Vec<(u32, u32)>) {
+        self.three.borrow_mut().throw_dice(message_id, rands);
+        self.is_reserve_rendering = true;
+    }
+
     pub fn render_reserved(&mut self, world: BlockRef<block::World>) {
         if self.is_reserve_rendering {
             self.is_reserve_rendering = false;
-            let three = Rc::clone(&self.three);
-            let is_2d_mode = self.is_2d_mode;
+            self.rendering_generation
+                .set(self.rendering_generation.get().wrapping_add(1));
+
+            Self::request_rendering(
+                Rc::clone(&self.three),
+                Rc::clone(&self.rendering_generation),
+                self.is_2d_mode,
+                world,
+                0,
+            );
+        }
+    }
 
-            let a = Closure::once(Box::new(move || {
-                three
-                    .borrow_mut()
-                    .render(is_2d_mode, BlockRef::clone(&world));
-            }));
+    fn request_rendering(
+        three: Rc<RefCell<Three>>,
+        rendering_generation: Rc<Cell<usize>>,
+        is_2d_mode: bool,
+        world: BlockRef<block::World>,
+        delay: i32,
+    ) {
+        let generation = rendering_generation.get();
 
-            let _ = web_sys::window()
-                .unwrap()
-                .request_animation_frame(a.as_ref().unchecked_ref());
+        let a = Closure::once(Box::new(move || {
+            if rendering_generation.get() != generation {
+                return;
+            }
 
-            a.forget();
+            let next_rendering = three
+                .borrow_mut()
+                .render(is_2d_mode, BlockRef::clone(&world));
+
+            if let Some(delay) = next_rendering {
+                Self::request_rendering(three, rendering_generation, is_2d_mode, world, delay);
+            }
+        }));
+
+        let window = web_sys::window().unwrap();
+        if delay > 0 {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                a.as_ref().unchecked_ref(),
+                delay,
+            );
+        } else {
+            let _ = window.request_animation_frame(a.as_ref().unchecked_ref());
         }
+
+        a.forget();
     }
 
     pub fn reset_size(&mut self) {
@@ -274,22 +314,7 @@ impl Table {
         mouse_coord: &[f64; 2],
         option: &table_tool::Character,
     ) {
-        let table = unwrap!(Self::selecting_table(world.as_ref()));
-        let (p, n) = self
-            .three
-            .borrow_mut()
-            .get_focused_position(mouse_coord, &self.ignored_id());
-        let p = [
-            p[0] + n[0] / 128.0,
-            p[1] + n[1] / 128.0,
-            p[2] + n[2] / 128.0,
-        ];
-
-        let is_bind_to_grid = table
-            .map(|table| table.default_is_bind_to_grid())
-            .unwrap_or(true);
-
-        let p = Self::grid_position(is_bind_to_grid, &p);
+        let (p, is_bind_to_grid) = unwrap!(self.character_position(world.as_ref(), mouse_coord));
         let mut character = block::Character::new(is_bind_to_grid);
 
         character.set_size(option.size);
@@ -301,6 +326,12 @@ impl Table {
             option.texture.as_ref().map(|block| BlockRef::clone(block)),
         );
 
+        let properties = option
+            .template
+            .as_ref()
+            .map(|template| template.apply(&mut arena, &mut character))
+            .unwrap_or_default();
+
         let character = arena.insert(character);
         let character_id = character.id();
         world.update(|world| {
@@ -309,6 +340,64 @@ impl Table {
         self.reserve_rendering();
         self.updated_blocks.update.insert(world.id());
         self.updated_blocks.insert.insert(character_id);
+        self.updated_blocks.insert.extend(properties);
+    }
+
+    pub fn create_character_from_sheet(
+        &mut self,
+        mut arena: ArenaMut,
+        mut world: BlockMut<block::World>,
+        mouse_coord: &[f64; 2],
+        sheet: &Sheet,
+    ) -> Option<U128Id> {
+        let (p, is_bind_to_grid) = self.character_position(world.as_ref(), mouse_coord)?;
+        let mut character = block::Character::new(is_bind_to_grid);
+
+        character.set_position(p);
+        if let Some(name) = &sheet.name {
+            character.set_name(name.clone());
+            character.set_display_name((Some(name.clone()), None));
+        }
+
+        let properties = sheet.template.apply(&mut arena, &mut character);
+
+        let character = arena.insert(character);
+        let character_id = character.id();
+        world.update(|world| {
+            world.push_character(character);
+        });
+        self.reserve_rendering();
+        self.updated_blocks.update.insert(world.id());
+        self.updated_blocks
+            .insert
+            .insert(U128Id::clone(&character_id));
+        self.updated_blocks.insert.extend(properties);
+
+        Some(character_id)
+    }
+
+    // 駒を置く位置と、グリッドに吸着させるかどうか
+    fn character_position(
+        &self,
+        world: BlockRef<block::World>,
+        mouse_coord: &[f64; 2],
+    ) -> Option<([f64; 3], bool)> {
+        let table = Self::selecting_table(world)?;
+        let (p, n) = self
+            .three
+            .borrow_mut()
+            .get_focused_position(mouse_coord, &self.ignored_id());
+        let p = [
+            p[0] + n[0] / 128.0,
+            p[1] + n[1] / 128.0,
+            p[2] + n[2] / 128.0,
+        ];
+
+        let is_bind_to_grid = table
+            .map(|table| table.default_is_bind_to_grid())
+            .unwrap_or(true);
+
+        Some((Self::grid_position(is_bind_to_grid, &p), is_bind_to_grid))
     }
 
     pub fn create_craftboard(
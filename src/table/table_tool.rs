@@ -43,6 +43,7 @@ pub struct Character {
     pub tex_size: f64,
     pub color: crate::libs::color::Pallet,
     pub texture: Option<BlockRef<resource::ImageData>>,
+    pub template: Option<Rc<block::character::Template>>,
 }
 
 #[derive(Clone)]
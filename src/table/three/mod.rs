@@ -15,6 +15,7 @@ pub use raycaster::Raycaster;
 pub use texture_table::TextureTable;
 
 pub struct Three {
+    client_id: Rc<String>,
     canvas: Rc<web_sys::HtmlCanvasElement>,
     camera: Camera,
     raycaster: Raycaster,
@@ -23,6 +24,7 @@ pub struct Three {
     renderer: three::WebGLRenderer,
     object_boxblock: table_object::Boxblock,
     object_craftboard: table_object::Craftboard,
+    object_dice: table_object::Dice,
     object_character: table_object::Character,
     object_terran: table_object::Terran,
     object_textboard: table_object::Textboard,
@@ -37,7 +39,7 @@ struct CommonLight {
 }
 
 impl Three {
-    pub fn new() -> Self {
+    pub fn new(client_id: Rc<String>) -> Self {
         let canvas = web_sys::window()
             .unwrap()
             .document()
@@ -72,6 +74,7 @@ impl Three {
         let device_pixel_ratio = web_sys::window().unwrap().device_pixel_ratio();
 
         Self {
+            client_id,
             canvas,
             camera,
             raycaster,
@@ -80,6 +83,7 @@ impl Three {
             renderer,
             object_boxblock: table_object::Boxblock::new(),
             object_craftboard: table_object::Craftboard::new(),
+            object_dice: table_object::Dice::new(),
             object_character: table_object::Character::new(),
             object_terran: table_object::Terran::new(),
             object_textboard: table_object::Textboard::new(),
@@ -238,7 +242,19 @@ impl Three {
         &mut self.camera
     }
 
-    pub fn render(&mut self, is_2d_mode: bool, world: BlockRef<block::World>) {
+    pub fn throw_dice(&mut self, message_id: U128Id, rands: &Vec<(u32, u32)>) {
+        let center = [self.canvas_size[0] * 0.5, self.canvas_size[1] * 0.5];
+        let (center, _) = self.get_focused_position(&center, &U128Id::none());
+        self.object_dice.throw(
+            &mut self.texture_table,
+            &self.scene,
+            message_id,
+            rands,
+            &center,
+        );
+    }
+
+    pub fn render(&mut self, is_2d_mode: bool, world: BlockRef<block::World>) -> Option<i32> {
         let scene = world
             .map(|world| world.selecting_scene().as_ref())
             .unwrap_or(BlockRef::<block::Scene>::none());
@@ -278,13 +294,23 @@ impl Three {
             );
         });
 
-        world.map(|world| {
-            self.object_character.update(
-                &mut self.texture_table,
-                &self.scene,
-                world.characters().iter().map(|block| block.as_ref()),
-            );
-        });
+        let next_rendering = world
+            .map(|world| {
+                self.object_character.update(
+                    &mut self.texture_table,
+                    &self.scene,
+                    &self.client_id,
+                    world.characters().iter().map(|block| block.as_ref()),
+                    world.conditions(),
+                )
+            })
+            .unwrap_or(None);
+
+        let dice_rendering = self.object_dice.update(&self.scene);
+        let next_rendering = match (next_rendering, dice_rendering) {
+            (Some(x), Some(y)) => Some(x.min(y)),
+            (x, y) => x.or(y),
+        };
 
         self.camera
             .set_aspect(self.canvas_size[0] / self.canvas_size[1]);
@@ -297,5 +323,7 @@ impl Three {
         self.renderer.render(&self.scene, &self.camera);
 
         self.texture_table.update();
+
+        next_rendering
     }
 }
@@ -1,11 +1,17 @@
 use super::super::TextureTable;
 use super::util;
-use crate::arena::{block, resource, BlockRef};
+use crate::arena::{block, resource, BlockMut, BlockRef};
 use crate::libs::random_id::U128Id;
 use crate::libs::three;
 use std::collections::{HashMap, HashSet};
 use wasm_bindgen::JsCast;
 
+const SPEECH_FADE_OUT: f64 = 1.0;
+const GAUGE_HEIGHT: f64 = 0.12;
+const GAUGE_MARGIN: f64 = 0.04;
+const BADGE_HEIGHT: f64 = 0.25;
+const BADGE_MARGIN: f64 = 0.05;
+
 pub struct Character {
     meshs: HashMap<U128Id, Mesh>,
     geometry_border: util::BasicRoundedRectangleGeometry,
@@ -14,9 +20,12 @@ pub struct Character {
     geometry_nameplate: util::nameplate::XZGeometry,
     geometry_offset_value: util::nameplate::XZGeometry,
     geometry_offset_line: three::CylinderGeometry,
+    geometry_gauge: three::BoxGeometry,
+    geometry_badge: util::nameplate::XZGeometry,
     material_border: three::MeshBasicMaterial,
     material_base: three::MeshBasicMaterial,
     material_offset_line: three::LineBasicMaterial,
+    material_gauge_background: three::MeshBasicMaterial,
 }
 
 pub struct Mesh {
@@ -34,6 +43,15 @@ pub struct Mesh {
 
     nameplate: util::Nameplate,
     nameplate_id: (String, String),
+    nameplate_height: f64,
+
+    speech: util::Nameplate,
+    speech_text: String,
+    speech_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    speech_received_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    gauges: Vec<Gauge>,
+    badges: Vec<Badge>,
 
     color: crate::libs::color::Pallet,
 
@@ -42,10 +60,24 @@ pub struct Mesh {
     data: three::Group,
 }
 
+struct Gauge {
+    fill_material: three::MeshBasicMaterial,
+    fill: three::Mesh,
+    data: three::Group,
+}
+
+struct Badge {
+    plate: util::Nameplate,
+    label: String,
+    color: crate::libs::color::Pallet,
+    width: f64,
+}
+
 impl Character {
     pub fn new() -> Self {
         let color_border = crate::libs::color::Pallet::blue(7).to_color().to_f64array();
         let color_base = crate::libs::color::Pallet::blue(5).to_color().to_f64array();
+        let color_gauge = crate::libs::color::Pallet::gray(9).to_color().to_f64array();
         Self {
             meshs: HashMap::new(),
 
@@ -58,6 +90,8 @@ impl Character {
                 false,
             ),
             geometry_offset_line: Self::create_offset_line_geometry(),
+            geometry_gauge: three::BoxGeometry::new(1.0, 0.02, 1.0),
+            geometry_badge: util::nameplate::XZGeometry::new(0.5, false),
 
             material_border: three::MeshBasicMaterial::new(&object! {
                 "color": &three::Color::new(color_border[0], color_border[1], color_border[2])
@@ -70,6 +104,9 @@ impl Character {
             material_offset_line: three::LineBasicMaterial::new(&object! {
                 "color": &three::Color::new(color_base[0], color_base[1], color_base[2]),
             }),
+            material_gauge_background: three::MeshBasicMaterial::new(&object! {
+                "color": &three::Color::new(color_gauge[0], color_gauge[1], color_gauge[2])
+            }),
         }
     }
 
@@ -77,8 +114,12 @@ impl Character {
         &mut self,
         texture_table: &mut TextureTable,
         scene: &three::Scene,
+        client_id: &String,
         characters: impl Iterator<Item = BlockRef<block::Character>>,
-    ) {
+        conditions: &Vec<BlockMut<block::Condition>>,
+    ) -> Option<i32> {
+        let mut next_rendering: Option<i32> = None;
+        let now = chrono::Utc::now();
         let mut unused = self.meshs.keys().map(U128Id::clone).collect::<HashSet<_>>();
 
         for character in characters {
@@ -122,6 +163,10 @@ impl Character {
                     offset_border.set_user_data(&character_id.to_jsvalue());
                     offset_border.data().set_visible(false);
 
+                    let speech = util::Nameplate::new(&self.geometry_nameplate);
+                    speech.set_color(&crate::libs::color::Pallet::gray(0));
+                    speech.set_visible(false);
+
                     let offset_value = util::Nameplate::new(&self.geometry_offset_value);
                     offset_value.set_color(&crate::libs::color::Pallet::gray(9));
                     offset_value.set_visible(false);
@@ -133,6 +178,7 @@ impl Character {
                     data.add(&base_data);
                     data.add(&texture_data);
                     data.add(&nameplate);
+                    data.add(&speech);
                     data.add(&offset_line);
                     data.add(&offset_base);
                     data.add(offset_border.data());
@@ -152,6 +198,16 @@ impl Character {
                             texture_id: U128Id::none(),
                             nameplate,
                             nameplate_id: (String::from(""), String::from("")),
+                            nameplate_height: 0.0,
+                            speech,
+                            speech_text: String::new(),
+                            // 生成時に既にある発言は表示済みとして扱う
+                            speech_timestamp: character
+                                .speech()
+                                .map(|(_, timestamp)| timestamp.clone()),
+                            speech_received_at: None,
+                            gauges: vec![],
+                            badges: vec![],
                             color: character.color().clone(),
                             z_offset: 0.0,
                             data,
@@ -235,6 +291,7 @@ impl Character {
                     if *character.display_name() != mesh.nameplate_id {
                         if character.display_name().0 == "" && character.display_name().1 == "" {
                             mesh.nameplate.board().set_visible(false);
+                            mesh.nameplate_height = 0.0;
                         } else {
                             let texture = texture_table.load_text(character.display_name());
                             mesh.nameplate.text().set_alpha_map(Some(&texture.data));
@@ -247,11 +304,13 @@ impl Character {
                                 .scale()
                                 .set(texture_width, 1.0, texture_height);
                             mesh.nameplate.board().set_visible(true);
+                            mesh.nameplate_height = texture_height;
                         }
 
                         mesh.nameplate_id = character.display_name().clone();
                     }
 
+                    let mut nameplate_z = 0.0;
                     if let Some(texture_block) = texture_block {
                         let tex_height = character.tex_size() * s;
                         texture_block.map(|texture| {
@@ -261,11 +320,57 @@ impl Character {
                                 1.0,
                                 tex_height,
                             );
-                            mesh.nameplate.position().set(0.0, 0.0, tex_height + 0.1);
+                            nameplate_z = tex_height + 0.1;
                         });
-                    } else {
-                        mesh.nameplate.position().set(0.0, 0.0, 0.0);
                     }
+                    mesh.nameplate.position().set(0.0, 0.0, nameplate_z);
+
+                    let gauges_height = Self::update_gauges(
+                        &self.geometry_gauge,
+                        &self.material_gauge_background,
+                        mesh,
+                        &character.token_gauges(client_id),
+                        s,
+                        nameplate_z + mesh.nameplate_height + 0.35,
+                    );
+
+                    let badges = conditions
+                        .iter()
+                        .filter_map(|condition| {
+                            condition
+                                .map(|condition| {
+                                    if condition.character().id() == character_id {
+                                        Some((condition.label(), condition.color().clone()))
+                                    } else {
+                                        None
+                                    }
+                                })
+                                .unwrap_or(None)
+                        })
+                        .collect::<Vec<_>>();
+                    let badges_height = Self::update_badges(
+                        texture_table,
+                        &self.geometry_badge,
+                        mesh,
+                        &badges,
+                        nameplate_z + mesh.nameplate_height + 0.35 + gauges_height,
+                    );
+
+                    let speech_rendering = Self::update_speech(
+                        texture_table,
+                        mesh,
+                        character,
+                        &now,
+                        nameplate_z
+                            + mesh.nameplate_height * 1.5
+                            + 0.1
+                            + gauges_height
+                            + badges_height,
+                    );
+                    next_rendering = match (next_rendering, speech_rendering) {
+                        (Some(x), Some(y)) => Some(x.min(y)),
+                        (x, y) => x.or(y),
+                    };
                 }
             });
         }
@@ -273,8 +378,207 @@ impl Character {
         for unused_character_id in unused {
             if let Some(mesh) = self.meshs.remove(&unused_character_id) {
                 scene.remove(&mesh.data);
+                for gauge in &mesh.gauges {
+                    gauge.fill_material.dispose();
+                }
+                for badge in &mesh.badges {
+                    badge.plate.dispose();
+                }
+            }
+        }
+
+        next_rendering
+    }
+
+    fn update_gauges(
+        geometry: &three::BoxGeometry,
+        material_background: &three::MeshBasicMaterial,
+        mesh: &mut Mesh,
+        gauges: &Vec<block::property::TokenGauge>,
+        size: f64,
+        z: f64,
+    ) -> f64 {
+        while mesh.gauges.len() > gauges.len() {
+            if let Some(gauge) = mesh.gauges.pop() {
+                mesh.data.remove(&gauge.data);
+                // ジオメトリと背景のマテリアルは共有しているので、ゲージごとのマテリアルだけ破棄する
+                gauge.fill_material.dispose();
             }
         }
+
+        while mesh.gauges.len() < gauges.len() {
+            let color = Self::gauge_color(mesh.gauges.len())
+                .to_color()
+                .to_f64array();
+            let fill_material = three::MeshBasicMaterial::new(&object! {
+                "color": &three::Color::new(color[0], color[1], color[2])
+            });
+            let fill = three::Mesh::new(geometry, &fill_material);
+            fill.position().set(0.0, -0.01, 0.0);
+            let background = three::Mesh::new(geometry, material_background);
+
+            let data = three::Group::new();
+            data.add(&background);
+            data.add(&fill);
+            data.scale().set(size * 0.9, 1.0, GAUGE_HEIGHT);
+            mesh.data.add(&data);
+
+            mesh.gauges.push(Gauge {
+                fill_material,
+                fill,
+                data,
+            });
+        }
+
+        for (idx, (gauge, token_gauge)) in mesh.gauges.iter().zip(gauges.iter()).enumerate() {
+            let range = token_gauge.max - token_gauge.min;
+            let rate = if range > 0.0 {
+                ((token_gauge.value - token_gauge.min) / range).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            gauge.data.scale().set(size * 0.9, 1.0, GAUGE_HEIGHT);
+            gauge.data.position().set(
+                0.0,
+                0.0,
+                z + (GAUGE_HEIGHT + GAUGE_MARGIN) * idx as f64 + GAUGE_HEIGHT * 0.5,
+            );
+            gauge.fill.scale().set(rate.max(0.001), 1.0, 1.0);
+            gauge.fill.position().set((rate - 1.0) * 0.5, -0.01, 0.0);
+            gauge.fill_material.set_needs_update(true);
+        }
+
+        (GAUGE_HEIGHT + GAUGE_MARGIN) * gauges.len() as f64
+    }
+
+    fn update_badges(
+        texture_table: &mut TextureTable,
+        geometry: &util::nameplate::XZGeometry,
+        mesh: &mut Mesh,
+        badges: &Vec<(String, crate::libs::color::Pallet)>,
+        z: f64,
+    ) -> f64 {
+        while mesh.badges.len() > badges.len() {
+            if let Some(badge) = mesh.badges.pop() {
+                mesh.data.remove(&badge.plate);
+                badge.plate.dispose();
+            }
+        }
+
+        while mesh.badges.len() < badges.len() {
+            let plate = util::Nameplate::new(geometry);
+            mesh.data.add(&plate);
+            mesh.badges.push(Badge {
+                plate,
+                label: String::new(),
+                color: crate::libs::color::Pallet::gray(0),
+                width: 0.0,
+            });
+        }
+
+        for (badge, (label, color)) in mesh.badges.iter_mut().zip(badges.iter()) {
+            if badge.label != *label {
+                let texture = texture_table.load_text(&(label.clone(), String::new()));
+                badge.plate.text().set_alpha_map(Some(&texture.data));
+                badge.plate.text().set_needs_update(true);
+                badge.width = BADGE_HEIGHT * texture.size[0] / texture.size[1];
+                badge
+                    .plate
+                    .board()
+                    .scale()
+                    .set(badge.width, 1.0, BADGE_HEIGHT);
+                badge.label = label.clone();
+            }
+            if badge.color != *color {
+                badge.plate.set_color(color);
+                badge.color = color.clone();
+            }
+        }
+
+        if badges.is_empty() {
+            return 0.0;
+        }
+
+        let total_width = mesh.badges.iter().map(|badge| badge.width).sum::<f64>()
+            + BADGE_MARGIN * (mesh.badges.len() - 1) as f64;
+        let mut x = -total_width * 0.5;
+        for badge in &mesh.badges {
+            badge.plate.position().set(x + badge.width * 0.5, 0.0, z);
+            x += badge.width + BADGE_MARGIN;
+        }
+
+        BADGE_HEIGHT + BADGE_MARGIN * 2.0
+    }
+
+    fn gauge_color(idx: usize) -> crate::libs::color::Pallet {
+        match idx % 5 {
+            0 => crate::libs::color::Pallet::red(5),
+            1 => crate::libs::color::Pallet::blue(5),
+            2 => crate::libs::color::Pallet::green(5),
+            3 => crate::libs::color::Pallet::yellow(5),
+            _ => crate::libs::color::Pallet::purple(5),
+        }
+    }
+
+    fn update_speech(
+        texture_table: &mut TextureTable,
+        mesh: &mut Mesh,
+        character: &block::Character,
+        now: &chrono::DateTime<chrono::Utc>,
+        z: f64,
+    ) -> Option<i32> {
+        let (text, timestamp) = match character.speech() {
+            Some(speech) if character.speech_duration() > 0.0 => speech,
+            _ => {
+                mesh.speech.set_visible(false);
+                return None;
+            }
+        };
+
+        if mesh.speech_timestamp.as_ref() != Some(timestamp) {
+            mesh.speech_timestamp = Some(timestamp.clone());
+            mesh.speech_received_at = Some(now.clone());
+        }
+
+        let received_at = match &mesh.speech_received_at {
+            Some(received_at) => received_at,
+            None => {
+                mesh.speech.set_visible(false);
+                return None;
+            }
+        };
+
+        let elapsed = (*now - *received_at).num_milliseconds() as f64 / 1000.0;
+        let duration = character.speech_duration();
+        let (opacity, next_rendering) = if elapsed < duration {
+            (1.0, Some(((duration - elapsed) * 1000.0).ceil() as i32))
+        } else if elapsed < duration + SPEECH_FADE_OUT {
+            (1.0 - (elapsed - duration) / SPEECH_FADE_OUT, Some(0))
+        } else {
+            mesh.speech.set_visible(false);
+            return None;
+        };
+
+        if *text != mesh.speech_text {
+            let texture = texture_table.load_text(&(text.clone(), String::new()));
+            mesh.speech.text().set_alpha_map(Some(&texture.data));
+            mesh.speech.text().set_needs_update(true);
+
+            let s = character.size();
+            let texture_width = f64::min(s * 4.0, texture.size[0] * 0.4);
+            let texture_height = texture_width * texture.size[1] / texture.size[0];
+            mesh.speech
+                .board()
+                .scale()
+                .set(texture_width, 1.0, texture_height);
+            mesh.speech_text = text.clone();
+        }
+
+        mesh.speech.position().set(0.0, 0.0, z);
+        mesh.speech.set_opacity(opacity);
+        mesh.speech.set_visible(true);
+
+        next_rendering
     }
 
     fn create_texture_geometry() -> three::BufferGeometry {
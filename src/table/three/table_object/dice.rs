@@ -0,0 +1,514 @@
+use super::super::TextureTable;
+use super::util;
+use crate::libs::color::Pallet;
+use crate::libs::random_id::U128Id;
+use crate::libs::three;
+
+const DICE_SIZE: f64 = 1.0;
+const DICE_SPACING: f64 = 1.25;
+const DICE_COLUMNS: usize = 5;
+const DURATION_ROLL: f64 = 1.2;
+const DURATION_HOLD: f64 = 3.0;
+const DURATION_FADE: f64 = 0.5;
+
+pub struct Dice {
+    throws: Vec<Throw>,
+    polyhedron_d4: Polyhedron,
+    polyhedron_d6: Polyhedron,
+    polyhedron_d8: Polyhedron,
+    polyhedron_d10: Polyhedron,
+    polyhedron_d12: Polyhedron,
+    polyhedron_d20: Polyhedron,
+    geometry_label: three::PlaneGeometry,
+    geometry_value: util::nameplate::XZGeometry,
+}
+
+struct Polyhedron {
+    geometry: three::BufferGeometry,
+    faces: Vec<Face>,
+}
+
+struct Face {
+    center: [f64; 3],
+    normal: [f64; 3],
+    size: f64,
+}
+
+struct Throw {
+    message_id: U128Id,
+    timestamp: chrono::DateTime<chrono::Utc>,
+    dies: Vec<Die>,
+}
+
+struct Die {
+    from: [f64; 3],
+    to: [f64; 3],
+    spin: [f64; 3],
+    material: three::MeshStandardMaterial,
+    material_labels: Vec<three::MeshBasicMaterial>,
+    body: three::Group,
+    value: util::Nameplate,
+    data: three::Group,
+}
+
+enum Shape {
+    D4,
+    D6,
+    D8,
+    D10,
+    D12,
+    D20,
+}
+
+impl Dice {
+    pub fn new() -> Self {
+        let r = DICE_SIZE * 0.5;
+        let phi = (1.0 + 5.0_f64.sqrt()) * 0.5;
+
+        let tetrahedron = vec![
+            [1.0, 1.0, 1.0],
+            [-1.0, -1.0, 1.0],
+            [-1.0, 1.0, -1.0],
+            [1.0, -1.0, -1.0],
+        ];
+
+        let mut cube = vec![];
+        for x in [-1.0, 1.0] {
+            for y in [-1.0, 1.0] {
+                for z in [-1.0, 1.0] {
+                    cube.push([x, y, z]);
+                }
+            }
+        }
+
+        let mut octahedron = vec![];
+        for a in [-1.0, 1.0] {
+            octahedron.push([a, 0.0, 0.0]);
+            octahedron.push([0.0, a, 0.0]);
+            octahedron.push([0.0, 0.0, a]);
+        }
+
+        let mut icosahedron = vec![];
+        let mut dodecahedron = cube.clone();
+        for a in [-1.0, 1.0] {
+            for b in [-1.0, 1.0] {
+                icosahedron.push([0.0, a, b * phi]);
+                icosahedron.push([a, b * phi, 0.0]);
+                icosahedron.push([b * phi, 0.0, a]);
+                dodecahedron.push([0.0, a / phi, b * phi]);
+                dodecahedron.push([a / phi, b * phi, 0.0]);
+                dodecahedron.push([b * phi, 0.0, a / phi]);
+            }
+        }
+
+        Self {
+            throws: vec![],
+            polyhedron_d4: Polyhedron::new(Self::scale(tetrahedron, r * 1.3)),
+            polyhedron_d6: Polyhedron::new(Self::scale(cube, DICE_SIZE * 0.4 * 3.0_f64.sqrt())),
+            polyhedron_d8: Polyhedron::new(Self::scale(octahedron, r * 1.1)),
+            polyhedron_d10: Polyhedron::new(Self::d10_vertices(r)),
+            polyhedron_d12: Polyhedron::new(Self::scale(dodecahedron, r)),
+            polyhedron_d20: Polyhedron::new(Self::scale(icosahedron, r * 1.05)),
+            geometry_label: three::PlaneGeometry::new(1.0, 1.0),
+            geometry_value: util::nameplate::XZGeometry::new(0.5, false),
+        }
+    }
+
+    pub fn throw(
+        &mut self,
+        texture_table: &mut TextureTable,
+        scene: &three::Scene,
+        message_id: U128Id,
+        rands: &Vec<(u32, u32)>,
+        center: &[f64; 3],
+    ) {
+        // 同じメッセージを続けて受け取っても、振っている最中のダイスは重ねて振らない
+        if self
+            .throws
+            .iter()
+            .any(|throw| throw.message_id == message_id)
+        {
+            return;
+        }
+
+        let faces = rands
+            .iter()
+            .map(|(sides, value)| match sides {
+                100 => vec![
+                    (
+                        10,
+                        format!("{:02}", (value / 10) % 10 * 10),
+                        (0..10).map(|x| format!("{:02}", x * 10)).collect(),
+                    ),
+                    (
+                        10,
+                        format!("{}", value % 10),
+                        (0..10).map(|x| x.to_string()).collect(),
+                    ),
+                ],
+                _ => vec![(
+                    *sides,
+                    value.to_string(),
+                    (1..=*sides).map(|x| x.to_string()).collect::<Vec<_>>(),
+                )],
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let num = faces.len();
+        let rows = (num + DICE_COLUMNS - 1) / DICE_COLUMNS;
+        let mut dies = vec![];
+
+        for (idx, (sides, value, labels)) in faces.into_iter().enumerate() {
+            let columns = (num - idx / DICE_COLUMNS * DICE_COLUMNS).min(DICE_COLUMNS);
+            let col = (idx % DICE_COLUMNS) as f64;
+            let row = (idx / DICE_COLUMNS) as f64;
+            let to = [
+                center[0] + (col - (columns as f64 - 1.0) * 0.5) * DICE_SPACING,
+                center[1] - (row - (rows as f64 - 1.0) * 0.5) * DICE_SPACING,
+                center[2] + DICE_SIZE * 0.5,
+            ];
+            let from = [
+                to[0] - 4.0 + js_sys::Math::random() * 2.0,
+                to[1] - 5.0 + js_sys::Math::random() * 2.0,
+                to[2] + 4.0,
+            ];
+            let spin = [
+                (4.0 + js_sys::Math::random() * 4.0) * std::f64::consts::PI,
+                (4.0 + js_sys::Math::random() * 4.0) * std::f64::consts::PI,
+                (2.0 + js_sys::Math::random() * 2.0) * std::f64::consts::PI,
+            ];
+
+            let color = Pallet::gray(0).to_color();
+            let [r, g, b, ..] = color.to_f64array();
+            let material = three::MeshStandardMaterial::new(&object! {
+                "flatShading": true,
+                "transparent": true
+            });
+            material.color().set_rgb(r, g, b);
+
+            let polyhedron = self.polyhedron(&Shape::from_sides(sides));
+            let mesh = three::Mesh::new(&polyhedron.geometry, &material);
+
+            let body = three::Group::new();
+            body.add(&mesh);
+
+            // 出目の面を上に向けて止まるように、残りの面には他の目を順に割り振る
+            let top = (js_sys::Math::random() * polyhedron.faces.len() as f64) as usize
+                % polyhedron.faces.len();
+            let mut others = labels.iter().filter(|label| **label != value).cycle();
+            let mut material_labels = vec![];
+            for (face_idx, face) in polyhedron.faces.iter().enumerate() {
+                let label = if face_idx == top {
+                    &value
+                } else {
+                    others.next().unwrap_or(&value)
+                };
+                let texture = texture_table.load_text(&(label.clone(), String::new()));
+                let material_label = three::MeshBasicMaterial::new(&object! {
+                    "transparent": true
+                });
+                material_label.set_alpha_map(Some(&texture.data));
+                material_label.color().set_rgb(0.0, 0.0, 0.0);
+
+                let height = face.size;
+                let width = height * texture.size[0] / texture.size[1];
+                let fit = (face.size * 1.4 / width).min(1.0);
+                let mesh_label = three::Mesh::new(&self.geometry_label, &material_label);
+                mesh_label.scale().set(width * fit, height * fit, 1.0);
+                mesh_label.position().set(
+                    face.center[0] + face.normal[0] * 0.005,
+                    face.center[1] + face.normal[1] * 0.005,
+                    face.center[2] + face.normal[2] * 0.005,
+                );
+                mesh_label.look_at(
+                    face.center[0] + face.normal[0],
+                    face.center[1] + face.normal[1],
+                    face.center[2] + face.normal[2],
+                );
+                mesh.add(&mesh_label);
+                material_labels.push(material_label);
+            }
+
+            let normal = &polyhedron.faces[top].normal;
+            mesh.quaternion().set_from_unit_vectors(
+                &three::Vector3::new(normal[0], normal[1], normal[2]),
+                &three::Vector3::new(0.0, 0.0, 1.0),
+            );
+
+            let value_plate = util::Nameplate::new(&self.geometry_value);
+            let texture = texture_table.load_text(&(value, String::new()));
+            value_plate.text().set_alpha_map(Some(&texture.data));
+            value_plate.text().set_needs_update(true);
+            let height = DICE_SIZE * 0.5;
+            value_plate.board().scale().set(
+                height * texture.size[0] / texture.size[1],
+                1.0,
+                height,
+            );
+            value_plate.set_color(&Pallet::gray(9));
+            value_plate.position().set(0.0, 0.0, DICE_SIZE * 0.6);
+            value_plate.set_visible(false);
+
+            let data = three::Group::new();
+            data.add(&body);
+            data.add(&value_plate);
+            data.position().set(from[0], from[1], from[2]);
+            scene.add(&data);
+
+            dies.push(Die {
+                from,
+                to,
+                spin,
+                material,
+                material_labels,
+                body,
+                value: value_plate,
+                data,
+            });
+        }
+
+        self.throws.push(Throw {
+            message_id,
+            timestamp: chrono::Utc::now(),
+            dies,
+        });
+    }
+
+    pub fn update(&mut self, scene: &three::Scene) -> Option<i32> {
+        let now = chrono::Utc::now();
+        let mut next_rendering = None;
+
+        self.throws.retain(|throw| {
+            let elapsed = (now - throw.timestamp).num_milliseconds() as f64 / 1000.0;
+            if elapsed >= DURATION_ROLL + DURATION_HOLD + DURATION_FADE {
+                for die in &throw.dies {
+                    scene.remove(&die.data);
+                    die.material.dispose();
+                    for material_label in &die.material_labels {
+                        material_label.dispose();
+                    }
+                    die.value.dispose();
+                }
+                return false;
+            }
+
+            for die in &throw.dies {
+                Self::update_die(die, elapsed);
+            }
+
+            next_rendering = if elapsed < DURATION_ROLL || elapsed >= DURATION_ROLL + DURATION_HOLD
+            {
+                Some(0)
+            } else {
+                let rest = ((DURATION_ROLL + DURATION_HOLD - elapsed) * 1000.0).ceil() as i32;
+                Some(next_rendering.map(|x: i32| x.min(rest)).unwrap_or(rest))
+            };
+
+            true
+        });
+
+        next_rendering
+    }
+
+    fn update_die(die: &Die, elapsed: f64) {
+        let t = (elapsed / DURATION_ROLL).min(1.0);
+        let p = 1.0 - (1.0 - t).powi(3);
+        let bounce = (1.0 - t).powi(2) * (3.0 * std::f64::consts::PI * t).cos().abs();
+
+        die.data.position().set(
+            die.from[0] + (die.to[0] - die.from[0]) * p,
+            die.from[1] + (die.to[1] - die.from[1]) * p,
+            die.to[2] + (die.from[2] - die.to[2]) * bounce,
+        );
+
+        let rotation = die.body.rotation();
+        rotation.set_x(die.spin[0] * (1.0 - p));
+        rotation.set_y(die.spin[1] * (1.0 - p));
+        rotation.set_z(die.spin[2] * (1.0 - p));
+
+        die.value.set_visible(t >= 1.0);
+
+        let fading = elapsed - DURATION_ROLL - DURATION_HOLD;
+        let opacity = if fading > 0.0 {
+            (1.0 - fading / DURATION_FADE).max(0.0)
+        } else {
+            1.0
+        };
+        die.material.set_opacity(opacity);
+        die.material.set_needs_update(true);
+        for material_label in &die.material_labels {
+            material_label.set_opacity(opacity);
+            material_label.set_needs_update(true);
+        }
+        die.value.set_opacity(opacity);
+    }
+
+    fn polyhedron(&self, shape: &Shape) -> &Polyhedron {
+        match shape {
+            Shape::D4 => &self.polyhedron_d4,
+            Shape::D6 => &self.polyhedron_d6,
+            Shape::D8 => &self.polyhedron_d8,
+            Shape::D10 => &self.polyhedron_d10,
+            Shape::D12 => &self.polyhedron_d12,
+            Shape::D20 => &self.polyhedron_d20,
+        }
+    }
+
+    fn scale(vertices: Vec<[f64; 3]>, radius: f64) -> Vec<[f64; 3]> {
+        vertices
+            .into_iter()
+            .map(|v| scale(&normalize(&v), radius))
+            .collect()
+    }
+
+    // 凧形の面が平面になるように、赤道の頂点の高さを頂点の高さから決める
+    fn d10_vertices(r: f64) -> Vec<[f64; 3]> {
+        let apex = r * 1.1;
+        let c = (std::f64::consts::PI / 5.0).cos();
+        let z = apex * (1.0 - c) / (1.0 + c);
+
+        let mut vertices = vec![[0.0, 0.0, apex], [0.0, 0.0, -apex]];
+        for i in 0..10 {
+            let a = i as f64 * std::f64::consts::PI / 5.0;
+            let z = if i % 2 == 0 { z } else { -z };
+            vertices.push([r * a.cos(), r * a.sin(), z]);
+        }
+        vertices
+    }
+}
+
+impl Polyhedron {
+    // 原点を囲む凸多面体の頂点から面を求める
+    fn new(vertices: Vec<[f64; 3]>) -> Self {
+        let eps = 1e-6;
+        let mut normals: Vec<[f64; 3]> = vec![];
+
+        for i in 0..vertices.len() {
+            for j in (i + 1)..vertices.len() {
+                for k in (j + 1)..vertices.len() {
+                    let n = cross(
+                        &sub(&vertices[j], &vertices[i]),
+                        &sub(&vertices[k], &vertices[i]),
+                    );
+                    if dot(&n, &n) < eps {
+                        continue;
+                    }
+                    let mut n = normalize(&n);
+                    if dot(&n, &vertices[i]) < 0.0 {
+                        n = scale(&n, -1.0);
+                    }
+                    let d = dot(&n, &vertices[i]);
+                    if vertices.iter().all(|v| dot(&n, v) <= d + eps)
+                        && normals.iter().all(|m| dot(m, &n) < 1.0 - eps)
+                    {
+                        normals.push(n);
+                    }
+                }
+            }
+        }
+
+        let mut points = vec![];
+        let mut faces = vec![];
+
+        for normal in normals {
+            let d = vertices
+                .iter()
+                .map(|v| dot(&normal, v))
+                .fold(f64::MIN, f64::max);
+            let mut face = vertices
+                .iter()
+                .filter(|v| dot(&normal, v) > d - eps)
+                .collect::<Vec<_>>();
+            let center = scale(
+                &face.iter().fold([0.0, 0.0, 0.0], |c, v| add(&c, v)),
+                1.0 / face.len() as f64,
+            );
+            let u = normalize(&sub(face[0], &center));
+            let w = cross(&normal, &u);
+            let angle = |v: &[f64; 3]| {
+                let v = sub(v, &center);
+                dot(&v, &w).atan2(dot(&v, &u))
+            };
+            face.sort_by(|a, b| {
+                angle(a)
+                    .partial_cmp(&angle(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+
+            for i in 1..(face.len() - 1) {
+                for v in [face[0], face[i], face[i + 1]] {
+                    points.extend(v.iter().map(|x| *x as f32));
+                }
+            }
+
+            let size = (0..face.len())
+                .map(|i| {
+                    let m = scale(&add(face[i], face[(i + 1) % face.len()]), 0.5);
+                    let m = sub(&m, &center);
+                    dot(&m, &m).sqrt()
+                })
+                .fold(f64::MAX, f64::min);
+
+            faces.push(Face {
+                center,
+                normal,
+                size,
+            });
+        }
+
+        let geometry = three::BufferGeometry::new();
+        geometry.set_attribute(
+            "position",
+            &three::BufferAttribute::new_with_f32array(
+                &js_sys::Float32Array::from(points.as_slice()),
+                3,
+                false,
+            ),
+        );
+        geometry.compute_vertex_normals();
+
+        Self { geometry, faces }
+    }
+}
+
+fn add(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: &[f64; 3], k: f64) -> [f64; 3] {
+    [a[0] * k, a[1] * k, a[2] * k]
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(a: &[f64; 3]) -> [f64; 3] {
+    scale(a, 1.0 / dot(a, a).sqrt())
+}
+
+impl Shape {
+    fn from_sides(sides: u32) -> Self {
+        match sides {
+            0..=4 => Self::D4,
+            5..=6 => Self::D6,
+            7..=8 => Self::D8,
+            9..=10 => Self::D10,
+            11..=12 => Self::D12,
+            _ => Self::D20,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 pub mod boxblock;
 pub mod character;
 pub mod craftboard;
+pub mod dice;
 pub mod terran;
 pub mod textboard;
 mod util;
@@ -8,6 +9,7 @@ mod util;
 pub use boxblock::Boxblock;
 pub use character::Character;
 pub use craftboard::Craftboard;
+pub use dice::Dice;
 pub use terran::Terran;
 pub use textboard::Textboard;
 
@@ -114,6 +114,19 @@ impl Nameplate {
         self.text().set_needs_update(true);
     }
 
+    pub fn set_opacity(&self, opacity: f64) {
+        self.material_text.set_opacity(opacity);
+        self.material_background.set_transparent(opacity < 1.0);
+        self.material_background.set_opacity(opacity);
+        self.material_text.set_needs_update(true);
+        self.material_background.set_needs_update(true);
+    }
+
+    pub fn dispose(&self) {
+        self.material_text.dispose();
+        self.material_background.dispose();
+    }
+
     pub fn set_user_data(&self, data: &JsValue) {
         self.mesh_front.set_user_data(data);
         self.mesh_back.set_user_data(data);